@@ -7,7 +7,7 @@ use photon::materials::{Diffusive, Reflective, RefractionIndex, Refractive};
 use photon::textures::Constant;
 use photon::things::Things;
 use photon::transforms::{AffineTransformation, Linear, Translation};
-use photon::viewing::{Camera, Exposure, Lens, Sensor};
+use photon::viewing::{Camera, Exposure, Lens, Perspective, Sensor};
 use photon::worlds::World;
 
 struct Sky;
@@ -23,6 +23,7 @@ impl World for Sky {
 
 pub fn main() {
     let camera = Camera {
+        projection: Perspective,
         lens: Lens::ideal(1.0),
         sensor: Sensor::new(960, 720, 1.0),
         exposure: Exposure(0.0),
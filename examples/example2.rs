@@ -5,13 +5,14 @@ use photon::basic::matrices::Matrix;
 use photon::basic::rays::Ray;
 use photon::basic::vectors::{Dot, Vec3D};
 use photon::builders::Building;
-use photon::geometries::{Geometry, Hit, Sphere};
-use photon::materials::{Diffusive, Emissive, Material, MaterialHolder, Reflective, RefractionIndex, Refractive};
-use photon::noise::{Fractal, Noise, Simple};
-use photon::textures::{Constant, Texture};
+use photon::geometries::Sphere;
+use photon::materials::{Diffusive, Emissive, Reflective, RefractionIndex, Refractive};
+use photon::noise::{Fractal, Simple};
+use photon::textures::procedural::{Checker, Domain, Procedural};
+use photon::textures::Constant;
 use photon::things::Things;
 use photon::transforms::{AffineTransformation, Linear, Translation};
-use photon::viewing::{Camera, Exposure, Lens, Sensor};
+use photon::viewing::{Camera, Exposure, Lens, Perspective, Sensor};
 use photon::worlds::World;
 
 struct Sky;
@@ -29,59 +30,6 @@ impl World for Sky {
 
 }
 
-struct CheckerBoard<W: Material, B: Material>(W, B);
-
-impl<W: Material, B: Material> Texture for CheckerBoard<W, B> {
-
-    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
-        let Self(w, b) = self;
-        let point = geometry.surface_coordinates(&hit.local_hit().incident_ray.origin);
-        let x = (5.0 * point.x() + 0.5).floor() as i32;
-        let y = (5.0 * point.y() + 0.0).floor() as i32;
-        MaterialHolder::Borrowing(if (x + y) & 1 == 0 { b } else { w })
-    }
-
-}
-
-struct PlanetCrust {
-    noise: Fractal<Simple>,
-    land: Color,
-    sea: Color,
-    sea_level: f64,
-    detail: f64
-}
-
-impl Texture for PlanetCrust {
-
-    fn material<'a>(&'a self, hit: &'a Hit, _: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
-        let noise = self.noise.value_at(&(hit.incident_ray.origin * self.detail)) * 2.0;
-        let level = ((noise - noise.floor()) * 2.0 - 1.0).abs();
-        let smooth_level = level * level * (3.0 - 2.0 * level);
-        MaterialHolder::Owning(if smooth_level > self.sea_level {
-            Box::new(Diffusive(smooth_level * self.land))
-        } else {
-            Box::new(Reflective(self.sea))
-        })
-    }
-
-}
-
-struct Woody {
-    noise: Fractal<Simple>,
-    color: Color,
-    freq: f64,
-    detail: f64
-}
-
-impl Texture for Woody {
-
-    fn material<'a>(&'a self, hit: &'a Hit, _: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
-        let noise = self.noise.value_at(&(hit.incident_ray.origin * self.detail)) * self.freq;
-        MaterialHolder::Owning(Box::new(Diffusive(noise.fract() * self.color)))
-    }
-
-}
-
 fn fractal_noise(depth: u8) -> Fractal<Simple> {
     let s = SQRT_2;
     Fractal::new(
@@ -96,6 +44,7 @@ fn fractal_noise(depth: u8) -> Fractal<Simple> {
 pub fn main() {
     let distance = 100.0;
     let camera = Camera {
+        projection: Perspective,
         lens: Lens::ideal(30.0),
         sensor: Sensor::new(960, 720, 1.0),
         exposure: Exposure(0.0),
@@ -109,34 +58,31 @@ pub fn main() {
         Building(Sphere)
             .transformed(Linear::scaling(2.0, 2.0, 2.0)
                 .then_displacement_of(-1.0, -1.0, -1.0))
-            .with_texture(Woody {
-                noise: fractal_noise(4),
-                color: Color::new(0.2, 0.4, 0.8),
-                freq: 32.0,
-                detail: 0.5
-            })
+            .with_texture(Procedural::wood(fractal_noise(4), Color::new(0.2, 0.4, 0.8), 32.0, 0.5))
             .boxed(),
         Building(Sphere)
             .transformed(Linear::scaling(2.0, 1.0, 2.0)
                 .then_rotation(&Vec3D::new(1.0, 0.0, -1.0), -PI/6.0)
                 .then_displacement_of(-2.0, 2.0, -1.0)
             )
-            .with_texture(CheckerBoard(
-                Diffusive(Color::new(0.8, 0.4, 0.2)),
-                Reflective(Color::new(1.0, 1.0, 0.1))
-            ))
+            .with_texture(Procedural::new(
+                Checker { scale: 5.0, offset: Vec3D::new(0.5, 0.0, 0.0) },
+                Simple,
+                Reflective(Color::new(1.0, 1.0, 0.1)),
+                Diffusive(Color::new(0.8, 0.4, 0.2))
+            ).in_domain(Domain::Surface))
             .boxed(),
         Building(Sphere)
             .transformed(Linear::scaling(2.0, 3.0, 2.0)
                 .then_rotation(&Vec3D::new(2.0, 0.0, 1.0), -PI/6.0)
                 .then_displacement_of(3.0, 0.0, -8.0))
-            .with_texture(PlanetCrust {
-                noise: fractal_noise(16),
-                land: Color::new(0.4, 0.8, 0.2),
-                sea: Color::new(0.1, 1.0, 1.0),
-                sea_level: 0.5,
-                detail: 0.5
-            })
+            .with_texture(Procedural::planet_crust(
+                fractal_noise(16),
+                Color::new(0.4, 0.8, 0.2),
+                Color::new(0.1, 1.0, 1.0),
+                0.5,
+                0.5
+            ))
             .boxed(),
         Building(Sphere)
             .transformed(Linear::scaling(10.0, 10.0, 10.0)
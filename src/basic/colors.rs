@@ -1,4 +1,4 @@
-use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Sub};
 
 use image::Rgb;
 
@@ -111,6 +111,31 @@ impl Color {
         )
     }
 
+    /// Returns a distinct, fully saturated color for each non-zero identifier, and black for zero.
+    /// It is useful for visualizing object and material identifiers.
+    pub fn false_color(id: u32) -> Self {
+        if id == 0 {
+            return Self::BLACK
+        }
+        let hue = ((id as f64) * 0.618033988749895).fract() * 6.0;
+        let x = 1.0 - (hue % 2.0 - 1.0).abs();
+        match hue as u8 {
+            0 => Self::new(1.0, x, 0.0),
+            1 => Self::new(x, 1.0, 0.0),
+            2 => Self::new(0.0, 1.0, x),
+            3 => Self::new(0.0, x, 1.0),
+            4 => Self::new(x, 0.0, 1.0),
+            _ => Self::new(1.0, 0.0, x),
+        }
+    }
+
+    /// Returns a color going from black, through red and yellow, to white, as the given value goes
+    /// from 0 to 1. It is useful for visualizing quantities, like distances, as heat maps.
+    pub fn heat(value: f64) -> Self {
+        let value = 3.0 * value.clamp(0.0, 1.0);
+        Self::new(value.clamp(0.0, 1.0), (value - 1.0).clamp(0.0, 1.0), (value - 2.0).clamp(0.0, 1.0))
+    }
+
     pub fn as_rgb(&self) -> Rgb<u8> {
         Rgb(self.components.map(|c| (c * 255.0).round() as u8))
     }
@@ -123,6 +148,14 @@ impl Color {
         )
     }
 
+    pub fn minus(&self, rhs: &Self) -> Self {
+        Self::new(
+            self[0] - rhs[0],
+            self[1] - rhs[1],
+            self[2] - rhs[2],
+        )
+    }
+
     pub fn times(&self, rhs: f64) -> Self {
         Self::new(
             self[0] * rhs,
@@ -196,6 +229,26 @@ impl Add for Color {
 
 }
 
+impl Sub for &Color {
+
+    type Output = Color;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.minus(rhs)
+    }
+
+}
+
+impl Sub for Color {
+
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.minus(&rhs)
+    }
+
+}
+
 impl MulAssign<f64> for Color {
 
     fn mul_assign(&mut self, rhs: f64) {
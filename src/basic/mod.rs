@@ -5,3 +5,4 @@ pub mod colors;
 pub mod vectors;
 pub mod matrices;
 pub mod rays;
+pub mod spectra;
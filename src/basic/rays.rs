@@ -1,18 +1,22 @@
 use crate::basic::colors::Color;
 use crate::basic::vectors::Vec3D;
 
+/// A ray of light, traced backwards from the camera. In spectral rendering, rays carry the
+/// wavelength, in nanometers, of the light they trace, which wavelength dependent materials, like
+/// dispersive ones, take into account.
 #[derive(Clone, Debug)]
 pub struct Ray {
     pub origin: Vec3D,
     pub direction: Vec3D,
     pub color: Color,
-    pub time: f64
+    pub time: f64,
+    pub wavelength: Option<f64>
 }
 
 impl Ray {
 
     pub fn new(origin: Vec3D, direction: Vec3D, color: Color, time: f64) -> Self {
-        Self { origin, direction, color, time }
+        Self { origin, direction, color, time, wavelength: None }
     }
 
     pub fn at(&self, distance: f64) -> Vec3D {
@@ -20,19 +24,23 @@ impl Ray {
     }
 
     pub fn with_origin(&self, origin: Vec3D) -> Self {
-        Self::new(origin, self.direction, self.color, self.time)
+        Self { origin, ..*self }
     }
 
     pub fn with_direction(&self, direction: Vec3D) -> Self {
-        Self::new(self.origin, direction, self.color, self.time)
+        Self { direction, ..*self }
     }
 
     pub fn with_origin_and_direction(&self, origin: Vec3D, direction: Vec3D) -> Self {
-        Self::new(origin, direction, self.color, self.time)
+        Self { origin, direction, ..*self }
     }
 
     pub fn with_color(&self, color: Color) -> Self {
-        Self::new(self.origin, self.direction, color, self.time)
+        Self { color, ..*self }
     }
 
-}
\ No newline at end of file
+    pub fn with_wavelength(&self, wavelength: Option<f64>) -> Self {
+        Self { wavelength, ..*self }
+    }
+
+}
@@ -0,0 +1,214 @@
+use std::cell::Cell;
+use std::sync::LazyLock;
+
+use crate::basic::colors::Color;
+use crate::basic::matrices::Matrix;
+use crate::basic::vectors::Vec3D;
+
+/// The shortest wavelength, in nanometers, considered in spectral rendering.
+pub const SHORTEST_WAVELENGTH: f64 = 380.0;
+
+/// The longest wavelength, in nanometers, considered in spectral rendering.
+pub const LONGEST_WAVELENGTH: f64 = 720.0;
+
+const SPECTRUM_WIDTH: f64 = LONGEST_WAVELENGTH - SHORTEST_WAVELENGTH;
+
+thread_local! {
+    static SECONDARIES_TERMINATED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A set of wavelengths, in nanometers, sampled according to hero wavelength sampling: A hero
+/// wavelength is picked uniformly from the visible spectrum, and the others are evenly spaced after
+/// it, wrapping around the spectrum. All the wavelengths share the path traced along the hero
+/// wavelength, unless that path goes through a wavelength dependent event, like dispersion. There
+/// is one wavelength per color component, in which the radiance at that wavelength is carried.
+///
+/// Example:
+/// ```
+/// # use photon::basic::spectra::{Wavelengths, SHORTEST_WAVELENGTH, LONGEST_WAVELENGTH};
+/// # use photon::rough_equality;
+///
+/// let wavelengths = Wavelengths::sample(0.875);
+/// assert!(rough_equality(wavelengths.hero(), 677.5));
+/// assert!(rough_equality(wavelengths.values()[1], 450.83333333333337));
+/// assert!(wavelengths.values().iter().all(|&w| w >= SHORTEST_WAVELENGTH && w < LONGEST_WAVELENGTH));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Wavelengths([f64; 3]);
+
+impl Wavelengths {
+
+    /// Samples the wavelengths, where the hero wavelength is at the given fraction (in `[0, 1)`) of
+    /// the visible spectrum.
+    pub fn sample(fraction: f64) -> Self {
+        Self(std::array::from_fn(|i| {
+            let fraction = (fraction + (i as f64) / 3.0).fract();
+            SHORTEST_WAVELENGTH + fraction * SPECTRUM_WIDTH
+        }))
+    }
+
+    /// Returns the wavelengths sampled along with the given hero wavelength.
+    pub fn of(hero: f64) -> Self {
+        Self::sample(((hero - SHORTEST_WAVELENGTH) / SPECTRUM_WIDTH).clamp(0.0, 1.0).fract())
+    }
+
+    pub fn hero(&self) -> f64 {
+        let Self(ref values) = self;
+        values[0]
+    }
+
+    pub fn values(&self) -> &[f64; 3] {
+        let Self(ref values) = self;
+        values
+    }
+
+    /// Traces, using the given function, the radiance carried along the hero wavelength, and returns
+    /// the color the film perceives out of it at all the wavelengths. The traced radiance holds, in
+    /// each color component, the radiance at the corresponding wavelength (see `seen_at`). If the
+    /// traced path terminates the secondary wavelengths, only the hero one is accounted for.
+    pub fn observe<F: FnOnce(f64) -> Color>(&self, trace: F) -> Color {
+        let Self(ref values) = self;
+        SECONDARIES_TERMINATED.with(|terminated| terminated.set(false));
+        let radiance = trace(self.hero());
+        let count = if SECONDARIES_TERMINATED.with(|terminated| terminated.replace(false)) { 1 } else { values.len() };
+        let xyz = values[..count].iter().enumerate()
+            .map(|(i, &wavelength)| color_matching(wavelength) * radiance[i])
+            .fold(Vec3D::zero(), |sum, xyz| sum + xyz);
+        xyz_to_color(&(xyz * (SPECTRUM_WIDTH / count as f64)))
+    }
+
+}
+
+/// Returns the given color, be it a reflectance or a radiance, as seen by a ray of the given
+/// wavelength, if any. Rays traced at a hero wavelength see, in each color component, the value of
+/// the upsampled spectrum of the color at the corresponding wavelength (see `Wavelengths`), so that
+/// colors get multiplied as spectra along paths. Other rays see colors as they are.
+pub fn seen_at(color: &Color, wavelength: Option<f64>) -> Color {
+    match wavelength {
+        Some(hero) => {
+            let Wavelengths(values) = Wavelengths::of(hero);
+            Color::new(upsampled(color, values[0]), upsampled(color, values[1]), upsampled(color, values[2]))
+        }
+        None => *color,
+    }
+}
+
+/// Signals that the path being traced went through a wavelength dependent event, making it valid
+/// only for the hero wavelength. Materials that behave differently for different wavelengths must
+/// call this whenever they make use of the wavelength of a ray.
+pub fn terminate_secondary_wavelengths() {
+    SECONDARIES_TERMINATED.with(|terminated| terminated.set(true));
+}
+
+/// Returns the value, at the given wavelength, of a smooth spectrum whose color is the given one,
+/// using the upsampling method of Smits ("An RGB-to-Spectrum Conversion for Reflectances", 1999).
+pub fn upsampled(color: &Color, wavelength: f64) -> f64 {
+    let (r, g, b) = (color.red(), color.green(), color.blue());
+    let at = |spectrum: &[f64; 10]| sampled(spectrum, wavelength);
+    if r <= g && r <= b {
+        r * at(&WHITE) + if g <= b {
+            (g - r) * at(&CYAN) + (b - g) * at(&BLUE)
+        } else {
+            (b - r) * at(&CYAN) + (g - b) * at(&GREEN)
+        }
+    } else if g <= r && g <= b {
+        g * at(&WHITE) + if r <= b {
+            (r - g) * at(&MAGENTA) + (b - r) * at(&BLUE)
+        } else {
+            (b - g) * at(&MAGENTA) + (r - b) * at(&RED)
+        }
+    } else {
+        b * at(&WHITE) + if r <= g {
+            (r - b) * at(&YELLOW) + (g - r) * at(&GREEN)
+        } else {
+            (g - b) * at(&YELLOW) + (r - g) * at(&RED)
+        }
+    }
+}
+
+/// Returns the CIE 1931 color matching functions at the given wavelength, using the multi-lobe
+/// Gaussian fit of Wyman, Sloan, and Shirley ("Simple Analytic Approximations to the CIE XYZ Color
+/// Matching Functions", 2013).
+pub fn color_matching(wavelength: f64) -> Vec3D {
+    let lobe = |mean: f64, left_deviation: f64, right_deviation: f64| {
+        let t = (wavelength - mean) / if wavelength < mean { left_deviation } else { right_deviation };
+        (-0.5 * t * t).exp()
+    };
+    Vec3D::new(
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7) - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+/// Converts the given XYZ tristimulus values to linear sRGB, white balanced such that a flat unit
+/// spectrum is white.
+pub fn xyz_to_color(xyz: &Vec3D) -> Color {
+    let rgb = &*XYZ_TO_RGB * xyz;
+    Color::new(rgb.x(), rgb.y(), rgb.z())
+}
+
+static XYZ_TO_RGB: LazyLock<Matrix> = LazyLock::new(|| {
+    let srgb = Matrix::new(
+        &Vec3D::new(3.2404542, -0.9692660, 0.0556434),
+        &Vec3D::new(-1.5371385, 1.8760108, -0.2040259),
+        &Vec3D::new(-0.4985314, 0.0415560, 1.0572252),
+    );
+    let steps = 1000;
+    let white_xyz = (0 .. steps)
+        .map(|i| color_matching(SHORTEST_WAVELENGTH + (i as f64 + 0.5) * SPECTRUM_WIDTH / steps as f64))
+        .fold(Vec3D::zero(), |sum, xyz| sum + xyz) * (SPECTRUM_WIDTH / steps as f64);
+    let white = &srgb * &white_xyz;
+    &Matrix::diagonal(1.0 / white.x(), 1.0 / white.y(), 1.0 / white.z()) * &srgb
+});
+
+fn sampled(spectrum: &[f64; 10], wavelength: f64) -> f64 {
+    let position = ((wavelength - SHORTEST_WAVELENGTH) / SPECTRUM_WIDTH * 10.0 - 0.5).clamp(0.0, 9.0);
+    let i = (position as usize).min(8);
+    let t = position - i as f64;
+    spectrum[i] * (1.0 - t) + spectrum[i + 1] * t
+}
+
+const WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::tests::color;
+
+    use super::*;
+
+    fn round_trip(color: &Color, terminating: bool) -> Color {
+        let steps = 1000;
+        (0 .. steps)
+            .map(|i| Wavelengths::sample((i as f64 + 0.5) / steps as f64).observe(|hero| {
+                if terminating {
+                    terminate_secondary_wavelengths();
+                }
+                seen_at(color, Some(hero))
+            }))
+            .fold(Color::BLACK, |sum, c| sum + c) / steps as f64
+    }
+
+    proptest! {
+
+        #[test]
+        fn observes_colors_as_they_are(color in color()) {
+            for terminating in [false, true] {
+                let observed = round_trip(&color, terminating);
+                for c in 0..3 {
+                    assert!((observed[c] - color[c]).abs() < 0.05, "{:?} was observed as {:?}", color, observed);
+                }
+            }
+        }
+
+    }
+
+}
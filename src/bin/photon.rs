@@ -0,0 +1,264 @@
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Instant;
+
+use image::ImageFormat;
+
+use photon::filters::{Bloom, ImageFilter};
+use photon::imaging::ToneMapper;
+use photon::sampling;
+use photon::scenes::{Scene, SceneError};
+use photon::viewing::{BoxFilter, Film, Sensor};
+
+const USAGE: &str = "\
+Usage: photon <scene file> [options]
+
+Renders the given scene file, and saves the rendered image.
+
+Options:
+  -o, --output <file>         The output file. Defaults to the output of the scene, if any, or
+                              to the scene file name with a `.png` extension.
+  -f, --format <format>       One of png, jpeg, bmp, tiff, exr, or hdr. Defaults to the format
+                              matching the output file extension. High dynamic range formats
+                              (exr and hdr) get linear, non-tone-mapped, images.
+  -r, --resolution <W>x<H>    The image resolution, e.g. 1920x1080.
+  -s, --samples <count>       The number of samples per pixel, per frame.
+  -d, --depth <depth>         The maximum number of bounces of light paths.
+      --stack <count>         The number of frames to render and average.
+      --seed <seed>           Makes renders reproducible, using the given random seed.
+  -t, --threads <count>       The number of rendering threads. Defaults to the number of CPUs.
+      --tone-mapper <mapper>  One of clamp, reinhard, reinhard:<white point>, or aces.
+                              Defaults to clamp.
+      --bloom <depth>         The depth of the bloom effect. Defaults to the bloom of the scene.
+      --bloom-size <size>     The half size of the bloom kernel. Defaults to a size suiting the
+                              image resolution.
+  -q, --quiet                 Suppresses progress reports.
+  -h, --help                  Prints this help.
+
+Exit codes:
+  0   The scene got rendered and saved.
+  64  The command line is invalid.
+  65  The scene file is invalid.
+  66  The scene file could not be read.
+  73  The output file could not be written.
+";
+
+const EXIT_USAGE: u8 = 64;
+const EXIT_INVALID_SCENE: u8 = 65;
+const EXIT_UNREADABLE_SCENE: u8 = 66;
+const EXIT_UNWRITABLE_OUTPUT: u8 = 73;
+
+#[derive(Default)]
+struct Options {
+    scene: String,
+    output: Option<String>,
+    format: Option<ImageFormat>,
+    resolution: Option<(usize, usize)>,
+    samples: Option<u16>,
+    depth: Option<u8>,
+    stack: Option<u16>,
+    seed: Option<u64>,
+    threads: Option<usize>,
+    tone_mapper: Option<ToneMapper>,
+    bloom_depth: Option<u8>,
+    bloom_size: Option<u8>,
+    quiet: bool,
+    help: bool,
+}
+
+pub fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) if options.help => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS
+        },
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("photon: {}\n\n{}", message, USAGE);
+            return ExitCode::from(EXIT_USAGE)
+        },
+    };
+    match render(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err((code, message)) => {
+            eprintln!("photon: {}", message);
+            ExitCode::from(code)
+        },
+    }
+}
+
+/// Overrides the settings of the given scene with the ones given on the command line, if any.
+fn override_settings(mut scene: Scene, options: &Options) -> Scene {
+    if let Some((width, height)) = options.resolution {
+        let sensor = &scene.camera.sensor;
+        scene.camera.sensor = Sensor::new(width, height, sensor.gain).with_spectral_response(sensor.spectral);
+    }
+    if let Some(samples) = options.samples {
+        scene.camera.samples_per_pixel = samples;
+    }
+    if let Some(depth) = options.depth {
+        scene.world = scene.world.with_depth(depth);
+    }
+    scene
+}
+
+fn render(options: &Options) -> Result<(), (u8, String)> {
+    let scene = Scene::load(&options.scene).map_err(|error| match error {
+        SceneError::Unreadable(_) => (EXIT_UNREADABLE_SCENE, format!("{}: {}", options.scene, error)),
+        SceneError::Invalid(_, _) => (EXIT_INVALID_SCENE, format!("{}:{}", options.scene, error)),
+    })?;
+    let output = options.output.clone()
+        .or_else(|| scene.render.output.clone())
+        .unwrap_or_else(|| Path::new(&options.scene).with_extension("png").to_string_lossy().into_owned());
+    let format = match options.format {
+        Some(format) => format,
+        None => ImageFormat::from_path(&output)
+            .map_err(|_| (EXIT_USAGE, format!("cannot tell the format of `{}`, use `--format` to specify one", output)))?,
+    };
+
+    let mut scene = override_settings(scene, options);
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()
+            .map_err(|error| (EXIT_USAGE, format!("cannot use {} threads: {}", threads, error)))?;
+    }
+    sampling::seed(options.seed);
+
+    let stack_size = options.stack.unwrap_or(scene.render.stack_size);
+    let passes = stack_size as usize * scene.camera.samples_per_pixel as usize;
+    let width = scene.camera.sensor.width;
+    let height = scene.camera.sensor.height;
+    if !options.quiet {
+        eprintln!("Rendering {} at {}x{}, with {} samples per pixel", options.scene, width, height, passes);
+    }
+
+    // Rendering one sample per pixel at a time gives finer progress reports, and parallelizes
+    // better than rendering whole frames in parallel.
+    let start = Instant::now();
+    let film = Film::new(width, height, BoxFilter { radius: 0.5 });
+    scene.camera.samples_per_pixel = 1;
+    let mut chains = scene.render.metropolis.as_ref().map(|metropolis| metropolis.start(&scene.camera, &scene.world));
+    for pass in 1 ..= passes {
+        match chains {
+            Some(ref mut chains) => chains.expose(&film),
+            None => scene.camera.expose(&scene.world, &film),
+        }
+        if !options.quiet {
+            let elapsed = start.elapsed().as_secs_f64();
+            let remaining = elapsed * ((passes - pass) as f64) / (pass as f64);
+            eprint!("\rRendered {}/{} samples per pixel ({:.1}s elapsed, {:.1}s remaining)   ", pass, passes, elapsed, remaining);
+        }
+    }
+    if !options.quiet {
+        eprintln!("\nRendered in {:.3}s", start.elapsed().as_secs_f64());
+    }
+
+    let linear = film.develop(1.0 / passes as f64);
+    let bloom_depth = options.bloom_depth.unwrap_or(scene.render.bloom_depth);
+    let bloomed = if bloom_depth > 0 {
+        let half_size = options.bloom_size.unwrap_or_else(|| scene.camera.bloom(bloom_depth).half_size);
+        Bloom { half_size, depth: bloom_depth }.filter(&linear)
+    } else {
+        linear
+    };
+    let image = match format {
+        ImageFormat::OpenExr | ImageFormat::Hdr => bloomed,
+        _ => options.tone_mapper.unwrap_or(ToneMapper::Clamp).develop(&bloomed),
+    };
+    image.save_as(&output, format)
+        .map_err(|error| (EXIT_UNWRITABLE_OUTPUT, format!("cannot save `{}`: {}", output, error)))?;
+    if !options.quiet {
+        eprintln!("Saved {}", output);
+    }
+    Ok(())
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut scene = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("`{}` expects a value", name));
+        match arg.as_str() {
+            "-o" | "--output" => options.output = Some(value(&arg)?),
+            "-f" | "--format" => options.format = Some(parse_format(&value(&arg)?)?),
+            "-r" | "--resolution" => options.resolution = Some(parse_resolution(&value(&arg)?)?),
+            "-s" | "--samples" => options.samples = Some(parse_number(&arg, &value(&arg)?, 1)?),
+            "-d" | "--depth" => options.depth = Some(parse_number(&arg, &value(&arg)?, 1)?),
+            "--stack" => options.stack = Some(parse_number(&arg, &value(&arg)?, 1)?),
+            "--seed" => options.seed = Some(parse_number(&arg, &value(&arg)?, 0)?),
+            "-t" | "--threads" => options.threads = Some(parse_number(&arg, &value(&arg)?, 1)?),
+            "--tone-mapper" => options.tone_mapper = Some(parse_tone_mapper(&value(&arg)?)?),
+            "--bloom" => options.bloom_depth = Some(parse_number(&arg, &value(&arg)?, 0)?),
+            "--bloom-size" => options.bloom_size = Some(parse_number(&arg, &value(&arg)?, 1)?),
+            "-q" | "--quiet" => options.quiet = true,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("unknown option `{}`", arg)),
+            _ if scene.is_none() => scene = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`, only one scene file is expected", arg)),
+        }
+    }
+    match scene {
+        Some(scene) => options.scene = scene,
+        None if options.help => {},
+        None => return Err("no scene file is specified".to_string()),
+    }
+    Ok(options)
+}
+
+fn parse_number<T: FromStr + PartialOrd + From<u8>>(name: &str, value: &str, min: u8) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(n) if n >= T::from(min) => Ok(n),
+        _ => Err(format!("`{}` expects a whole number no less than {}, but got `{}`", name, min, value)),
+    }
+}
+
+fn parse_resolution(value: &str) -> Result<(usize, usize), String> {
+    let dimensions = value.split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+    match dimensions {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => Err(format!("`{}` is not a valid resolution, expected something like 1920x1080", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<ImageFormat, String> {
+    match value.to_lowercase().as_str() {
+        "png" => Ok(ImageFormat::Png),
+        "jpeg" | "jpg" => Ok(ImageFormat::Jpeg),
+        "bmp" => Ok(ImageFormat::Bmp),
+        "tiff" | "tif" => Ok(ImageFormat::Tiff),
+        "exr" => Ok(ImageFormat::OpenExr),
+        "hdr" => Ok(ImageFormat::Hdr),
+        _ => Err(format!("unknown format `{}`, expected one of png, jpeg, bmp, tiff, exr, or hdr", value)),
+    }
+}
+
+fn parse_tone_mapper(value: &str) -> Result<ToneMapper, String> {
+    match value.split_once(':') {
+        None if value == "clamp" => Ok(ToneMapper::Clamp),
+        None if value == "aces" => Ok(ToneMapper::Aces),
+        None if value == "reinhard" => Ok(ToneMapper::Reinhard { white: 4.0 }),
+        Some(("reinhard", white)) => match white.parse::<f64>() {
+            Ok(white) if white > 0.0 => Ok(ToneMapper::Reinhard { white }),
+            _ => Err(format!("`{}` is not a valid white point, expected a positive number", white)),
+        },
+        _ => Err(format!("unknown tone mapper `{}`, expected one of clamp, reinhard, reinhard:<white point>, or aces", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_the_resolution_keeping_the_spectral_response() {
+        let scene = Scene::parse("camera { sensor 320 240 2 spectral }\nworld {}").unwrap();
+        let options = parse_options(["scene", "-r", "64x48"].into_iter().map(String::from)).unwrap();
+
+        let scene = override_settings(scene, &options);
+
+        let sensor = &scene.camera.sensor;
+        assert_eq!((sensor.width, sensor.height, sensor.gain, sensor.spectral), (64, 48, 2.0, true));
+    }
+
+}
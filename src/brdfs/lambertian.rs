@@ -1,11 +1,11 @@
 use std::f64::consts::PI;
 
-use rand::{rng, RngExt};
+use rand::RngExt;
 
 use crate::basic::matrices::Matrix;
 use crate::basic::vectors::{Dot, Vec3D};
 use crate::brdfs::BRDF;
-use crate::sampling::{Space, UniformSolidUnitSquare, PDF};
+use crate::sampling::{rng, Space, UniformSolidUnitSquare, PDF};
 
 /// This type represents Lambertian BRDF. It is typically used to implement matte/diffusive
 /// materials.
@@ -30,6 +30,13 @@ impl Lambertian {
         matrix.z()
     }
 
+    /// Evaluates, at the given direction, a BRDF whose ratio to this one is the given factor, which
+    /// lets rough diffuse BRDFs sample directions like this one does, and weigh them by the factor.
+    pub(crate) fn eval_scaled<F: FnOnce(&Vec3D) -> f64>(&self, direction: &Vec3D, factor: F) -> f64 {
+        let pdf = self.pdf(direction);
+        if pdf > 0.0 { pdf * factor(direction).max(0.0) } else { 0.0 }
+    }
+
 }
 
 const ONE_PI: f64 = 1.0 / PI;
@@ -0,0 +1,172 @@
+use std::f64::consts::PI;
+
+use rand::RngExt;
+
+use crate::basic::matrices::Matrix;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::BRDF;
+use crate::sampling::{rng, Space, UniformSolidUnitSquare, PDF};
+
+/// This type represents the GGX (a.k.a. Trowbridge-Reitz) microfacet BRDF, which models glossy
+/// reflection off rough surfaces made of tiny mirrors (microfacets), whose orientations are
+/// scattered around the surface normal. The roughness controls how scattered they are, from 0 for
+/// perfect mirrors, to 1 for very rough surfaces.
+///
+/// Scattered rays are sampled by reflecting the incident ray off randomly oriented microfacets.
+/// The BRDF accounts for the shadowing and masking between microfacets (using Smith's separable
+/// approximation), and for their Fresnel reflectance (using Schlick's approximation), which is 1
+/// unless set otherwise, for materials that already choose whether to reflect by it.
+#[derive(Debug)]
+pub struct Microfacet {
+    frame: Matrix,
+    outgoing: Vec3D,
+    alpha: f64,
+    reflectance: f64,
+}
+
+impl Microfacet {
+
+    /// This constructor takes the surface normal at the point where the incident ray hit the
+    /// surface, the direction of the incident ray, and the roughness of the surface.
+    pub fn new(surface_normal: &Vec3D, incident_direction: &Vec3D, roughness: f64) -> Self {
+        let outgoing = -incident_direction.unit();
+        let normal = if surface_normal.dot(&outgoing) >= 0.0 { *surface_normal } else { -surface_normal };
+        let alpha = (roughness * roughness).max(1e-4);
+        Self { frame: Matrix::with_z_alignment(&normal), outgoing, alpha, reflectance: 1.0 }
+    }
+
+    /// Sets the Fresnel reflectance of the microfacets at normal incidence.
+    pub fn with_reflectance(self, reflectance: f64) -> Self {
+        Self { reflectance, ..self }
+    }
+
+    pub fn normal(&self) -> &Vec3D {
+        self.frame.z()
+    }
+
+    /// The distribution of microfacet normals, weighted by their projected area.
+    fn distribution(&self, cos_theta: f64) -> f64 {
+        let alpha2 = self.alpha * self.alpha;
+        let d = (alpha2 - 1.0) * cos_theta * cos_theta + 1.0;
+        alpha2 / (PI * d * d)
+    }
+
+    /// Samples the normal of a microfacet, according to the distribution of microfacet normals.
+    pub fn microfacet_normal(&self) -> Vec3D {
+        let unit_square_sample = rng().sample(UniformSolidUnitSquare);
+        let u = unit_square_sample.x();
+        let tan_theta_squared = self.alpha * self.alpha * u / (1.0 - u).max(1e-12);
+        let cos_theta = 1.0 / (1.0 + tan_theta_squared).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (2.0 * PI * unit_square_sample.y()).sin_cos();
+        &self.frame * &Vec3D::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta)
+    }
+
+    /// The fraction of microfacets, facing the given direction, that are visible from it, rather
+    /// than hidden by others.
+    fn visibility(&self, direction: &Vec3D) -> f64 {
+        let cos_theta = direction.dot(self.normal()).clamp(1e-12, 1.0);
+        let tan_theta_squared = (1.0 - cos_theta * cos_theta) / (cos_theta * cos_theta);
+        2.0 / (1.0 + (1.0 + self.alpha * self.alpha * tan_theta_squared).sqrt())
+    }
+
+    fn fresnel(&self, cos_angle: f64) -> f64 {
+        self.reflectance + (1.0 - self.reflectance) * (1.0 - cos_angle).powi(5)
+    }
+
+    fn reflection(&self, microfacet_normal: &Vec3D) -> Vec3D {
+        2.0 * self.outgoing.dot(*microfacet_normal) * microfacet_normal - self.outgoing
+    }
+
+}
+
+impl BRDF for Microfacet {
+
+    fn narrowness(&self) -> f64 {
+        1.0 - 0.5 * self.alpha.min(1.0)
+    }
+
+    fn eval(&self, direction: &Vec3D) -> f64 {
+        if !self.contains(direction) {
+            return 0.0
+        }
+        let half_way = (self.outgoing + *direction).unit();
+        let cos_outgoing = self.outgoing.dot(*self.normal()).max(1e-12);
+        let cos_half_way = half_way.dot(*self.normal()).max(0.0);
+        let shadowing = self.visibility(&self.outgoing) * self.visibility(direction);
+        self.distribution(cos_half_way) * shadowing * self.fresnel(self.outgoing.dot(half_way).abs()) / (4.0 * cos_outgoing)
+    }
+
+}
+
+impl Space<Vec3D> for Microfacet {
+
+    /// Reflections going below the surface are lost, with a PDF of zero.
+    fn arbitrary_sample_and_pdf(&self) -> (Vec3D, f64) {
+        let microfacet_normal = self.microfacet_normal();
+        let direction = self.reflection(&microfacet_normal);
+        if self.outgoing.dot(microfacet_normal) <= 0.0 {
+            return (direction, 0.0)
+        }
+        (direction, self.pdf(&direction))
+    }
+
+}
+
+impl PDF<Vec3D> for Microfacet {
+
+    fn pdf(&self, direction: &Vec3D) -> f64 {
+        if !self.contains(direction) {
+            return 0.0
+        }
+        let half_way = (self.outgoing + *direction).unit();
+        let cos_theta = half_way.dot(*self.normal()).max(0.0);
+        self.distribution(cos_theta) * cos_theta / (4.0 * self.outgoing.dot(half_way).abs().max(1e-12))
+    }
+
+    fn contains(&self, direction: &Vec3D) -> bool {
+        self.normal().dot(direction) > 0.0
+    }
+
+    fn strict_pdf(&self, direction: &Vec3D) -> f64 {
+        self.pdf(direction)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::vectors::Vec3D;
+    use crate::sampling::UniformUnitSphere;
+
+    use super::*;
+
+    #[test]
+    fn samples_directions_according_to_the_pdf() {
+        // The probabilities of sampling directions within bands of latitude are compared with the
+        // integrals of the PDF over them, estimated using uniformly sampled directions.
+        let normal = Vec3D::new(1.0, 2.0, 3.0).unit();
+        let incident = Vec3D::new(-1.0, 0.0, -1.0).unit();
+        let microfacet = Microfacet::new(&normal, &incident, 0.5);
+        let bands = 4;
+        let band = |direction: &Vec3D| ((direction.dot(&normal) * bands as f64) as usize).min(bands - 1);
+        let count = 200_000;
+        let mut sampled = vec![0.0; bands];
+        let mut integrated = vec![0.0; bands];
+        for _ in 0 .. count {
+            let (direction, pdf) = microfacet.arbitrary_sample_and_pdf();
+            if pdf > 0.0 {
+                assert!(direction.dot(normal) > 0.0);
+                sampled[band(&direction)] += 1.0 / count as f64;
+            }
+            let (direction, uniform_pdf) = UniformUnitSphere.arbitrary_sample_and_pdf();
+            if direction.dot(normal) > 0.0 {
+                integrated[band(&direction)] += microfacet.pdf(&direction) / (uniform_pdf * count as f64);
+            }
+        }
+        for (sampled, integrated) in sampled.iter().zip(&integrated) {
+            assert!((sampled - integrated).abs() < 0.01, "{:?} {:?}", sampled, integrated);
+        }
+    }
+
+}
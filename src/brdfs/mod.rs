@@ -1,9 +1,15 @@
 pub use lambertian::*;
+pub use microfacet::*;
+pub use oren_nayar::*;
+pub use retro_reflective::*;
 
 use crate::basic::vectors::Vec3D;
 use crate::sampling::Space;
 
 mod lambertian;
+mod microfacet;
+mod oren_nayar;
+mod retro_reflective;
 
 /// The Bidirectional Reflectance Distribution Function representing the scattering of some incident
 /// ray off some hit surface. The ray direction, the surface normal, and the surface material are
@@ -48,4 +54,12 @@ pub trait BRDF: Space<Vec3D> {
 
     fn narrowness(&self) -> f64;
 
+    /// Evaluates the BRDF, times the cosine of the angle between the given direction and the
+    /// normal, relative to the color of the scattering effect. BRDFs whose sampling PDF is
+    /// proportional to that product (e.g. Lambertian BRDF) need not override the default, which is
+    /// the PDF itself.
+    fn eval(&self, direction: &Vec3D) -> f64 {
+        self.pdf(direction)
+    }
+
 }
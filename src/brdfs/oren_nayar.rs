@@ -0,0 +1,162 @@
+use std::f64::consts::PI;
+
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::{Lambertian, BRDF};
+use crate::sampling::{Space, PDF};
+
+/// This type represents the Oren-Nayar BRDF, which models rough diffusive surfaces (e.g. clay,
+/// plaster, or moon dust) as collections of tiny Lambertian facets. Compared to Lambertian
+/// surfaces, they look flatter, and brighter when lit from behind the viewer.
+///
+/// The roughness is the standard deviation of the slopes of the facets, in radians, where 0 makes
+/// the BRDF Lambertian. The qualitative model is the commonly used simplification of the full one,
+/// which also accounts for interreflections between facets. Since the interreflections depend on
+/// the albedo squared, the full model takes the albedo too.
+#[derive(Debug)]
+pub struct OrenNayar {
+    lambertian: Lambertian,
+    outgoing: Vec3D,
+    roughness_squared: f64,
+    albedo: Option<f64>,
+}
+
+impl OrenNayar {
+
+    pub fn qualitative(surface_normal: &Vec3D, incident_direction: &Vec3D, roughness: f64) -> Self {
+        Self::new(surface_normal, incident_direction, roughness, None)
+    }
+
+    /// The albedo could be the luminance of the surface color.
+    pub fn full(surface_normal: &Vec3D, incident_direction: &Vec3D, roughness: f64, albedo: f64) -> Self {
+        Self::new(surface_normal, incident_direction, roughness, Some(albedo))
+    }
+
+    fn new(surface_normal: &Vec3D, incident_direction: &Vec3D, roughness: f64, albedo: Option<f64>) -> Self {
+        let outgoing = -incident_direction.unit();
+        let normal = if surface_normal.dot(&outgoing) >= 0.0 { *surface_normal } else { -surface_normal };
+        Self { lambertian: Lambertian::new(&normal), outgoing, roughness_squared: roughness * roughness, albedo }
+    }
+
+    pub fn normal(&self) -> &Vec3D {
+        self.lambertian.normal()
+    }
+
+    fn factor(&self, direction: &Vec3D) -> f64 {
+        let normal = self.normal();
+        let cos_in = direction.dot(normal).clamp(-1.0, 1.0);
+        let cos_out = self.outgoing.dot(*normal).clamp(-1.0, 1.0);
+        let theta_in = cos_in.acos();
+        let theta_out = cos_out.acos();
+        let (alpha, beta) = (theta_in.max(theta_out), theta_in.min(theta_out));
+        let tangent_in = direction - &(cos_in * normal);
+        let tangent_out = self.outgoing - cos_out * normal;
+        let lengths = tangent_in.length() * tangent_out.length();
+        let cos_phi = if lengths > 1e-12 { tangent_in.dot(tangent_out) / lengths } else { 0.0 };
+
+        let s2 = self.roughness_squared;
+        match self.albedo {
+            None => {
+                let a = 1.0 - 0.5 * s2 / (s2 + 0.33);
+                let b = 0.45 * s2 / (s2 + 0.09);
+                a + b * cos_phi.max(0.0) * alpha.sin() * beta.tan()
+            },
+            Some(albedo) => {
+                let beta_ratio = 2.0 * beta / PI;
+                let c1 = 1.0 - 0.5 * s2 / (s2 + 0.33);
+                let c2 = 0.45 * s2 / (s2 + 0.09) * if cos_phi >= 0.0 { alpha.sin() } else { alpha.sin() - beta_ratio.powi(3) };
+                let c3 = 0.125 * s2 / (s2 + 0.09) * (4.0 * alpha * beta / (PI * PI)).powi(2);
+                let direct = c1 + cos_phi * c2 * beta.tan() + (1.0 - cos_phi.abs()) * c3 * (0.5 * (alpha + beta)).tan();
+                let interreflected = 0.17 * albedo * s2 / (s2 + 0.13) * (1.0 - cos_phi * beta_ratio * beta_ratio);
+                direct + interreflected
+            },
+        }
+    }
+
+}
+
+impl BRDF for OrenNayar {
+
+    fn narrowness(&self) -> f64 {
+        0.5
+    }
+
+    fn eval(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.eval_scaled(direction, |direction| self.factor(direction))
+    }
+
+}
+
+impl Space<Vec3D> for OrenNayar {
+
+    fn arbitrary_sample_and_pdf(&self) -> (Vec3D, f64) {
+        self.lambertian.arbitrary_sample_and_pdf()
+    }
+
+}
+
+impl PDF<Vec3D> for OrenNayar {
+
+    fn pdf(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.pdf(direction)
+    }
+
+    fn contains(&self, direction: &Vec3D) -> bool {
+        self.lambertian.contains(direction)
+    }
+
+    fn strict_pdf(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.strict_pdf(direction)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::tests::unit_vec3;
+    use crate::rough_equality;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn generates_unit_length_directions_above_the_surface(normal in unit_vec3(), incident in unit_vec3(), roughness in 0.0..1.0) {
+            let oren_nayar = OrenNayar::qualitative(&normal, &incident, roughness);
+
+            let (direction, pdf) = oren_nayar.arbitrary_sample_and_pdf();
+
+            assert!(rough_equality(direction.length(), 1.0));
+            assert!(direction.dot(*oren_nayar.normal()) >= 0.0);
+            assert!(rough_equality(pdf, direction.dot(*oren_nayar.normal()) / PI));
+        }
+
+        #[test]
+        fn reduces_to_lambertian_for_smooth_surfaces(normal in unit_vec3(), incident in unit_vec3(), albedo in 0.0..1.0) {
+            let qualitative = OrenNayar::qualitative(&normal, &incident, 0.0);
+            let full = OrenNayar::full(&normal, &incident, 0.0, albedo);
+
+            let direction = qualitative.arbitrary_sample();
+
+            assert!(rough_equality(qualitative.eval(&direction), qualitative.pdf(&direction)));
+            assert!(rough_equality(full.eval(&direction), full.pdf(&direction)));
+        }
+
+        #[test]
+        fn does_not_create_energy(normal in unit_vec3(), incident in unit_vec3(), roughness in 0.0..1.0) {
+            let oren_nayar = OrenNayar::qualitative(&normal, &incident, roughness);
+
+            let samples = 1000;
+            let mut albedo = 0.0;
+            for _ in 0..samples {
+                let (direction, pdf) = oren_nayar.arbitrary_sample_and_pdf();
+                albedo += oren_nayar.eval(&direction) / pdf / samples as f64;
+            }
+
+            assert!(albedo < 1.1);
+        }
+
+    }
+
+}
@@ -0,0 +1,114 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::{Lambertian, BRDF};
+use crate::sampling::{Space, PDF};
+
+/// This type represents Burley's diffuse BRDF, which is retro-reflective, i.e. rough surfaces
+/// scatter more light back towards the light source at grazing angles, while smooth surfaces get
+/// darker there. It suits cloth, and other rough, dusty surfaces.
+///
+/// The roughness ranges from 0 for smooth surfaces, to 1 for very rough ones.
+#[derive(Debug)]
+pub struct RetroReflective {
+    lambertian: Lambertian,
+    outgoing: Vec3D,
+    roughness: f64,
+}
+
+impl RetroReflective {
+
+    pub fn new(surface_normal: &Vec3D, incident_direction: &Vec3D, roughness: f64) -> Self {
+        let outgoing = -incident_direction.unit();
+        let normal = if surface_normal.dot(&outgoing) >= 0.0 { *surface_normal } else { -surface_normal };
+        Self { lambertian: Lambertian::new(&normal), outgoing, roughness }
+    }
+
+    pub fn normal(&self) -> &Vec3D {
+        self.lambertian.normal()
+    }
+
+    fn factor(&self, direction: &Vec3D) -> f64 {
+        let half_way = (self.outgoing + *direction).unit();
+        let cos_d = direction.dot(&half_way);
+        let f90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+        let schlick = |cos: f64| 1.0 + (f90 - 1.0) * (1.0 - cos.clamp(0.0, 1.0)).powi(5);
+        schlick(direction.dot(self.normal())) * schlick(self.outgoing.dot(*self.normal()))
+    }
+
+}
+
+impl BRDF for RetroReflective {
+
+    fn narrowness(&self) -> f64 {
+        0.5
+    }
+
+    fn eval(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.eval_scaled(direction, |direction| self.factor(direction))
+    }
+
+}
+
+impl Space<Vec3D> for RetroReflective {
+
+    fn arbitrary_sample_and_pdf(&self) -> (Vec3D, f64) {
+        self.lambertian.arbitrary_sample_and_pdf()
+    }
+
+}
+
+impl PDF<Vec3D> for RetroReflective {
+
+    fn pdf(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.pdf(direction)
+    }
+
+    fn contains(&self, direction: &Vec3D) -> bool {
+        self.lambertian.contains(direction)
+    }
+
+    fn strict_pdf(&self, direction: &Vec3D) -> f64 {
+        self.lambertian.strict_pdf(direction)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use proptest::*;
+
+    use crate::basic::vectors::tests::unit_vec3;
+    use crate::rough_equality;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn generates_directions_with_pdf_proportional_to_cos_theta(normal in unit_vec3(), incident in unit_vec3(), roughness in 0.0..1.0) {
+            let retro_reflective = RetroReflective::new(&normal, &incident, roughness);
+
+            let (direction, pdf) = retro_reflective.arbitrary_sample_and_pdf();
+
+            let cos_theta = direction.dot(*retro_reflective.normal());
+            assert!(rough_equality(direction.length(), 1.0));
+            assert!(cos_theta >= 0.0);
+            assert!(rough_equality(pdf, cos_theta / PI));
+        }
+
+        #[test]
+        fn reflects_more_back_towards_grazing_light_when_rough(normal in unit_vec3(), tangent in unit_vec3()) {
+            let tangent = tangent.reject(&normal, true);
+            prop_assume!(tangent.length() > 0.1);
+            let grazing = (normal * 0.1 + tangent.unit()).unit();
+
+            let smooth = RetroReflective::new(&normal, &-grazing, 0.0);
+            let rough = RetroReflective::new(&normal, &-grazing, 1.0);
+
+            assert!(rough.eval(&grazing) > smooth.eval(&grazing));
+        }
+
+    }
+
+}
@@ -0,0 +1,15 @@
+use crate::builders::Building;
+use crate::things::Thing;
+use crate::worlds::AmbientOccluded;
+
+impl<T: Thing> Building<AmbientOccluded<T>> {
+
+    pub fn with_radius(self, radius: f64) -> Building<AmbientOccluded<T>> {
+        let world = self.done();
+        Building(AmbientOccluded {
+            subject: world.subject,
+            radius,
+        })
+    }
+
+}
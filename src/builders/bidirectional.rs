@@ -0,0 +1,38 @@
+use crate::builders::Building;
+use crate::lights::Lights;
+use crate::things::Thing;
+use crate::worlds::{BidirectionalPathTraced, World};
+
+impl<W: World, T: Thing> Building<BidirectionalPathTraced<W, T>> {
+
+    pub fn with_environment<E: World>(self, environment: E) -> Building<BidirectionalPathTraced<E, T>> {
+        let world = self.done();
+        Building(BidirectionalPathTraced {
+            subject: world.subject,
+            environment,
+            depth: world.depth,
+            lights: world.lights,
+        })
+    }
+
+    pub fn with_depth(self, depth: u8) -> Building<BidirectionalPathTraced<W, T>> {
+        let world = self.done();
+        Building(BidirectionalPathTraced {
+            subject: world.subject,
+            environment: world.environment,
+            depth,
+            lights: world.lights,
+        })
+    }
+
+    pub fn with_lights(self, lights: Lights) -> Building<BidirectionalPathTraced<W, T>> {
+        let world = self.done();
+        Building(BidirectionalPathTraced {
+            subject: world.subject,
+            environment: world.environment,
+            depth: world.depth,
+            lights,
+        })
+    }
+
+}
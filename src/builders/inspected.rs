@@ -0,0 +1,15 @@
+use crate::builders::Building;
+use crate::things::Thing;
+use crate::worlds::{Aspect, Inspected};
+
+impl<T: Thing> Building<Inspected<T>> {
+
+    pub fn with_aspect(self, aspect: Aspect) -> Building<Inspected<T>> {
+        let world = self.done();
+        Building(Inspected {
+            subject: world.subject,
+            aspect,
+        })
+    }
+
+}
@@ -3,10 +3,15 @@
 use std::sync::Arc;
 
 use crate::transforms::{Transformation, Transformed};
+use crate::Tagged;
 
 mod thing;
 mod geometry;
 mod path_traced;
+mod bidirectional;
+mod photon_mapped;
+mod ambient_occluded;
+mod inspected;
 
 pub struct Building<T>(pub T);
 
@@ -25,6 +30,13 @@ impl<T> Building<T> {
         Arc::new(self.done())
     }
 
+    pub fn tagged(self, id: u32) -> Building<Tagged<T>> {
+        Building(Tagged {
+            subject: self.done(),
+            id
+        })
+    }
+
     pub fn transformed<F: Transformation>(self, transformation: F) -> Building<Transformed<T, F>> {
         Building(Transformed {
             subject: self.done(),
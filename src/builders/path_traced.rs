@@ -1,4 +1,5 @@
 use crate::builders::Building;
+use crate::lights::Lights;
 use crate::things::Thing;
 use crate::worlds::{ImportantDirectionSampler, PathTraced, World};
 
@@ -11,6 +12,8 @@ impl<W: World, T: Thing, S: ImportantDirectionSampler> Building<PathTraced<W, T,
             environment,
             depth: world.depth,
             directions_sampler: world.directions_sampler,
+            lights: world.lights,
+            prepared_lights: Default::default(),
         })
     }
 
@@ -21,6 +24,8 @@ impl<W: World, T: Thing, S: ImportantDirectionSampler> Building<PathTraced<W, T,
             environment: world.environment,
             depth,
             directions_sampler: world.directions_sampler,
+            lights: world.lights,
+            prepared_lights: Default::default(),
         })
     }
 
@@ -31,6 +36,20 @@ impl<W: World, T: Thing, S: ImportantDirectionSampler> Building<PathTraced<W, T,
             environment: world.environment,
             depth: world.depth,
             directions_sampler,
+            lights: world.lights,
+            prepared_lights: Default::default(),
+        })
+    }
+
+    pub fn with_lights(self, lights: Lights) -> Building<PathTraced<W, T, S>> {
+        let world = self.done();
+        Building(PathTraced {
+            subject: world.subject,
+            environment: world.environment,
+            depth: world.depth,
+            directions_sampler: world.directions_sampler,
+            lights,
+            prepared_lights: Default::default(),
         })
     }
 
@@ -0,0 +1,78 @@
+use crate::builders::Building;
+use crate::lights::Lights;
+use crate::things::Thing;
+use crate::worlds::{PhotonMapped, World};
+
+impl<W: World, T: Thing> Building<PhotonMapped<W, T>> {
+
+    pub fn with_environment<E: World>(self, environment: E) -> Building<PhotonMapped<E, T>> {
+        let world = self.done();
+        Building(PhotonMapped {
+            subject: world.subject,
+            environment,
+            depth: world.depth,
+            lights: world.lights,
+            photons: world.photons,
+            radius: world.radius,
+            map: world.map,
+            visible_points: world.visible_points,
+        })
+    }
+
+    pub fn with_depth(self, depth: u8) -> Building<PhotonMapped<W, T>> {
+        let world = self.done();
+        Building(PhotonMapped {
+            subject: world.subject,
+            environment: world.environment,
+            depth,
+            lights: world.lights,
+            photons: world.photons,
+            radius: world.radius,
+            map: world.map,
+            visible_points: world.visible_points,
+        })
+    }
+
+    pub fn with_lights(self, lights: Lights) -> Building<PhotonMapped<W, T>> {
+        let world = self.done();
+        Building(PhotonMapped {
+            subject: world.subject,
+            environment: world.environment,
+            depth: world.depth,
+            lights,
+            photons: world.photons,
+            radius: world.radius,
+            map: world.map,
+            visible_points: world.visible_points,
+        })
+    }
+
+    pub fn with_photons(self, photons: usize) -> Building<PhotonMapped<W, T>> {
+        let world = self.done();
+        Building(PhotonMapped {
+            subject: world.subject,
+            environment: world.environment,
+            depth: world.depth,
+            lights: world.lights,
+            photons,
+            radius: world.radius,
+            map: world.map,
+            visible_points: world.visible_points,
+        })
+    }
+
+    pub fn with_radius(self, radius: f64) -> Building<PhotonMapped<W, T>> {
+        let world = self.done();
+        Building(PhotonMapped {
+            subject: world.subject,
+            environment: world.environment,
+            depth: world.depth,
+            lights: world.lights,
+            photons: world.photons,
+            radius,
+            map: world.map,
+            visible_points: world.visible_points,
+        })
+    }
+
+}
@@ -1,7 +1,8 @@
 use crate::basic::colors::Color;
 use crate::builders::Building;
+use crate::lights::Lights;
 use crate::things::Thing;
-use crate::worlds::{Omnidirectional, PathTraced};
+use crate::worlds::{AmbientOccluded, Aspect, BidirectionalPathTraced, Inspected, Omnidirectional, PathTraced, PhotonMapped};
 
 impl<T: Thing> Building<T> {
 
@@ -10,7 +11,45 @@ impl<T: Thing> Building<T> {
             subject: self.done(),
             environment: Color::BLACK,
             depth: 8,
-            directions_sampler: Omnidirectional
+            directions_sampler: Omnidirectional,
+            lights: Lights::default(),
+            prepared_lights: Default::default(),
+        })
+    }
+
+    pub fn bidirectional_path_traced(self) -> Building<BidirectionalPathTraced<Color, T>> {
+        Building(BidirectionalPathTraced {
+            subject: self.done(),
+            environment: Color::BLACK,
+            depth: 8,
+            lights: Lights::default(),
+        })
+    }
+
+    pub fn photon_mapped(self) -> Building<PhotonMapped<Color, T>> {
+        Building(PhotonMapped {
+            subject: self.done(),
+            environment: Color::BLACK,
+            depth: 8,
+            lights: Lights::default(),
+            photons: 100_000,
+            radius: 0.1,
+            map: Default::default(),
+            visible_points: Default::default(),
+        })
+    }
+
+    pub fn ambient_occluded(self) -> Building<AmbientOccluded<T>> {
+        Building(AmbientOccluded {
+            subject: self.done(),
+            radius: 1.0,
+        })
+    }
+
+    pub fn inspected(self) -> Building<Inspected<T>> {
+        Building(Inspected {
+            subject: self.done(),
+            aspect: Aspect::Normals,
         })
     }
 
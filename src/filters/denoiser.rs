@@ -0,0 +1,248 @@
+use crate::basic::colors::Color;
+use crate::filters::AtomicImageFilter;
+use crate::imaging::{Image, Layers, PixelPosition};
+
+/// An edge-avoiding à-trous wavelet denoiser, guided by the auxiliary layers of a render. It is a
+/// pipeline of `levels` passes, each of which is a joint bilateral filter whose taps are twice as
+/// far apart as those of the previous pass.
+///
+/// Neighbouring pixels contribute less the more their depth, normal, and albedo differ from those
+/// of the filtered pixel, so that geometric and texture edges stay sharp. They also contribute
+/// less the more their luminance differs, relative to the standard deviation of the estimated
+/// luminance of the filtered pixel, so that noise gets smoothed while lighting details survive.
+///
+/// Example:
+/// ```no_run
+/// # use photon::filters::{Denoiser, FilteringPipeLine, ImageFilter};
+/// # use photon::imaging::Layers;
+/// # fn denoise(layers: &Layers) {
+/// let denoiser = Denoiser::new(layers);
+/// let denoised = denoiser.pipeline().to_filter().filter(&layers.beauty);
+/// # }
+/// ```
+pub struct Denoiser<'a> {
+    pub guides: &'a Layers,
+    pub levels: u8,
+    pub luminance_sigma: f64,
+    pub normal_exponent: f64,
+    pub depth_sigma: f64,
+    pub albedo_sigma: f64,
+}
+
+/// A single pass of the denoiser, at the given level.
+pub struct ATrous<'a> {
+    pub guides: &'a Layers,
+    pub level: u8,
+    pub luminance_sigma: f64,
+    pub normal_exponent: f64,
+    pub depth_sigma: f64,
+    pub albedo_sigma: f64,
+}
+
+const WEIGHTS: [f64; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// The factor by which the variance of an image is reduced after a pass with the kernel weights,
+/// ignoring edge stopping.
+const VARIANCE_REDUCTION: f64 = 0.07476806640625;
+
+impl<'a> Denoiser<'a> {
+
+    pub fn new(guides: &'a Layers) -> Self {
+        Self {
+            guides,
+            levels: 5,
+            luminance_sigma: 4.0,
+            normal_exponent: 128.0,
+            depth_sigma: 0.05,
+            albedo_sigma: 0.1,
+        }
+    }
+
+    pub fn pipeline(&self) -> Vec<ATrous<'a>> {
+        (0 .. self.levels)
+            .map(|level| ATrous {
+                guides: self.guides,
+                level,
+                luminance_sigma: self.luminance_sigma,
+                normal_exponent: self.normal_exponent,
+                depth_sigma: self.depth_sigma,
+                albedo_sigma: self.albedo_sigma,
+            })
+            .collect()
+    }
+
+}
+
+impl<'a> ATrous<'a> {
+
+    fn weight(&self, p: (usize, usize), q: (usize, usize), input: &Image, standard_deviation: f64) -> f64 {
+        let guides = self.guides;
+        let (p, q) = (position(input, p), position(input, q));
+
+        let (np, nq) = (&guides.normal[&p], &guides.normal[&q]);
+        let normal_weight = if *np == Color::BLACK && *nq == Color::BLACK {
+            1.0
+        } else {
+            (np[0] * nq[0] + np[1] * nq[1] + np[2] * nq[2]).max(0.0).powf(self.normal_exponent)
+        };
+
+        let (zp, zq) = (guides.depth[&p].red(), guides.depth[&q].red());
+        let depth_weight = (-(zp - zq).abs() / (self.depth_sigma * zp.abs().max(zq.abs()) + 1e-6)).exp();
+
+        let albedo_difference = guides.albedo[&p] - guides.albedo[&q];
+        let albedo_distance_squared = (albedo_difference * albedo_difference).luminance();
+        let albedo_weight = (-albedo_distance_squared / (self.albedo_sigma * self.albedo_sigma)).exp();
+
+        let luminance_difference = (input[&p].luminance() - input[&q].luminance()).abs();
+        let luminance_weight = (-luminance_difference / (self.luminance_sigma * standard_deviation + 1e-6)).exp();
+
+        normal_weight * depth_weight * albedo_weight * luminance_weight
+    }
+
+    /// Returns the variance of the luminance of the given pixel. The variance layer holds the
+    /// variances of the color channels, which are correlated, so the luminance of their standard
+    /// deviations gets squared, which bounds the variance of the luminance, and matches it for
+    /// fully correlated channels, e.g. under white light. Per-pixel variance estimates are
+    /// themselves noisy at low sample counts, so they are smoothed a bit over the 3x3 neighbourhood
+    /// of the pixel.
+    fn variance_at(&self, column: usize, row: usize) -> f64 {
+        let variance = &self.guides.variance;
+        let (width, height) = (variance.width(), variance.height());
+        let mut sum = 0.0;
+        let mut weights_sum = 0.0;
+        for y in row.max(1) - 1 ..= (row + 1).min(height - 1) {
+            for x in column.max(1) - 1 ..= (column + 1).min(width - 1) {
+                let weight = if x == column { 2.0 } else { 1.0 } * if y == row { 2.0 } else { 1.0 };
+                let v = &variance[&position(variance, (x, y))];
+                let deviation = Color::new(v.red().max(0.0).sqrt(), v.green().max(0.0).sqrt(), v.blue().max(0.0).sqrt()).luminance();
+                sum += deviation * deviation * weight;
+                weights_sum += weight;
+            }
+        }
+        sum / weights_sum
+    }
+
+}
+
+impl<'a> AtomicImageFilter for ATrous<'a> {
+
+    fn filter_into(&self, output: &mut Image, input: &Image) {
+        let step = 1isize << self.level;
+        let width = input.width() as isize;
+        let height = input.height() as isize;
+        let variance_scale = VARIANCE_REDUCTION.powi(self.level as i32);
+        for ref p in input.pixel_position_iterator() {
+            let variance = self.variance_at(p.column, p.row) * variance_scale;
+            let standard_deviation = variance.sqrt();
+            let mut color = Color::BLACK;
+            let mut weights_sum = 0.0;
+            for (j, wy) in WEIGHTS.iter().enumerate() {
+                let y = p.row as isize + (j as isize - 2) * step;
+                if y < 0 || y >= height {
+                    continue
+                }
+                for (i, wx) in WEIGHTS.iter().enumerate() {
+                    let x = p.column as isize + (i as isize - 2) * step;
+                    if x < 0 || x >= width {
+                        continue
+                    }
+                    let q = (x as usize, y as usize);
+                    let weight = wx * wy * self.weight((p.column, p.row), q, input, standard_deviation);
+                    color += input[&position(input, q)] * weight;
+                    weights_sum += weight;
+                }
+            }
+            output[p] = if weights_sum > 0.0 { color / weights_sum } else { input[p] };
+        }
+    }
+
+}
+
+fn position(image: &Image, (column, row): (usize, usize)) -> PixelPosition {
+    PixelPosition { column, row, linear: row * image.width() + column }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::tests::color;
+    use crate::filters::{FilteringPipeLine, ImageFilter};
+
+    use super::*;
+
+    fn guides(beauty: &Image, normal: Image, albedo: Image) -> Layers {
+        let (width, height) = (beauty.width(), beauty.height());
+        Layers {
+            beauty: beauty.clone(),
+            variance: Image::solid(Color::grey_shade(1.0), width, height),
+            depth: Image::solid(Color::grey_shade(1.0), width, height),
+            normal,
+            albedo,
+            surface_coordinates: Image::new(width, height),
+            object_id: Image::new(width, height),
+            material_id: Image::new(width, height),
+            motion: Image::new(width, height),
+        }
+    }
+
+    /// Returns an image whose left and right halves have the given colors.
+    fn step(left: Color, right: Color) -> Image {
+        let mut image = Image::new(16, 12);
+        for ref p in image.pixel_position_iterator() {
+            image[p] = if p.column < 8 { left } else { right };
+        }
+        image
+    }
+
+    #[test]
+    fn preserves_edges_of_the_guides() {
+        // the noise is high enough for the luminance not to stop the filter at the edge
+        let beauty = step(Color::grey_shade(0.2), Color::grey_shade(0.8));
+        let flat = Image::solid(Color::new(0.0, 0.0, 1.0), 16, 12);
+        let grey = Image::solid(Color::grey_shade(0.5), 16, 12);
+        let layers = [
+            guides(&beauty, flat.clone(), step(Color::grey_shade(0.2), Color::grey_shade(0.8))),
+            guides(&beauty, step(Color::new(0.0, 0.0, 1.0), Color::new(1.0, 0.0, 0.0)), grey.clone()),
+        ];
+        for guides in &layers {
+            let denoised = Denoiser::new(guides).pipeline().to_filter().filter(&beauty);
+            for ref p in denoised.pixel_position_iterator() {
+                assert!((denoised[p].luminance() - beauty[p].luminance()).abs() < 1e-6, "{:?}", denoised[p]);
+            }
+        }
+        let blurred = Denoiser::new(&guides(&beauty, flat, grey)).pipeline().to_filter().filter(&beauty);
+        let edge = position(&blurred, (7, 6));
+        assert!(blurred[&edge].luminance() > 0.3, "{:?}", blurred[&edge]);
+    }
+
+    proptest! {
+
+        #[test]
+        fn preserves_uniform_images(shade in color(), normal_z in 0.5..1.0f64, depth in 1.0..10.0) {
+            let beauty = Image::solid(shade, 16, 12);
+            let normal = Color::new(0.0, (1.0 - normal_z * normal_z).sqrt(), normal_z);
+            let guides = Layers {
+                beauty: beauty.clone(),
+                variance: Image::solid(Color::grey_shade(0.01), 16, 12),
+                depth: Image::solid(Color::grey_shade(depth), 16, 12),
+                normal: Image::solid(normal, 16, 12),
+                albedo: Image::solid(shade, 16, 12),
+                surface_coordinates: Image::new(16, 12),
+                object_id: Image::new(16, 12),
+                material_id: Image::new(16, 12),
+                motion: Image::new(16, 12),
+            };
+
+            let denoised = Denoiser::new(&guides).pipeline().to_filter().filter(&beauty);
+
+            for ref p in denoised.pixel_position_iterator() {
+                for c in 0..3 {
+                    assert!((denoised[p][c] - shade[c]).abs() < 1e-9);
+                }
+            }
+        }
+
+    }
+
+}
@@ -2,6 +2,7 @@ use std::mem::swap;
 use std::sync::Arc;
 
 pub use bloom::*;
+pub use denoiser::*;
 pub use gaussian::*;
 pub use kernel1d::*;
 pub use kernel2d::*;
@@ -13,6 +14,7 @@ mod kernel1d;
 mod kernel2d;
 mod bloom;
 mod gaussian;
+mod denoiser;
 
 pub type SubFilters<'a> = Box<dyn Iterator<Item=&'a dyn AtomicImageFilter> + 'a>;
 
@@ -3,8 +3,9 @@ use std::sync::Arc;
 
 pub use sphere::*;
 
+use crate::basic::matrices::Matrix;
 use crate::basic::rays::Ray;
-use crate::basic::vectors::Vec3D;
+use crate::basic::vectors::{Dot, Vec3D};
 
 mod sphere;
 mod transformed;
@@ -15,9 +16,16 @@ pub trait Geometry: Send + Sync {
 
     fn surface_coordinates(&self, point: &Vec3D) -> Vec3D;
 
+    /// Samples a point uniformly over the surface, returning the hit of a ray reaching it from
+    /// outside, against the normal, along with the area density of sampling it. Geometries that
+    /// cannot sample their surfaces return `None`.
+    fn sample_surface(&self) -> Option<(Hit, f64)> {
+        None
+    }
+
 }
 
-impl<G: Geometry> Geometry for Arc<G> {
+impl<G: Geometry + ?Sized> Geometry for Arc<G> {
 
     fn shoot(&self, ray: &Ray, min: f64, max: f64) -> Option<Hit> {
         self.as_ref().shoot(ray, min, max)
@@ -27,6 +35,10 @@ impl<G: Geometry> Geometry for Arc<G> {
         self.as_ref().surface_coordinates(point)
     }
 
+    fn sample_surface(&self) -> Option<(Hit, f64)> {
+        self.as_ref().sample_surface()
+    }
+
 }
 
 #[derive(Clone, Debug)]
@@ -34,6 +46,9 @@ pub struct Hit {
 
     pub incident_ray: Ray,
     pub normal: Vec3D,
+    /// The direction in which the first surface coordinate increases, or zero if the geometry
+    /// does not tell.
+    pub tangent: Vec3D,
     pub distance: f64,
     pub outside: bool,
 
@@ -44,11 +59,36 @@ pub struct Hit {
 impl Hit {
 
     pub fn new(outside: bool, normal: Vec3D, incident_ray: Ray, distance: f64) -> Self {
-        Self { incident_ray, normal, distance, outside, local_hit: None }
+        Self { incident_ray, normal, tangent: Vec3D::ZERO, distance, outside, local_hit: None }
     }
 
-    pub fn transformed_as(&self, incident_ray: Ray, normal: Vec3D) -> Self {
-        Self { incident_ray, normal, distance: self.distance, outside: self.outside, local_hit: Some(self.local_hit()) }
+    pub fn with_tangent(self, tangent: Vec3D) -> Self {
+        Self { tangent, ..self }
+    }
+
+    /// Returns a copy of this hit, with the given normal, scaled to the length of the original one.
+    pub fn with_normal(&self, normal: &Vec3D) -> Self {
+        Self { normal: normal.unit() * self.normal.length(), local_hit: Some(self.local_hit()), ..self.clone() }
+    }
+
+    /// Returns a copy of this hit, as if the incident ray came along the given direction.
+    pub fn with_incident_direction(&self, direction: &Vec3D) -> Self {
+        Self { incident_ray: self.incident_ray.with_direction(*direction), local_hit: Some(self.local_hit()), ..self.clone() }
+    }
+
+    pub fn transformed_as(&self, incident_ray: Ray, normal: Vec3D, tangent: Vec3D) -> Self {
+        Self { incident_ray, normal, tangent, distance: self.distance, outside: self.outside, local_hit: Some(self.local_hit()) }
+    }
+
+    /// Returns an orthonormal frame, whose columns are the tangent, the bitangent, and the unit
+    /// normal, in that order. If the tangent is unknown, an arbitrary one is picked.
+    pub fn tangent_frame(&self) -> Matrix {
+        let normal = self.normal.unit();
+        if self.tangent.reject(&normal, true).length_squared() > 1e-12 {
+            Matrix::with_z_and_x_alignment(&normal, &self.tangent)
+        } else {
+            Matrix::with_z_alignment(&normal)
+        }
     }
 
     pub fn local_hit(&self) -> Rc<Self> {
@@ -1,8 +1,10 @@
 use std::f64::consts::PI;
 
+use crate::basic::colors::Color;
 use crate::basic::rays::Ray;
 use crate::basic::vectors::{Dot, Vec3D};
 use crate::geometries::{Geometry, Hit};
+use crate::sampling::{Space, UniformUnitSphere};
 
 pub struct Sphere;
 
@@ -38,6 +40,13 @@ impl Geometry for Sphere {
         Vec3D::new(a, b, 0.0)
     }
 
+    fn sample_surface(&self) -> Option<(Hit, f64)> {
+        let point = UniformUnitSphere.arbitrary_sample();
+        let tangent = Vec3D::new(point.z(), 0.0, -point.x());
+        let ray = Ray::new(point, -point, Color::WHITE, 0.0);
+        Some((Hit::new(true, point, ray, 0.0).with_tangent(tangent), 1.0 / (4.0 * PI)))
+    }
+
 }
 
 impl Sphere {
@@ -58,7 +67,9 @@ impl Sphere {
         } else {
             -4.0 * PI
         };
-        Hit::new(outside, area * point, ray.with_origin(point), distance)
+        // The direction in which the longitude, i.e. the first surface coordinate, increases.
+        let tangent = Vec3D::new(point.z(), 0.0, -point.x());
+        Hit::new(outside, area * point, ray.with_origin(point), distance).with_tangent(tangent)
     }
 
 }
@@ -1,5 +1,5 @@
 use crate::basic::rays::Ray;
-use crate::basic::vectors::Vec3D;
+use crate::basic::vectors::{Dot, Vec3D};
 use crate::geometries::{Geometry, Hit};
 use crate::transforms::{Transformation, Transformed};
 
@@ -15,4 +15,13 @@ impl<G: Geometry, T: Transformation> Geometry for Transformed<G, T> {
         self.subject.surface_coordinates(point)
     }
 
+    /// Affine transformations scale the normals by as much as they scale the areas around them,
+    /// which is what the density of the sampled point gets divided by.
+    fn sample_surface(&self) -> Option<(Hit, f64)> {
+        let (hit, pdf) = self.subject.sample_surface()?;
+        let global_hit = self.transformation.to_global(&hit);
+        let scale = global_hit.normal.length() / hit.normal.length();
+        Some((global_hit, pdf / scale))
+    }
+
 }
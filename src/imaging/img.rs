@@ -1,7 +1,7 @@
 use std::ops::{Index, IndexMut, Mul};
 use std::sync::atomic::{AtomicU16, Ordering};
 
-use image::ImageBuffer;
+use image::{DynamicImage, ImageBuffer, ImageFormat, ImageResult, Rgb, Rgba};
 use rayon::prelude::*;
 
 use crate::basic::colors::Color;
@@ -38,23 +38,64 @@ impl Image {
         }
     }
 
+    /// Loads an image file into linear space. Pixels of high dynamic range images (e.g. OpenEXR and
+    /// Radiance HDR ones) are taken as they are, while those of other images are assumed to be in
+    /// the non-linear space produced by `to_non_linear_space`.
+    pub fn load(file: &str) -> ImageResult<Self> {
+        Self::open(file, |c| c * c)
+    }
+
+    /// Loads an image file holding data other than colors (e.g. a normal map), taking its pixels
+    /// as they are, except for scaling those of low dynamic range images into the range `[0, 1]`.
+    pub fn load_data(file: &str) -> ImageResult<Self> {
+        Self::open(file, |c| c)
+    }
+
+    /// Loads the alpha channel of an image file, as a gray image whose pixels are in the range
+    /// `[0, 1]`. Images without an alpha channel load as white images.
+    pub fn load_alpha(file: &str) -> ImageResult<Self> {
+        let image = image::open(file)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels = image.to_rgba32f().pixels()
+            .map(|Rgba([_, _, _, a])| Color::new(*a as f64, *a as f64, *a as f64))
+            .collect();
+        Ok(Self { pixels, width, height })
+    }
+
+    fn open(file: &str, decode: fn(f64) -> f64) -> ImageResult<Self> {
+        let image = image::open(file)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels = match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => image.to_rgb32f().pixels()
+                .map(|Rgb([r, g, b])| Color::new(*r as f64, *g as f64, *b as f64))
+                .collect(),
+            _ => image.to_rgb8().pixels()
+                .map(|Rgb(components)| {
+                    let [r, g, b] = components.map(|c| decode(c as f64 / 255.0));
+                    Color::new(r, g, b)
+                })
+                .collect(),
+        };
+        Ok(Self { pixels, width, height })
+    }
+
     pub fn stack<S, P>(stack_size: u16, supplier: S, progress: P) -> Image
     where
-        S: Sync + Send + Fn() -> Image,
+        S: Sync + Send + Fn(u16) -> Image,
         P: Sync + Send + Fn(u16)
     {
         let counter = AtomicU16::new(0);
         let supplier_ref = &supplier;
         let image: Image = (0 .. stack_size).into_par_iter()
-            .map(|_| {
-                let img = supplier_ref();
+            .map(|frame| {
+                let img = supplier_ref(frame);
                 progress(counter.fetch_add(1, Ordering::Relaxed) + 1);
                 img
             })
             .reduce_with(|i1, i2| {
                 i1.blend(&i2, |c1, c2| c1 + c2)
             })
-            .unwrap_or_else(supplier_ref);
+            .unwrap_or_else(|| supplier_ref(0));
         let ratio = 1.0 / (stack_size as f64);
         image.map(|c, _, _| c.mul(ratio))
     }
@@ -67,6 +108,10 @@ impl Image {
         self.height
     }
 
+    pub fn pixel(&self, column: usize, row: usize) -> &Color {
+        &self.pixels[row * self.width + column]
+    }
+
     pub fn new_with_same_size(&self) -> Self {
         Self::new(self.width, self.height)
     }
@@ -117,6 +162,27 @@ impl Image {
         buffer.save(file).unwrap_or(());
     }
 
+    /// Saves the image in the given format, reporting failures. High dynamic range formats (i.e.
+    /// OpenEXR and Radiance HDR) get the pixel values as they are, while others get them clamped
+    /// and quantized to 8 bits.
+    pub fn save_as(&self, file: &str, format: ImageFormat) -> ImageResult<()> {
+        match format {
+            ImageFormat::OpenExr | ImageFormat::Hdr => {
+                let buffer = ImageBuffer::from_fn(self.width as u32, self.height as u32, |x, y| {
+                    let color = &self.pixels[y as usize * self.width + x as usize];
+                    Rgb([color.red() as f32, color.green() as f32, color.blue() as f32])
+                });
+                buffer.save_with_format(file, format)
+            },
+            _ => {
+                let buffer = ImageBuffer::from_fn(self.width as u32, self.height as u32, |x, y| {
+                    self.pixels[y as usize * self.width + x as usize].saturated().as_rgb()
+                });
+                buffer.save_with_format(file, format)
+            },
+        }
+    }
+
     pub fn pixel_position_iterator(&self) -> PixelPositionIterator {
         self.pixel_positions_of_rect(0, 0, self.width, self.height)
     }
@@ -0,0 +1,85 @@
+use exr::prelude::{AnyChannel, AnyChannels, Encoding, FlatSamples, ImageAttributes, IntegerBounds, Layer, LayerAttributes, SmallVec, Vec2, WritableImage};
+
+use crate::basic::colors::Color;
+use crate::imaging::Image;
+
+/// A multi-layer render result, containing the rendered (beauty) image, along with auxiliary
+/// images describing the first surfaces hit by camera rays. All layers are in linear space, and
+/// their pixels are zero where camera rays hit nothing.
+///
+/// Scalar layers (depth and identifiers) replicate their values in all three color components.
+#[derive(Clone)]
+pub struct Layers {
+    pub beauty: Image,
+    /// The variance of the estimated pixel colors of the beauty layer.
+    pub variance: Image,
+    pub depth: Image,
+    pub normal: Image,
+    pub albedo: Image,
+    pub surface_coordinates: Image,
+    pub object_id: Image,
+    pub material_id: Image,
+    pub motion: Image,
+}
+
+impl Layers {
+
+    /// Returns the layers mapped to displayable colors, along with their names.
+    pub fn visualized(&self) -> Vec<(&'static str, Image)> {
+        let max_depth = self.depth.pixel_position_iterator()
+            .map(|ref p| self.depth[p].red())
+            .fold(0.0, f64::max);
+        let depth_scale = if max_depth > 0.0 { 1.0 / max_depth } else { 0.0 };
+        let signed = |c: &Color| Color::new(c[0] * 0.5 + 0.5, c[1] * 0.5 + 0.5, c[2] * 0.5 + 0.5);
+        vec![
+            ("beauty", self.beauty.to_non_linear_space()),
+            ("variance", self.variance.to_non_linear_space()),
+            ("depth", self.depth.map(|c, _, _| c * depth_scale)),
+            ("normal", self.normal.map(|c, _, _| if *c != Color::BLACK { signed(c) } else { *c })),
+            ("albedo", self.albedo.to_non_linear_space()),
+            ("uv", self.surface_coordinates.map(|c, _, _| Color::new(c[0] - c[0].floor(), c[1] - c[1].floor(), 0.0))),
+            ("object_id", self.object_id.map(|c, _, _| Color::false_color(c.red() as u32))),
+            ("material_id", self.material_id.map(|c, _, _| Color::false_color(c.red() as u32))),
+            ("motion", self.motion.map(|c, _, _| signed(c))),
+        ]
+    }
+
+    /// Saves each layer as a separate image, whose file name is the given prefix followed by an
+    /// underscore and the layer name, and whose format is deduced from the given extension.
+    pub fn save(&self, prefix: &str, extension: &str) {
+        for (name, image) in self.visualized() {
+            image.save(&format!("{}_{}.{}", prefix, name, extension));
+        }
+    }
+
+    /// Saves all layers, in linear space, into a single multi-layer OpenEXR file.
+    pub fn save_exr(&self, file: &str) -> exr::error::UnitResult {
+        let size = Vec2(self.beauty.width(), self.beauty.height());
+        let layers: Vec<Layer<AnyChannels<FlatSamples>>> = vec![
+            Self::exr_layer(size, "beauty", &self.beauty, &["R", "G", "B"]),
+            Self::exr_layer(size, "variance", &self.variance, &["R", "G", "B"]),
+            Self::exr_layer(size, "depth", &self.depth, &["Z"]),
+            Self::exr_layer(size, "normal", &self.normal, &["X", "Y", "Z"]),
+            Self::exr_layer(size, "albedo", &self.albedo, &["R", "G", "B"]),
+            Self::exr_layer(size, "uv", &self.surface_coordinates, &["U", "V"]),
+            Self::exr_layer(size, "object_id", &self.object_id, &["id"]),
+            Self::exr_layer(size, "material_id", &self.material_id, &["id"]),
+            Self::exr_layer(size, "motion", &self.motion, &["X", "Y", "Z"]),
+        ];
+        let attributes = ImageAttributes::new(IntegerBounds::from_dimensions(size));
+        exr::prelude::Image::from_layers(attributes, layers).write().to_file(file)
+    }
+
+    fn exr_layer(size: Vec2<usize>, name: &str, image: &Image, channel_names: &[&str]) -> Layer<AnyChannels<FlatSamples>> {
+        let channels: SmallVec<[AnyChannel<FlatSamples>; 4]> = channel_names.iter().enumerate()
+            .map(|(i, &channel_name)| {
+                let samples = image.pixel_position_iterator()
+                    .map(|ref p| image[p][i] as f32)
+                    .collect();
+                AnyChannel::new(channel_name, FlatSamples::F32(samples))
+            })
+            .collect();
+        Layer::new(size, LayerAttributes::named(name), Encoding::FAST_LOSSLESS, AnyChannels::sort(channels))
+    }
+
+}
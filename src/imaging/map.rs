@@ -0,0 +1,156 @@
+use crate::basic::colors::Color;
+use crate::imaging::Image;
+
+/// An image that could be looked up at continuous UV coordinates, where the unit square
+/// `[0, 1] x [0, 1]` covers the whole image, and the top row of the image maps to `v = 1`.
+/// Coordinates outside the unit square are wrapped around, according to the wrapping mode.
+pub struct ImageMap {
+    pub image: Image,
+    pub filtering: Filtering,
+    pub wrapping: Wrapping,
+}
+
+/// How colors are interpolated between the centers of neighbouring pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Filtering {
+    /// Takes the color of the nearest pixel.
+    Nearest,
+    /// Interpolates linearly between the 2x2 nearest pixels.
+    Bilinear,
+    /// Interpolates, using Catmull-Rom splines, between the 4x4 nearest pixels. It is sharper than
+    /// bilinear filtering, but could slightly overshoot at edges.
+    Bicubic,
+}
+
+/// How pixels beyond the edges of the image are resolved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wrapping {
+    /// Tiles the image.
+    Repeat,
+    /// Extends the edge pixels.
+    Clamp,
+    /// Tiles the image, flipping every other tile.
+    Mirror,
+}
+
+impl ImageMap {
+
+    pub fn new(image: Image) -> Self {
+        Self { image, filtering: Filtering::Bilinear, wrapping: Wrapping::Repeat }
+    }
+
+    pub fn with_filtering(self, filtering: Filtering) -> Self {
+        Self { filtering, ..self }
+    }
+
+    pub fn with_wrapping(self, wrapping: Wrapping) -> Self {
+        Self { wrapping, ..self }
+    }
+
+    pub fn color_at(&self, u: f64, v: f64) -> Color {
+        let x = u * self.image.width() as f64 - 0.5;
+        let y = (1.0 - v) * self.image.height() as f64 - 0.5;
+        match self.filtering {
+            Filtering::Nearest => self.texel(x.round() as i64, y.round() as i64),
+            Filtering::Bilinear => self.interpolated(x, y, 1, |t| [1.0 - t, t]),
+            Filtering::Bicubic => {
+                let color = self.interpolated(x, y, 2, catmull_rom_weights);
+                Color::new(color.red().max(0.0), color.green().max(0.0), color.blue().max(0.0))
+            },
+        }
+    }
+
+    /// Blends the `2 * reach` x `2 * reach` pixels around the given position, using the separable
+    /// weights produced by the given function out of the fractional parts of the position.
+    fn interpolated<const N: usize, F: Fn(f64) -> [f64; N]>(&self, x: f64, y: f64, reach: i64, weights: F) -> Color {
+        let (column, row) = (x.floor(), y.floor());
+        let (wx, wy) = (weights(x - column), weights(y - row));
+        let (column, row) = (column as i64 - reach + 1, row as i64 - reach + 1);
+        let mut color = Color::BLACK;
+        for (j, wy) in wy.iter().enumerate() {
+            for (i, wx) in wx.iter().enumerate() {
+                color += self.texel(column + i as i64, row + j as i64) * (wx * wy);
+            }
+        }
+        color
+    }
+
+    fn texel(&self, column: i64, row: i64) -> Color {
+        let column = self.wrapping.wrap(column, self.image.width());
+        let row = self.wrapping.wrap(row, self.image.height());
+        *self.image.pixel(column, row)
+    }
+
+}
+
+impl Wrapping {
+
+    fn wrap(&self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        (match self {
+            Wrapping::Repeat => index.rem_euclid(size),
+            Wrapping::Clamp => index.clamp(0, size - 1),
+            Wrapping::Mirror => {
+                let index = index.rem_euclid(2 * size);
+                if index < size { index } else { 2 * size - 1 - index }
+            },
+        }) as usize
+    }
+
+}
+
+fn catmull_rom_weights(t: f64) -> [f64; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::tests::color;
+
+    use super::*;
+
+    fn checker(column: usize, row: usize) -> Color {
+        if (column + row).is_multiple_of(2) { Color::WHITE } else { Color::BLACK }
+    }
+
+    proptest! {
+
+        #[test]
+        fn reproduces_pixels_at_their_centers(column in 0..4usize, row in 0..3usize, tile_u in -2..2i32, tile_v in -2..2i32) {
+            let u = (column as f64 + 0.5) / 4.0 + tile_u as f64;
+            let v = 1.0 - (row as f64 + 0.5) / 3.0 + tile_v as f64;
+            for filtering in [Filtering::Nearest, Filtering::Bilinear, Filtering::Bicubic] {
+                let map = ImageMap::new(Image::init(4, 3, checker)).with_filtering(filtering);
+                let color = map.color_at(u, v);
+                let expected = checker(column, row);
+                for c in 0..3 {
+                    assert!((color[c] - expected[c]).abs() < 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn preserves_uniform_images(shade in color(), u in -3.0..3.0, v in -3.0..3.0) {
+            for filtering in [Filtering::Nearest, Filtering::Bilinear, Filtering::Bicubic] {
+                for wrapping in [Wrapping::Repeat, Wrapping::Clamp, Wrapping::Mirror] {
+                    let map = ImageMap::new(Image::solid(shade, 5, 3)).with_filtering(filtering).with_wrapping(wrapping);
+                    let color = map.color_at(u, v);
+                    for c in 0..3 {
+                        assert!((color[c] - shade[c]).abs() < 1e-9);
+                    }
+                }
+            }
+        }
+
+    }
+
+}
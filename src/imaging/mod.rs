@@ -1,6 +1,12 @@
 pub use img::*;
 pub use iterator::*;
+pub use layers::*;
+pub use map::*;
+pub use tone_mapping::*;
 
 mod img;
 mod iterator;
+mod layers;
+mod map;
+mod tone_mapping;
 
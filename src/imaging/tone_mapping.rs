@@ -0,0 +1,47 @@
+use crate::basic::colors::Color;
+use crate::imaging::Image;
+
+/// Tone mappers compress the unbounded intensities of rendered images into the displayable range
+/// `[0, 1]`, before the images get converted into non-linear space.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ToneMapper {
+    /// Clips intensities exceeding 1, which is what `Image::to_non_linear_space` does.
+    Clamp,
+    /// The extended Reinhard operator, applied to luminance. It compresses highlights smoothly,
+    /// mapping luminance values at the given white point, or brighter, to 1.
+    Reinhard { white: f64 },
+    /// Narkowicz's fit of the ACES filmic curve, applied to each channel. Its response is a bit
+    /// brighter and more contrasty than Reinhard's.
+    Aces,
+}
+
+impl ToneMapper {
+
+    pub fn map(&self, color: &Color) -> Color {
+        match self {
+            ToneMapper::Clamp => color.saturated(),
+            ToneMapper::Reinhard { white } => {
+                let luminance = color.luminance();
+                if luminance <= 0.0 {
+                    return Color::BLACK
+                }
+                let mapped = luminance * (1.0 + luminance / (white * white)) / (1.0 + luminance);
+                (color * (mapped / luminance)).saturated()
+            },
+            ToneMapper::Aces => {
+                let aces = |c: f64| {
+                    let x = 0.6 * c.max(0.0);
+                    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+                };
+                Color::new(aces(color.red()), aces(color.green()), aces(color.blue())).saturated()
+            },
+        }
+    }
+
+    /// Maps the given image, which is in linear space, and converts the result into non-linear
+    /// space.
+    pub fn develop(&self, linear: &Image) -> Image {
+        linear.map(|c, _, _| self.map(c).corrected())
+    }
+
+}
@@ -12,6 +12,8 @@ pub mod builders;
 pub mod noise;
 pub mod basic;
 pub mod filters;
+pub mod scenes;
+pub mod lights;
 pub mod wgpu;
 pub mod win;
 
@@ -22,6 +24,13 @@ pub enum Holder<'a, T: 'a + ?Sized> {
     Owning(Box<T>)
 }
 
+/// Wraps a thing or a texture, giving it an identifier that shows up in the auxiliary data of
+/// rendered images (e.g. object and material ID layers).
+pub struct Tagged<S> {
+    pub subject: S,
+    pub id: u32,
+}
+
 pub fn rough_equality(a: f64, b: f64) -> bool {
     (a - b).abs() <= EPSILON
 }
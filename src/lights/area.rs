@@ -0,0 +1,335 @@
+use std::f64::consts::PI;
+
+use crate::basic::colors::Color;
+use crate::basic::matrices::Matrix;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::Lambertian;
+use crate::lights::{cone_pdf, sample_cone, Emission, Light, LightBounds, LightSample};
+use crate::sampling::{random, Space, UniformUnitSphere};
+
+/// A rectangular (or, more generally, parallelogram) light, centered at the given position, and
+/// spanning the given half edges. It emits the given radiance uniformly from its front side, which
+/// faces the direction of the cross product of its half edges.
+pub struct RectangleLight {
+    pub center: Vec3D,
+    pub half_edges: (Vec3D, Vec3D),
+    pub radiance: Color,
+}
+
+/// A disk light, centered at the given position, emitting the given radiance uniformly from its
+/// front side, which faces the direction of its normal.
+pub struct DiskLight {
+    pub center: Vec3D,
+    pub normal: Vec3D,
+    pub radius: f64,
+    pub radiance: Color,
+}
+
+/// A spherical light, emitting the given radiance uniformly from its whole surface.
+pub struct SphereLight {
+    pub center: Vec3D,
+    pub radius: f64,
+    pub radiance: Color,
+}
+
+/// A flat light, which is sampled uniformly over its area, and which emits from its front side.
+trait Planar {
+
+    fn center(&self) -> &Vec3D;
+
+    fn normal(&self) -> Vec3D;
+
+    fn area(&self) -> f64;
+
+    fn radiance(&self) -> &Color;
+
+    fn sample_point(&self) -> Vec3D;
+
+    fn contains(&self, offset: &Vec3D) -> bool;
+
+    /// The half extents, along every axis, of the box enclosing the light around its center.
+    fn half_extents(&self) -> Vec3D;
+
+    /// The distance along the given ray at which it hits the front side of the light, if it does.
+    fn distance(&self, ray: &Ray) -> Option<f64> {
+        let normal = self.normal();
+        let cos_angle = ray.direction.dot(normal);
+        if cos_angle >= 0.0 {
+            return None
+        }
+        let distance = (self.center() - &ray.origin).dot(normal) / cos_angle;
+        if distance > 0.0 && self.contains(&(ray.at(distance) - *self.center())) {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// Converts the density of sampling a point at the given distance, and at the given cosine with
+    /// the normal, from area measure to solid angle measure.
+    fn solid_angle_pdf(&self, distance: f64, cos_angle: f64) -> f64 {
+        distance * distance / (self.area() * cos_angle)
+    }
+
+}
+
+impl<P: Planar + Send + Sync> Light for P {
+
+    fn sample(&self, position: &Vec3D) -> Option<LightSample> {
+        let offset = self.sample_point() - *position;
+        let distance = offset.length();
+        let direction = offset / distance;
+        let cos_angle = -direction.dot(self.normal());
+        if cos_angle <= 0.0 {
+            return None
+        }
+        Some(LightSample { direction, distance, radiance: *self.radiance(), pdf: self.solid_angle_pdf(distance, cos_angle) })
+    }
+
+    fn pdf(&self, position: &Vec3D, direction: &Vec3D) -> f64 {
+        match self.distance(&Ray::new(*position, *direction, Color::WHITE, 0.0)) {
+            Some(distance) => self.solid_angle_pdf(distance, -direction.dot(&self.normal())),
+            None => 0.0,
+        }
+    }
+
+    fn eval(&self, ray: &Ray) -> Option<(f64, Color)> {
+        self.distance(ray).map(|distance| (distance, *self.radiance()))
+    }
+
+    fn power(&self) -> f64 {
+        PI * self.area() * self.radiance().luminance()
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let (center, half_extents) = (*self.center(), self.half_extents());
+        Some(LightBounds {
+            min: center - half_extents,
+            max: center + half_extents,
+            power: self.power(),
+            axis: self.normal(),
+            cos_theta_o: 1.0,
+            cos_theta_e: 0.0,
+            two_sided: false,
+        })
+    }
+
+    fn emit(&self) -> Option<Emission> {
+        let normal = self.normal();
+        let (direction, pdf_direction) = Lambertian::new(&normal).arbitrary_sample_and_pdf();
+        Some(Emission {
+            origin: self.sample_point(),
+            direction,
+            normal: Some(normal),
+            radiance: *self.radiance(),
+            pdf_position: 1.0 / self.area(),
+            pdf_direction,
+        })
+    }
+
+    fn emission_pdf(&self, _: &Vec3D, direction: &Vec3D) -> Option<(f64, f64)> {
+        Some((1.0 / self.area(), direction.dot(&self.normal()).max(0.0) / PI))
+    }
+
+    fn normal_at(&self, _: &Vec3D) -> Option<Vec3D> {
+        Some(self.normal())
+    }
+
+}
+
+impl RectangleLight {
+
+    pub fn new(center: Vec3D, half_edge_1: Vec3D, half_edge_2: Vec3D, radiance: Color) -> Self {
+        Self { center, half_edges: (half_edge_1, half_edge_2), radiance }
+    }
+
+}
+
+impl Planar for RectangleLight {
+
+    fn center(&self) -> &Vec3D {
+        &self.center
+    }
+
+    fn normal(&self) -> Vec3D {
+        let (ref u, ref v) = self.half_edges;
+        u.cross(v).unit()
+    }
+
+    fn area(&self) -> f64 {
+        let (ref u, ref v) = self.half_edges;
+        4.0 * u.cross(v).length()
+    }
+
+    fn radiance(&self) -> &Color {
+        &self.radiance
+    }
+
+    fn sample_point(&self) -> Vec3D {
+        let (u, v) = self.half_edges;
+        self.center + u * (2.0 * random::<f64>() - 1.0) + v * (2.0 * random::<f64>() - 1.0)
+    }
+
+    fn contains(&self, offset: &Vec3D) -> bool {
+        let (ref u, ref v) = self.half_edges;
+        let normal = u.cross(v);
+        let area = normal.length_squared();
+        let a = offset.cross(v).dot(normal) / area;
+        let b = u.cross(offset).dot(normal) / area;
+        a.abs() <= 1.0 && b.abs() <= 1.0
+    }
+
+    fn half_extents(&self) -> Vec3D {
+        let (ref u, ref v) = self.half_edges;
+        Vec3D::new(u.x().abs() + v.x().abs(), u.y().abs() + v.y().abs(), u.z().abs() + v.z().abs())
+    }
+
+}
+
+impl DiskLight {
+
+    pub fn new(center: Vec3D, normal: Vec3D, radius: f64, radiance: Color) -> Self {
+        Self { center, normal: normal.unit(), radius, radiance }
+    }
+
+}
+
+impl Planar for DiskLight {
+
+    fn center(&self) -> &Vec3D {
+        &self.center
+    }
+
+    fn normal(&self) -> Vec3D {
+        self.normal
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    fn radiance(&self) -> &Color {
+        &self.radiance
+    }
+
+    fn sample_point(&self) -> Vec3D {
+        let r = self.radius * random::<f64>().sqrt();
+        let phi = 2.0 * PI * random::<f64>();
+        self.center + &Matrix::with_z_alignment(&self.normal) * &Vec3D::new(r * phi.cos(), r * phi.sin(), 0.0)
+    }
+
+    fn contains(&self, offset: &Vec3D) -> bool {
+        offset.length_squared() <= self.radius * self.radius
+    }
+
+    fn half_extents(&self) -> Vec3D {
+        let extent = |n: f64| self.radius * (1.0 - n * n).max(0.0).sqrt();
+        Vec3D::new(extent(self.normal.x()), extent(self.normal.y()), extent(self.normal.z()))
+    }
+
+}
+
+impl SphereLight {
+
+    pub fn new(center: Vec3D, radius: f64, radiance: Color) -> Self {
+        Self { center, radius, radiance }
+    }
+
+    /// The cosine of the half angle of the cone the sphere subtends as seen from the given
+    /// position, or nothing if the position is within the sphere.
+    fn cos_max(&self, position: &Vec3D) -> Option<f64> {
+        let distance_squared = (self.center - *position).length_squared();
+        let sin_squared = self.radius * self.radius / distance_squared;
+        if sin_squared < 1.0 { Some((1.0 - sin_squared).sqrt()) } else { None }
+    }
+
+    /// The distance along the given ray at which it hits the sphere from the outside, if it does.
+    fn distance(&self, ray: &Ray) -> Option<f64> {
+        let offset = ray.origin - self.center;
+        let a = ray.direction.length_squared();
+        let b = offset.dot(ray.direction);
+        let c = offset.length_squared() - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+        if c <= 0.0 || discriminant < 0.0 {
+            return None
+        }
+        let distance = (-b - discriminant.sqrt()) / a;
+        if distance > 0.0 { Some(distance) } else { None }
+    }
+
+}
+
+impl Light for SphereLight {
+
+    fn sample(&self, position: &Vec3D) -> Option<LightSample> {
+        let cos_max = self.cos_max(position)?;
+        let direction = sample_cone(&(self.center - *position).unit(), cos_max);
+        // Directions grazing the sphere could miss it, due to rounding errors.
+        let distance = self.distance(&Ray::new(*position, direction, Color::WHITE, 0.0))
+            .unwrap_or_else(|| (self.center - *position).length());
+        Some(LightSample { direction, distance, radiance: self.radiance, pdf: cone_pdf(cos_max) })
+    }
+
+    fn pdf(&self, position: &Vec3D, direction: &Vec3D) -> f64 {
+        match self.cos_max(position) {
+            Some(cos_max) if direction.dot(&(self.center - *position).unit()) >= cos_max => cone_pdf(cos_max),
+            _ => 0.0,
+        }
+    }
+
+    fn eval(&self, ray: &Ray) -> Option<(f64, Color)> {
+        self.distance(ray).map(|distance| (distance, self.radiance))
+    }
+
+    fn power(&self) -> f64 {
+        4.0 * PI * PI * self.radius * self.radius * self.radiance.luminance()
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let half_extents = Vec3D::new(self.radius, self.radius, self.radius);
+        Some(LightBounds::omnidirectional(self.center - half_extents, self.center + half_extents, self.power()))
+    }
+
+    fn emit(&self) -> Option<Emission> {
+        let normal = UniformUnitSphere.arbitrary_sample();
+        let (direction, pdf_direction) = Lambertian::new(&normal).arbitrary_sample_and_pdf();
+        Some(Emission {
+            origin: self.center + normal * self.radius,
+            direction,
+            normal: Some(normal),
+            radiance: self.radiance,
+            pdf_position: 1.0 / (4.0 * PI * self.radius * self.radius),
+            pdf_direction,
+        })
+    }
+
+    fn emission_pdf(&self, point: &Vec3D, direction: &Vec3D) -> Option<(f64, f64)> {
+        let normal = (*point - self.center).unit();
+        Some((1.0 / (4.0 * PI * self.radius * self.radius), direction.dot(&normal).max(0.0) / PI))
+    }
+
+    fn normal_at(&self, point: &Vec3D) -> Option<Vec3D> {
+        Some((*point - self.center).unit())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lights::tests::assert_consistent_sampling;
+
+    use super::*;
+
+    #[test]
+    fn samples_area_lights_consistently() {
+        let radiance = Color::new(1.0, 0.5, 0.25);
+        for (x, y, size) in [(0.0, 0.0, 0.5), (1.0, -0.5, 1.0), (-1.0, 1.0, 2.0)] {
+            let position = Vec3D::new(x, y, 4.0);
+            assert_consistent_sampling(&RectangleLight::new(Vec3D::zero(), Vec3D::X * size, Vec3D::Y * (size / 2.0), radiance), &position, size * 1.2);
+            assert_consistent_sampling(&DiskLight::new(Vec3D::zero(), Vec3D::new(0.2, 0.1, 1.0), size, radiance), &position, size);
+            assert_consistent_sampling(&SphereLight::new(Vec3D::zero(), size / 2.0, radiance), &position, size / 2.0);
+        }
+    }
+
+}
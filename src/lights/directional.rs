@@ -0,0 +1,103 @@
+use std::f64::consts::PI;
+
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::lights::{cone_pdf, sample_cone, Light, LightSample};
+
+/// A light arriving from infinitely far away, along a single direction, like sunlight. The light
+/// is given as the irradiance it causes on surfaces facing it. Unless it has a zero angular radius,
+/// the light source is a disk in the sky (e.g. the sun, whose angular radius is about 0.27 degrees),
+/// which softens the shadows it casts, and which could be seen by rays.
+pub struct DirectionalLight {
+    pub direction: Vec3D,
+    pub irradiance: Color,
+    pub cos_angular_radius: f64,
+}
+
+impl DirectionalLight {
+
+    /// Creates a directional light, shining in the given direction.
+    pub fn new(direction: Vec3D, irradiance: Color) -> Self {
+        Self { direction: direction.unit(), irradiance, cos_angular_radius: 1.0 }
+    }
+
+    /// Sets the angular radius (in radians) of the light source.
+    pub fn with_angular_radius(self, angular_radius: f64) -> Self {
+        Self { cos_angular_radius: angular_radius.cos(), ..self }
+    }
+
+    fn radiance(&self) -> Color {
+        self.irradiance * cone_pdf(self.cos_angular_radius)
+    }
+
+}
+
+impl Light for DirectionalLight {
+
+    fn sample(&self, _: &Vec3D) -> Option<LightSample> {
+        if self.is_delta() {
+            return Some(LightSample { direction: -self.direction, distance: f64::INFINITY, radiance: self.irradiance, pdf: 1.0 })
+        }
+        let direction = sample_cone(&-self.direction, self.cos_angular_radius);
+        Some(LightSample { direction, distance: f64::INFINITY, radiance: self.radiance(), pdf: cone_pdf(self.cos_angular_radius) })
+    }
+
+    fn pdf(&self, _: &Vec3D, direction: &Vec3D) -> f64 {
+        if !self.is_delta() && -direction.dot(&self.direction) >= self.cos_angular_radius {
+            cone_pdf(self.cos_angular_radius)
+        } else {
+            0.0
+        }
+    }
+
+    fn eval(&self, ray: &Ray) -> Option<(f64, Color)> {
+        if !self.is_delta() && -ray.direction.unit().dot(self.direction) >= self.cos_angular_radius {
+            Some((f64::INFINITY, self.radiance()))
+        } else {
+            None
+        }
+    }
+
+    /// The power falling on a unit disk facing the light, as directional lights illuminate whole
+    /// scenes, whose extents they do not know.
+    fn power(&self) -> f64 {
+        PI * self.irradiance.luminance()
+    }
+
+    fn is_delta(&self) -> bool {
+        self.cos_angular_radius >= 1.0
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shines_along_its_direction_unless_it_has_an_angular_radius() {
+        let light = DirectionalLight::new(Vec3D::new(1.0, -2.0, 0.5), Color::new(3.0, 2.0, 1.0));
+        let sample = light.sample(&Vec3D::zero()).unwrap();
+        assert!((sample.direction + light.direction).length() < 1e-9);
+        assert_eq!((sample.radiance, sample.pdf, sample.distance), (light.irradiance, 1.0, f64::INFINITY));
+        assert!(light.is_delta());
+        assert!(light.eval(&Ray::new(Vec3D::zero(), -light.direction, Color::WHITE, 0.0)).is_none());
+    }
+
+    #[test]
+    fn gives_its_irradiance_to_surfaces_facing_it() {
+        let light = DirectionalLight::new(-Vec3D::Y, Color::new(3.0, 2.0, 1.0)).with_angular_radius(0.05);
+        let count = 10000;
+        let irradiance = (0 .. count).fold(Color::BLACK, |sum, _| {
+            let sample = light.sample(&Vec3D::zero()).unwrap();
+            assert!((sample.pdf - light.pdf(&Vec3D::zero(), &sample.direction)).abs() < 1e-9 * sample.pdf);
+            let (distance, radiance) = light.eval(&Ray::new(Vec3D::zero(), sample.direction, Color::WHITE, 0.0)).unwrap();
+            assert_eq!((distance, radiance), (f64::INFINITY, sample.radiance));
+            sum + sample.radiance * (sample.direction.y() / sample.pdf)
+        }) / count as f64;
+        assert!((irradiance.luminance() - light.irradiance.luminance()).abs() < 0.01 * light.irradiance.luminance(), "{:?}", irradiance);
+        assert!(light.eval(&Ray::new(Vec3D::zero(), Vec3D::new(0.1, 1.0, 0.0), Color::WHITE, 0.0)).is_none());
+    }
+
+}
@@ -0,0 +1,379 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+pub use area::*;
+pub use directional::*;
+pub use point::*;
+pub use spot::*;
+pub use tree::*;
+
+use crate::basic::colors::Color;
+use crate::basic::matrices::Matrix;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::Vec3D;
+use crate::sampling::random;
+
+mod point;
+mod spot;
+mod directional;
+mod area;
+mod tree;
+
+/// A light source that could be sampled directly from the surfaces it illuminates. Lights are not
+/// part of the things of a world; they do not block rays, though area and sun lights could be seen
+/// by rays hitting them.
+pub trait Light: Send + Sync {
+
+    /// Samples a direction, from the given position, towards the light, along with the radiance
+    /// arriving from it in that direction.
+    fn sample(&self, position: &Vec3D) -> Option<LightSample>;
+
+    /// The solid angle density of sampling the given unit direction from the given position. It is
+    /// zero for delta lights, which are sampled along a single direction.
+    fn pdf(&self, position: &Vec3D, direction: &Vec3D) -> f64;
+
+    /// The distance along the given ray at which it hits the light, if it does, and the radiance it
+    /// sees there.
+    fn eval(&self, ray: &Ray) -> Option<(f64, Color)>;
+
+    /// The total power emitted by the light, which makes it more or less likely to be picked for
+    /// sampling, among other lights.
+    fn power(&self) -> f64;
+
+    /// The bounds of the light emitted by the light, used to estimate its contribution at given
+    /// positions, unless it has none, like lights infinitely far away.
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+
+    /// Whether the light is a delta light, like point lights, which could not be hit by rays.
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// Samples a ray of the light emitted by the light, for tracing light from it. Lights that
+    /// cannot start such rays, like directional lights, which do not know where to emit from,
+    /// return `None`, which is the default.
+    fn emit(&self) -> Option<Emission> {
+        None
+    }
+
+    /// The densities with which `emit` samples the given point on the light (in area measure, or
+    /// one for lights at a single point), and the given unit direction from it (in solid angle
+    /// measure), or `None` for lights that cannot emit.
+    fn emission_pdf(&self, _point: &Vec3D, _direction: &Vec3D) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// The unit normal of the light surface at the given point on it, or `None` for lights that
+    /// have no surface.
+    fn normal_at(&self, _point: &Vec3D) -> Option<Vec3D> {
+        None
+    }
+
+}
+
+impl<L: Light + ?Sized> Light for Arc<L> {
+
+    fn sample(&self, position: &Vec3D) -> Option<LightSample> {
+        self.as_ref().sample(position)
+    }
+
+    fn pdf(&self, position: &Vec3D, direction: &Vec3D) -> f64 {
+        self.as_ref().pdf(position, direction)
+    }
+
+    fn eval(&self, ray: &Ray) -> Option<(f64, Color)> {
+        self.as_ref().eval(ray)
+    }
+
+    fn power(&self) -> f64 {
+        self.as_ref().power()
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        self.as_ref().bounds()
+    }
+
+    fn is_delta(&self) -> bool {
+        self.as_ref().is_delta()
+    }
+
+    fn emit(&self) -> Option<Emission> {
+        self.as_ref().emit()
+    }
+
+    fn emission_pdf(&self, point: &Vec3D, direction: &Vec3D) -> Option<(f64, f64)> {
+        self.as_ref().emission_pdf(point, direction)
+    }
+
+    fn normal_at(&self, point: &Vec3D) -> Option<Vec3D> {
+        self.as_ref().normal_at(point)
+    }
+
+}
+
+/// A sample of the light arriving at some position from a light source. The direction is a unit
+/// vector pointing towards the light, which is at the given distance (possibly infinite). For delta
+/// lights, the pdf is one.
+#[derive(Clone, Debug)]
+pub struct LightSample {
+    pub direction: Vec3D,
+    pub distance: f64,
+    pub radiance: Color,
+    pub pdf: f64,
+}
+
+/// A ray of light emitted by a light, from the given origin, along the given unit direction, with
+/// the given radiance (or intensity, for lights at a single point), along with the densities of
+/// sampling the origin and the direction (see `Light::emission_pdf`). Origins on light surfaces
+/// have the normals of those surfaces.
+#[derive(Clone, Debug)]
+pub struct Emission {
+    pub origin: Vec3D,
+    pub direction: Vec3D,
+    pub normal: Option<Vec3D>,
+    pub radiance: Color,
+    pub pdf_position: f64,
+    pub pdf_direction: f64,
+}
+
+/// A collection of lights, from which lights are picked with probabilities following estimates of
+/// their contributions at the positions they illuminate. Lights with bounds are organized in a
+/// `LightTree`, which makes picking them efficient, even among thousands of lights. Lights without
+/// bounds, like directional lights, are each picked as often as the whole tree, and otherwise in
+/// proportion to their powers.
+///
+/// The tree could also hold emissive surfaces of things (see `with_surfaces`), which get picked
+/// along with the lights, but which only the worlds made of those things know how to sample.
+#[derive(Clone, Default)]
+pub struct Lights {
+    lights: Vec<Arc<dyn Light>>,
+    surfaces: Vec<EmissiveSurface>,
+    tree: LightTree,
+    unbounded: Vec<usize>,
+    unbounded_cumulative_powers: Vec<f64>,
+    cumulative_powers: Vec<f64>,
+}
+
+impl Lights {
+
+    pub fn new(lights: Vec<Arc<dyn Light>>) -> Self {
+        Self::build(lights, Vec::new())
+    }
+
+    /// Returns these lights, along with the given emissive surfaces, sorted by index, which come
+    /// after the lights, by index, when picked.
+    pub fn with_surfaces(&self, surfaces: Vec<EmissiveSurface>) -> Self {
+        Self::build(self.lights.clone(), surfaces)
+    }
+
+    fn build(lights: Vec<Arc<dyn Light>>, surfaces: Vec<EmissiveSurface>) -> Self {
+        let bounds: Vec<_> = lights.iter().map(|light| light.bounds())
+            .chain(surfaces.iter().map(|surface| Some(surface.bounds)))
+            .collect();
+        let tree = LightTree::new(&bounds);
+        let unbounded: Vec<_> = (0..lights.len()).filter(|&index| bounds[index].is_none()).collect();
+        let unbounded_cumulative_powers = cumulative_powers(unbounded.iter().map(|&index| &lights[index]));
+        let cumulative_powers = cumulative_powers(lights.iter());
+        Self { lights, surfaces, tree, unbounded, unbounded_cumulative_powers, cumulative_powers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty() && self.surfaces.is_empty()
+    }
+
+    pub fn lights(&self) -> &[Arc<dyn Light>] {
+        &self.lights
+    }
+
+    /// Returns the emissive surface at the given index, if it is not the index of a light.
+    pub fn surface(&self, index: usize) -> Option<&EmissiveSurface> {
+        self.surfaces.get(index.checked_sub(self.lights.len())?)
+    }
+
+    /// Returns the index, when picked, of the emissive surface that has the given index among the
+    /// surfaces of things, if it is one of the emissive surfaces, along with that surface.
+    pub fn index_of_surface(&self, surface_index: usize) -> Option<(usize, &EmissiveSurface)> {
+        let i = self.surfaces.binary_search_by_key(&surface_index, |surface| surface.index).ok()?;
+        Some((self.lights.len() + i, &self.surfaces[i]))
+    }
+
+    /// The probability of picking among the lights without bounds rather than from the tree.
+    fn unbounded_probability(&self) -> f64 {
+        match (self.unbounded.len(), self.tree.is_empty()) {
+            (0, _) => 0.0,
+            (_, true) => 1.0,
+            (count, false) => count as f64 / (count + 1) as f64,
+        }
+    }
+
+    /// Picks a light at random, for illuminating the given position, returning it along with the
+    /// probability of picking it, or nothing if an emissive surface is picked instead.
+    pub fn pick(&self, position: &Vec3D) -> Option<(&dyn Light, f64)> {
+        let (index, probability) = self.pick_index(position)?;
+        Some((self.lights.get(index)?.as_ref(), probability))
+    }
+
+    /// Same as `pick`, except that it returns the index of the picked light, or emissive surface.
+    pub fn pick_index(&self, position: &Vec3D) -> Option<(usize, f64)> {
+        let unbounded_probability = self.unbounded_probability();
+        if random::<f64>() < unbounded_probability {
+            let index = self.unbounded[pick_by_power(&self.unbounded_cumulative_powers)?];
+            Some((index, self.probability(index, position)))
+        } else {
+            let (index, probability) = self.tree.pick(position)?;
+            Some((index, (1.0 - unbounded_probability) * probability))
+        }
+    }
+
+    /// The probability of picking the light, or emissive surface, at the given index, for
+    /// illuminating the given position.
+    pub fn probability(&self, index: usize, position: &Vec3D) -> f64 {
+        let unbounded_probability = self.unbounded_probability();
+        match self.unbounded.binary_search(&index) {
+            Ok(i) => unbounded_probability * power_probability(&self.unbounded_cumulative_powers, i),
+            Err(_) => (1.0 - unbounded_probability) * self.tree.probability(index, position),
+        }
+    }
+
+    /// Picks a light at random, in proportion to its power only, regardless of the positions it
+    /// illuminates, returning its index, along with the probability of picking it. This suits
+    /// picking lights to trace light from.
+    pub fn pick_by_power(&self) -> Option<(usize, f64)> {
+        let index = pick_by_power(&self.cumulative_powers)?;
+        Some((index, self.power_probability(index)))
+    }
+
+    /// The probability of picking the light at the given index with `pick_by_power`.
+    pub fn power_probability(&self, index: usize) -> f64 {
+        power_probability(&self.cumulative_powers, index)
+    }
+
+    /// Returns the nearest light the given ray hits before the given distance, if any, as the index
+    /// of the light, the distance to it, and the radiance the ray sees there.
+    pub fn hit(&self, ray: &Ray, max: f64) -> Option<(usize, f64, Color)> {
+        let mut nearest: Option<(usize, f64, Color)> = None;
+        let mut try_light = |index: usize| {
+            let max = nearest.map_or(max, |(_, distance, _)| distance);
+            if let Some((distance, radiance)) = self.lights[index].eval(ray) {
+                if distance > 0.0 && distance <= max {
+                    nearest = Some((index, distance, radiance));
+                }
+            }
+        };
+        self.unbounded.iter().for_each(|&index| try_light(index));
+        self.tree.visit(ray, max, |index| if index < self.lights.len() { try_light(index) });
+        nearest
+    }
+
+}
+
+/// An emissive surface of things, given by its index among their surfaces (see `Thing::surfaces`),
+/// along with the bounds of its light, and the average area density of sampling it.
+#[derive(Copy, Clone, Debug)]
+pub struct EmissiveSurface {
+    pub index: usize,
+    pub bounds: LightBounds,
+    pub density: f64,
+}
+
+fn cumulative_powers<'a, I: Iterator<Item = &'a Arc<dyn Light>>>(lights: I) -> Vec<f64> {
+    let mut sum = 0.0;
+    lights
+        .map(|light| {
+            sum += light.power().max(0.0);
+            sum
+        })
+        .collect()
+}
+
+fn pick_by_power(cumulative_powers: &[f64]) -> Option<usize> {
+    let total = *cumulative_powers.last()?;
+    if total <= 0.0 {
+        return None
+    }
+    let threshold = random::<f64>() * total;
+    Some(cumulative_powers.partition_point(|&sum| sum <= threshold).min(cumulative_powers.len() - 1))
+}
+
+fn power_probability(cumulative_powers: &[f64], index: usize) -> f64 {
+    let total = cumulative_powers.last().copied().unwrap_or(0.0);
+    let previous = if index > 0 { cumulative_powers[index - 1] } else { 0.0 };
+    if total > 0.0 { (cumulative_powers[index] - previous) / total } else { 0.0 }
+}
+
+/// Samples a unit direction uniformly from the cone around the given unit axis, whose half angle
+/// has the given cosine.
+fn sample_cone(axis: &Vec3D, cos_max: f64) -> Vec3D {
+    let cos_theta = 1.0 - random::<f64>() * (1.0 - cos_max);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * random::<f64>();
+    &Matrix::with_z_alignment(axis) * &Vec3D::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+fn cone_pdf(cos_max: f64) -> f64 {
+    1.0 / (2.0 * PI * (1.0 - cos_max))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::Dot;
+
+    use super::*;
+
+    /// Checks that the average of the radiance of samples, weighted by their pdfs, matches the
+    /// radiance of rays hitting the light uniformly from all the directions of the cone enclosing
+    /// the sphere of the given radius around the origin, which is assumed to enclose the light.
+    /// Also checks that emitted rays have the densities the light reports for them.
+    pub fn assert_consistent_sampling<L: Light>(light: &L, position: &Vec3D, radius: f64) {
+        let axis = (-*position).unit();
+        let cos_max = (1.0 - radius * radius / position.length_squared()).sqrt();
+        let count = 20000;
+        let mut sampled = Color::BLACK;
+        let mut hit = Color::BLACK;
+        for _ in 0..count {
+            if let Some(sample) = light.sample(position) {
+                assert!((sample.pdf - light.pdf(position, &sample.direction)).abs() < 1e-6 * sample.pdf);
+                sampled += sample.radiance / sample.pdf;
+            }
+            if let Some(emission) = light.emit() {
+                let (pdf_position, pdf_direction) = light.emission_pdf(&emission.origin, &emission.direction).unwrap();
+                assert!((pdf_position - emission.pdf_position).abs() < 1e-6 * pdf_position);
+                assert!((pdf_direction - emission.pdf_direction).abs() < 1e-6 * pdf_direction);
+            }
+            let direction = sample_cone(&axis, cos_max);
+            if let Some((_, radiance)) = light.eval(&Ray::new(*position, direction, Color::WHITE, 0.0)) {
+                hit += radiance / cone_pdf(cos_max);
+            }
+        }
+        let (sampled, hit) = (sampled / count as f64, hit / count as f64);
+        assert!((sampled.luminance() - hit.luminance()).abs() < 0.05 * sampled.luminance().max(0.1), "{:?} vs {:?}", sampled, hit);
+    }
+
+    proptest! {
+
+        #[test]
+        fn samples_cones_within_their_bounds(cos_max in -1.0..0.99, x in -1.0..1.0, y in -1.0..1.0) {
+            let axis = Vec3D::new(x, y, 0.5).unit();
+            let direction = sample_cone(&axis, cos_max);
+            assert!((direction.length() - 1.0).abs() < 1e-9);
+            assert!(direction.dot(axis) >= cos_max - 1e-9);
+        }
+
+        #[test]
+        fn picks_lights_proportionally_to_their_powers(a in 0.1..10.0, b in 0.1..10.0) {
+            let lights = Lights::new(vec![
+                Arc::new(PointLight::new(Vec3D::zero(), Color::grey_shade(a))),
+                Arc::new(PointLight::new(Vec3D::zero(), Color::grey_shade(b))),
+            ]);
+            let position = Vec3D::new(1.0, 2.0, 3.0);
+            assert!((lights.probability(0, &position) - a / (a + b)).abs() < 1e-9);
+            assert!((lights.probability(0, &position) + lights.probability(1, &position) - 1.0).abs() < 1e-9);
+        }
+
+    }
+
+}
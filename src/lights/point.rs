@@ -0,0 +1,98 @@
+use std::f64::consts::PI;
+
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::lights::{Emission, Light, LightBounds, LightSample};
+use crate::sampling::{Space, UniformUnitSphere, PDF};
+
+/// A light emitting equally in all directions from a single point, with the given intensity (i.e.
+/// power per unit solid angle).
+pub struct PointLight {
+    pub position: Vec3D,
+    pub intensity: Color,
+}
+
+impl PointLight {
+
+    pub fn new(position: Vec3D, intensity: Color) -> Self {
+        Self { position, intensity }
+    }
+
+}
+
+impl Light for PointLight {
+
+    fn sample(&self, position: &Vec3D) -> Option<LightSample> {
+        let offset = self.position - *position;
+        let distance_squared = offset.length_squared();
+        let distance = distance_squared.sqrt();
+        Some(LightSample { direction: offset / distance, distance, radiance: self.intensity / distance_squared, pdf: 1.0 })
+    }
+
+    fn pdf(&self, _: &Vec3D, _: &Vec3D) -> f64 {
+        0.0
+    }
+
+    fn eval(&self, _: &Ray) -> Option<(f64, Color)> {
+        None
+    }
+
+    fn power(&self) -> f64 {
+        4.0 * PI * self.intensity.luminance()
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        Some(LightBounds::omnidirectional(self.position, self.position, self.power()))
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn emit(&self) -> Option<Emission> {
+        let (direction, pdf_direction) = UniformUnitSphere.arbitrary_sample_and_pdf();
+        Some(Emission { origin: self.position, direction, normal: None, radiance: self.intensity, pdf_position: 1.0, pdf_direction })
+    }
+
+    fn emission_pdf(&self, _: &Vec3D, direction: &Vec3D) -> Option<(f64, f64)> {
+        Some((1.0, UniformUnitSphere.pdf(direction)))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn falls_off_with_the_squared_distance(x in 0.1..10.0f64, y in -10.0..10.0f64, z in -10.0..10.0f64) {
+            let light = PointLight::new(Vec3D::new(1.0, 2.0, 3.0), Color::new(4.0, 2.0, 1.0));
+            let position = Vec3D::new(1.0 + x, 2.0 + y, 3.0 + z);
+            let sample = light.sample(&position).unwrap();
+            let distance_squared = x * x + y * y + z * z;
+            assert!((sample.distance * sample.distance - distance_squared).abs() < 1e-9 * distance_squared);
+            assert!((position + sample.direction * sample.distance - light.position).length() < 1e-9);
+            assert!((sample.radiance.luminance() - light.intensity.luminance() / distance_squared).abs() < 1e-9);
+            assert_eq!(sample.pdf, 1.0);
+        }
+
+    }
+
+    #[test]
+    fn emits_its_power_in_all_directions() {
+        let light = PointLight::new(Vec3D::zero(), Color::new(4.0, 2.0, 1.0));
+        let count = 10000;
+        let power = (0 .. count).map(|_| {
+            let emission = light.emit().unwrap();
+            assert_eq!(light.emission_pdf(&emission.origin, &emission.direction), Some((emission.pdf_position, emission.pdf_direction)));
+            emission.radiance.luminance() / (emission.pdf_position * emission.pdf_direction)
+        }).sum::<f64>() / count as f64;
+        assert!((power - light.power()).abs() < 1e-9 * light.power(), "{} vs {}", power, light.power());
+    }
+
+}
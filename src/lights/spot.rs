@@ -0,0 +1,205 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::lights::{cone_pdf, sample_cone, Emission, Light, LightBounds, LightSample};
+
+/// A light emitting from a single point, within a cone around its direction. The intensity falls
+/// off smoothly, from its full value within the inner angle of the cone, to zero at its outer
+/// angle. The intensity could be further shaped by an angular profile, like the ones found in IES
+/// photometric files.
+pub struct SpotLight {
+    pub position: Vec3D,
+    pub direction: Vec3D,
+    pub intensity: Color,
+    pub cos_inner: f64,
+    pub cos_outer: f64,
+    pub profile: Option<Arc<Profile>>,
+}
+
+/// An angular intensity profile, given as relative intensities at evenly spaced angles from the
+/// light direction, starting at zero, and ending at the given maximum angle (in radians), beyond
+/// which the intensity is zero. Intensities in between are linearly interpolated.
+pub struct Profile {
+    pub max_angle: f64,
+    pub intensities: Vec<f64>,
+}
+
+impl SpotLight {
+
+    /// Creates a spot light, with the given inner and outer cone angles (in radians).
+    pub fn new(position: Vec3D, direction: Vec3D, intensity: Color, inner_angle: f64, outer_angle: f64) -> Self {
+        Self {
+            position,
+            direction: direction.unit(),
+            intensity,
+            cos_inner: inner_angle.cos(),
+            cos_outer: outer_angle.max(inner_angle).cos(),
+            profile: None,
+        }
+    }
+
+    pub fn with_profile(self, profile: Profile) -> Self {
+        Self { profile: Some(Arc::new(profile)), ..self }
+    }
+
+    /// The fraction of the full intensity emitted in the given unit direction.
+    fn falloff(&self, direction: &Vec3D) -> f64 {
+        let cos_angle = direction.dot(&self.direction);
+        let cone = if cos_angle >= self.cos_inner {
+            1.0
+        } else if cos_angle <= self.cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - self.cos_outer) / (self.cos_inner - self.cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        };
+        match self.profile {
+            Some(ref profile) if cone > 0.0 => cone * profile.intensity_at(cos_angle.clamp(-1.0, 1.0).acos()),
+            _ => cone,
+        }
+    }
+
+}
+
+impl Light for SpotLight {
+
+    fn sample(&self, position: &Vec3D) -> Option<LightSample> {
+        let offset = self.position - *position;
+        let distance_squared = offset.length_squared();
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+        let falloff = self.falloff(&-direction);
+        if falloff <= 0.0 {
+            return None
+        }
+        Some(LightSample { direction, distance, radiance: self.intensity * (falloff / distance_squared), pdf: 1.0 })
+    }
+
+    fn pdf(&self, _: &Vec3D, _: &Vec3D) -> f64 {
+        0.0
+    }
+
+    fn eval(&self, _: &Ray) -> Option<(f64, Color)> {
+        None
+    }
+
+    fn power(&self) -> f64 {
+        2.0 * PI * (1.0 - (self.cos_inner + self.cos_outer) / 2.0) * self.intensity.luminance()
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        Some(LightBounds {
+            min: self.position,
+            max: self.position,
+            power: self.power(),
+            axis: self.direction,
+            cos_theta_o: self.cos_inner,
+            cos_theta_e: (self.cos_outer.acos() - self.cos_inner.acos()).cos(),
+            two_sided: false,
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    /// Emits uniformly within the outer cone.
+    fn emit(&self) -> Option<Emission> {
+        let direction = sample_cone(&self.direction, self.cos_outer);
+        let radiance = self.intensity * self.falloff(&direction);
+        Some(Emission { origin: self.position, direction, normal: None, radiance, pdf_position: 1.0, pdf_direction: cone_pdf(self.cos_outer) })
+    }
+
+    fn emission_pdf(&self, _: &Vec3D, direction: &Vec3D) -> Option<(f64, f64)> {
+        Some((1.0, if direction.dot(&self.direction) >= self.cos_outer { cone_pdf(self.cos_outer) } else { 0.0 }))
+    }
+
+}
+
+impl Profile {
+
+    pub fn intensity_at(&self, angle: f64) -> f64 {
+        let last = self.intensities.len().saturating_sub(1);
+        if last == 0 {
+            return self.intensities.first().copied().unwrap_or(1.0)
+        }
+        if angle > self.max_angle {
+            return 0.0
+        }
+        let position = angle / self.max_angle * last as f64;
+        let i = (position as usize).min(last - 1);
+        let t = position - i as f64;
+        self.intensities[i] * (1.0 - t) + self.intensities[i + 1] * t
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use super::*;
+
+    fn spot_light() -> SpotLight {
+        SpotLight::new(Vec3D::new(0.0, 2.0, 0.0), -Vec3D::Y, Color::new(8.0, 8.0, 8.0), 0.5, 1.0)
+    }
+
+    /// Returns the point of the floor seen from the spot light at the given angle from its
+    /// direction, along with its distance from the light.
+    fn floor_at(angle: f64) -> (Vec3D, f64) {
+        (Vec3D::new(2.0 * angle.tan(), 0.0, 0.0), 2.0 / angle.cos())
+    }
+
+    proptest! {
+
+        #[test]
+        fn falls_off_smoothly_between_its_cone_angles(angle in 0.0..1.4f64, other_angle in 0.0..1.4f64) {
+            let light = spot_light();
+            let intensity_at = |angle: f64| {
+                let (position, distance) = floor_at(angle);
+                light.sample(&position).map_or(0.0, |sample| sample.radiance.luminance() * distance * distance)
+            };
+            let intensity = intensity_at(angle);
+            if angle <= 0.5 {
+                assert!((intensity - 8.0).abs() < 1e-6, "{}", intensity);
+            } else if angle >= 1.0 {
+                assert_eq!(intensity, 0.0);
+            } else {
+                assert!(intensity > 0.0 && intensity < 8.0, "{}", intensity);
+            }
+            if angle < other_angle {
+                assert!(intensity >= intensity_at(other_angle) - 1e-9);
+            }
+        }
+
+        #[test]
+        fn interpolates_its_profile_linearly(angle in 0.0..0.8f64) {
+            let profile = Profile { max_angle: 0.8, intensities: vec![1.0, 0.5, 0.5, 0.0] };
+            let expected = if angle < 0.8 / 3.0 {
+                1.0 - 0.5 * angle / (0.8 / 3.0)
+            } else if angle < 1.6 / 3.0 {
+                0.5
+            } else {
+                0.5 * (0.8 - angle) / (0.8 / 3.0)
+            };
+            assert!((profile.intensity_at(angle) - expected).abs() < 1e-9, "{} vs {}", profile.intensity_at(angle), expected);
+        }
+
+    }
+
+    #[test]
+    fn shapes_its_intensity_by_its_profile() {
+        let profile = Profile { max_angle: 0.8, intensities: vec![1.0, 0.5, 0.5, 0.0] };
+        assert_eq!(profile.intensity_at(0.9), 0.0);
+        assert_eq!(Profile { max_angle: 0.8, intensities: vec![0.25] }.intensity_at(0.9), 0.25);
+
+        let light = spot_light().with_profile(profile);
+        let (position, distance) = floor_at(0.4);
+        let intensity = light.sample(&position).unwrap().radiance.luminance() * distance * distance;
+        assert!((intensity - 4.0).abs() < 1e-6, "{}", intensity);
+    }
+
+}
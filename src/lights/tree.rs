@@ -0,0 +1,320 @@
+use crate::basic::rays::Ray;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::sampling::random;
+
+/// The spatial and directional extents of the light emitted by a light, or by a group of lights:
+/// The light is emitted from within the given box, with the given power, in directions that are
+/// within the emission angle (whose cosine is `cos_theta_e`) of directions within the orientation
+/// cone around the given unit axis (whose half angle has the cosine `cos_theta_o`). Two sided
+/// lights emit along both the directions of their orientation cone, and their opposites.
+#[derive(Copy, Clone, Debug)]
+pub struct LightBounds {
+    pub min: Vec3D,
+    pub max: Vec3D,
+    pub power: f64,
+    pub axis: Vec3D,
+    pub cos_theta_o: f64,
+    pub cos_theta_e: f64,
+    pub two_sided: bool,
+}
+
+impl LightBounds {
+
+    /// The bounds of a light emitting in all directions from within the given box.
+    pub fn omnidirectional(min: Vec3D, max: Vec3D, power: f64) -> Self {
+        Self { min, max, power, axis: Vec3D::Z, cos_theta_o: -1.0, cos_theta_e: 0.0, two_sided: false }
+    }
+
+    pub fn centroid(&self) -> Vec3D {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Bounds enclosing both the given ones.
+    pub fn union(&self, other: &Self) -> Self {
+        if self.power <= 0.0 {
+            return *other
+        } else if other.power <= 0.0 {
+            return *self
+        }
+        let (axis, cos_theta_o) = union_of_cones((&self.axis, self.cos_theta_o), (&other.axis, other.cos_theta_o));
+        Self {
+            min: Vec3D::new(self.min.x().min(other.min.x()), self.min.y().min(other.min.y()), self.min.z().min(other.min.z())),
+            max: Vec3D::new(self.max.x().max(other.max.x()), self.max.y().max(other.max.y()), self.max.z().max(other.max.z())),
+            power: self.power + other.power,
+            axis,
+            cos_theta_o,
+            cos_theta_e: self.cos_theta_e.min(other.cos_theta_e),
+            two_sided: self.two_sided || other.two_sided,
+        }
+    }
+
+    /// A conservative estimate of the light reaching the given position from within the bounds,
+    /// following "Importance Sampling of Many Lights with Adaptive Tree Splitting" (Conty Estevez
+    /// and Kulla, 2018). It is zero only if no light could reach the position.
+    pub fn importance(&self, position: &Vec3D) -> f64 {
+        if self.power <= 0.0 {
+            return 0.0
+        }
+        let center = self.centroid();
+        let radius = (self.max - self.min).length() / 2.0;
+        let offset = *position - center;
+        let distance_squared = offset.length_squared();
+        let cos_w = if distance_squared > 0.0 { offset.dot(self.axis) / distance_squared.sqrt() } else { 1.0 };
+        let cos_w = if self.two_sided { cos_w.abs() } else { cos_w };
+        let sin_w = (1.0 - cos_w * cos_w).max(0.0).sqrt();
+        let cos_b = if distance_squared > radius * radius { (1.0 - radius * radius / distance_squared).sqrt() } else { -1.0 };
+        let sin_b = (1.0 - cos_b * cos_b).max(0.0).sqrt();
+        let sin_o = (1.0 - self.cos_theta_o * self.cos_theta_o).max(0.0).sqrt();
+        // The smallest angle between the direction towards the position and the directions of the
+        // orientation cone, further reduced by the angle the bounds subtend from the position.
+        let (sin_x, cos_x) = angle_difference((sin_w, cos_w), (sin_o, self.cos_theta_o));
+        let (_, cos_p) = angle_difference((sin_x, cos_x), (sin_b, cos_b));
+        if cos_p <= self.cos_theta_e {
+            return 0.0
+        }
+        self.power * cos_p / distance_squared.max(radius)
+    }
+
+    /// The interval of the given ray within the bounds, clipped to the given maximum distance.
+    fn interval(&self, ray: &Ray, max: f64) -> Option<(f64, f64)> {
+        let (mut near, mut far) = (0.0, max);
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction[axis];
+            let (t1, t2) = ((self.min[axis] - ray.origin[axis]) * inverse, (self.max[axis] - ray.origin[axis]) * inverse);
+            let (t1, t2) = if t1 <= t2 { (t1, t2) } else { (t2, t1) };
+            near = if t1 > near { t1 } else { near };
+            far = if t2 < far { t2 } else { far };
+            if near > far {
+                return None
+            }
+        }
+        Some((near, far))
+    }
+
+}
+
+/// A bounding volume hierarchy over lights, whose nodes are bounded by `LightBounds`, allowing to
+/// pick lights in proportion to estimates of their contributions at given positions, rather than
+/// to their powers only. Lights are picked by descending the tree from its root, choosing either
+/// child of every node with a probability proportional to its importance.
+///
+/// The lights are given by index, along with their bounds, if they have any. Lights without
+/// bounds are left out of the tree.
+#[derive(Clone, Debug, Default)]
+pub struct LightTree {
+    nodes: Vec<Node>,
+    trails: Vec<Option<u64>>,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: LightBounds,
+    content: Content,
+}
+
+/// The content of a node, either a single light, given by index, or two child nodes, the first of
+/// which immediately follows the node, and the second of which is at the given index.
+#[derive(Copy, Clone, Debug)]
+enum Content {
+    Light(usize),
+    Children(usize),
+}
+
+impl LightTree {
+
+    pub fn new(bounds: &[Option<LightBounds>]) -> Self {
+        let mut tree = Self { nodes: Vec::new(), trails: vec![None; bounds.len()] };
+        let mut entries: Vec<_> = bounds.iter().enumerate()
+            .filter_map(|(index, bounds)| bounds.filter(|bounds| bounds.power > 0.0).map(|bounds| (index, bounds)))
+            .collect();
+        if !entries.is_empty() {
+            tree.build(&mut entries, 0, 0);
+        }
+        tree
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Builds the subtree of the given lights, reached from the root following the given trail
+    /// (whose bits tell which child was taken at every depth), and returns the index of its root.
+    /// Lights are split in two halves along the axis of the widest spread of their centroids.
+    fn build(&mut self, entries: &mut [(usize, LightBounds)], trail: u64, depth: u32) -> usize {
+        let index = self.nodes.len();
+        if let [(light, bounds)] = *entries {
+            self.nodes.push(Node { bounds, content: Content::Light(light) });
+            self.trails[light] = Some(trail);
+            return index
+        }
+        let bounds = entries[1..].iter().fold(entries[0].1, |union, (_, bounds)| union.union(bounds));
+        let centroids = entries.iter().fold((Vec3D::new(f64::INFINITY, f64::INFINITY, f64::INFINITY), -Vec3D::new(f64::INFINITY, f64::INFINITY, f64::INFINITY)), |(min, max), (_, bounds)| {
+            let centroid = bounds.centroid();
+            (
+                Vec3D::new(min.x().min(centroid.x()), min.y().min(centroid.y()), min.z().min(centroid.z())),
+                Vec3D::new(max.x().max(centroid.x()), max.y().max(centroid.y()), max.z().max(centroid.z())),
+            )
+        });
+        let spread = centroids.1 - centroids.0;
+        let axis = (0..3).max_by(|&a, &b| spread[a].total_cmp(&spread[b])).unwrap_or(0);
+        entries.sort_by(|(_, a), (_, b)| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+        self.nodes.push(Node { bounds, content: Content::Children(0) });
+        let middle = entries.len() / 2;
+        let (first, second) = entries.split_at_mut(middle);
+        self.build(first, trail, depth + 1);
+        let second = self.build(second, trail | (1 << depth), depth + 1);
+        self.nodes[index].content = Content::Children(second);
+        index
+    }
+
+    /// Picks a light at random, in proportion to its importance at the given position, returning its
+    /// index, along with the probability of picking it.
+    pub fn pick(&self, position: &Vec3D) -> Option<(usize, f64)> {
+        let root = self.nodes.first()?;
+        if root.bounds.importance(position) <= 0.0 {
+            return None
+        }
+        let (mut index, mut probability) = (0, 1.0);
+        loop {
+            match self.nodes[index].content {
+                Content::Light(light) => return Some((light, probability)),
+                Content::Children(second) => {
+                    let (first_importance, second_importance) = (self.nodes[index + 1].bounds.importance(position), self.nodes[second].bounds.importance(position));
+                    let total = first_importance + second_importance;
+                    if total <= 0.0 {
+                        return None
+                    }
+                    let first_probability = first_importance / total;
+                    if random::<f64>() < first_probability {
+                        index += 1;
+                        probability *= first_probability;
+                    } else {
+                        index = second;
+                        probability *= 1.0 - first_probability;
+                    }
+                },
+            }
+        }
+    }
+
+    /// The probability of picking the light of the given index at the given position.
+    pub fn probability(&self, light: usize, position: &Vec3D) -> f64 {
+        let Some(Some(trail)) = self.trails.get(light) else {
+            return 0.0
+        };
+        if self.nodes[0].bounds.importance(position) <= 0.0 {
+            return 0.0
+        }
+        let (mut index, mut probability, mut trail) = (0, 1.0, *trail);
+        while let Content::Children(second) = self.nodes[index].content {
+            let (first_importance, second_importance) = (self.nodes[index + 1].bounds.importance(position), self.nodes[second].bounds.importance(position));
+            let total = first_importance + second_importance;
+            if total <= 0.0 {
+                return 0.0
+            }
+            if trail & 1 == 0 {
+                index += 1;
+                probability *= first_importance / total;
+            } else {
+                index = second;
+                probability *= second_importance / total;
+            }
+            trail >>= 1;
+        }
+        probability
+    }
+
+    /// Calls the given function with the index of every light whose bounds the given ray goes
+    /// through before the given distance, skipping the subtrees whose bounds it misses.
+    pub fn visit<F: FnMut(usize)>(&self, ray: &Ray, max: f64, mut visit: F) {
+        if self.nodes.is_empty() {
+            return
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.bounds.interval(ray, max).is_none() {
+                continue
+            }
+            match node.content {
+                Content::Light(light) => visit(light),
+                Content::Children(second) => stack.extend([second, index + 1]),
+            }
+        }
+    }
+
+}
+
+/// The smallest cone enclosing both the given cones, given as their unit axes, and the cosines of
+/// their half angles.
+fn union_of_cones((axis_a, cos_a): (&Vec3D, f64), (axis_b, cos_b): (&Vec3D, f64)) -> (Vec3D, f64) {
+    let (theta_a, theta_b) = (cos_a.clamp(-1.0, 1.0).acos(), cos_b.clamp(-1.0, 1.0).acos());
+    let theta_d = axis_a.dot(axis_b).clamp(-1.0, 1.0).acos();
+    if (theta_d + theta_b).min(std::f64::consts::PI) <= theta_a {
+        return (*axis_a, cos_a)
+    } else if (theta_d + theta_a).min(std::f64::consts::PI) <= theta_b {
+        return (*axis_b, cos_b)
+    }
+    let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+    let rotation_axis = axis_a.cross(axis_b);
+    if theta_o >= std::f64::consts::PI || rotation_axis.length_squared() == 0.0 {
+        return (Vec3D::Z, -1.0)
+    }
+    // Rotates the first axis towards the second one, which is orthogonal to the rotation axis.
+    let theta_r = theta_o - theta_a;
+    let axis = *axis_a * theta_r.cos() + rotation_axis.unit().cross(axis_a) * theta_r.sin();
+    (axis.unit(), theta_o.cos())
+}
+
+/// The sine and cosine of the difference between the given angles (given as their sines and
+/// cosines), clamped to zero when the first angle is the smallest.
+fn angle_difference((sin_a, cos_a): (f64, f64), (sin_b, cos_b): (f64, f64)) -> (f64, f64) {
+    if cos_a > cos_b {
+        (0.0, 1.0)
+    } else {
+        (sin_a * cos_b - cos_a * sin_b, cos_a * cos_b + sin_a * sin_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection::vec, *};
+
+    use crate::basic::vectors::tests::{unit_vec3, vec3};
+
+    use super::*;
+
+    prop_compose! {
+        fn bounds()(center in vec3(), size in 0.0..0.5, power in 0.1..10.0, axis in unit_vec3(), cos_theta_o in -1.0..1.0, cos_theta_e in 0.0..1.0, two_sided in bool::ANY) -> LightBounds {
+            let extent = Vec3D::new(size, size, size);
+            LightBounds { min: center * 4.0 - extent, max: center * 4.0 + extent, power, axis, cos_theta_o, cos_theta_e, two_sided }
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn picks_lights_with_the_probabilities_it_reports(bounds in vec(bounds(), 1..24), position in vec3()) {
+            let tree = LightTree::new(&bounds.iter().map(|&bounds| Some(bounds)).collect::<Vec<_>>());
+            let probabilities: Vec<_> = (0..bounds.len()).map(|light| tree.probability(light, &position)).collect();
+            let total: f64 = probabilities.iter().sum();
+            assert!(total <= 1.0 + 1e-9);
+            for _ in 0..16 {
+                if let Some((light, probability)) = tree.pick(&position) {
+                    assert!((probability - probabilities[light]).abs() < 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn encloses_the_cones_it_unites(a in unit_vec3(), cos_a in -1.0..1.0, b in unit_vec3(), cos_b in -1.0..1.0) {
+            let (axis, cos_theta) = union_of_cones((&a, cos_a), (&b, cos_b));
+            let angle = axis.dot(a).clamp(-1.0, 1.0).acos() + cos_a.acos();
+            assert!(angle <= cos_theta.acos() + 1e-6 || cos_theta <= -1.0);
+            let angle = axis.dot(b).clamp(-1.0, 1.0).acos() + cos_b.acos();
+            assert!(angle <= cos_theta.acos() + 1e-6 || cos_theta <= -1.0);
+        }
+
+    }
+
+}
@@ -0,0 +1,137 @@
+use crate::basic::colors::Color;
+use crate::basic::vectors::Dot;
+use crate::brdfs::Microfacet;
+use crate::geometries::Hit;
+use crate::materials::{Effect, Material, RefractionIndex};
+use crate::sampling::random;
+
+/// A material with a clear dielectric coating over a base material, like car paint, lacquered
+/// wood, or glazed ceramics. Incident rays get reflected off the coating with the probability
+/// given by the Fresnel reflectance of the coating, which increases at grazing angles. Otherwise,
+/// they go through the coating, and interact with the base material.
+///
+/// The coating is smooth (i.e. mirror-like) unless given a roughness, and is perfectly clear
+/// unless given an absorption, which tints whatever goes through it, more so at grazing angles,
+/// where the path through the coating is longer.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::{Coated, Diffusive};
+/// let car_paint = Coated::new(Diffusive(Color::new(0.6, 0.05, 0.05)), 1.5).with_roughness(0.05);
+/// ```
+pub struct Coated<M: Material> {
+    pub base: M,
+    pub index: RefractionIndex,
+    pub roughness: f64,
+    pub absorption: Color,
+}
+
+impl<M: Material> Coated<M> {
+
+    pub fn new(base: M, index: f64) -> Self {
+        Self { base, index: RefractionIndex::of(index), roughness: 0.0, absorption: Color::BLACK }
+    }
+
+    pub fn with_roughness(self, roughness: f64) -> Self {
+        Self { roughness, ..self }
+    }
+
+    /// Sets the absorption coefficients of the coating, per unit of its thickness.
+    pub fn with_absorption(self, absorption: Color) -> Self {
+        Self { absorption, ..self }
+    }
+
+    /// The fraction of light surviving the trip through the coating, into the base and back.
+    fn transmittance(&self, cos_incidence: f64) -> Color {
+        if self.absorption == Color::BLACK {
+            return Color::WHITE
+        }
+        let index = self.index.value();
+        let cos_refraction = (1.0 - (1.0 - cos_incidence * cos_incidence) / (index * index)).max(0.0).sqrt();
+        let path_length = 2.0 / cos_refraction.max(1e-3);
+        let t = |a: f64| (-a * path_length).exp();
+        Color::new(t(self.absorption.red()), t(self.absorption.green()), t(self.absorption.blue()))
+    }
+
+}
+
+impl<M: Material> Material for Coated<M> {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        if !hit.outside {
+            return self.base.effect_of(hit)
+        }
+        let incident = hit.incident_ray.direction.unit();
+        let normal = hit.normal.unit();
+        let cos_incidence = incident.dot(normal).abs();
+        if random::<f64>() < self.index.schlick_reflectance(cos_incidence) {
+            if self.roughness > 0.0 {
+                Effect::Scattering(Color::WHITE, Box::new(Microfacet::new(&normal, &incident, self.roughness)))
+            } else {
+                Effect::Redirection(Color::WHITE, incident - 2.0 * incident.project_on(&normal, true))
+            }
+        } else {
+            let tint = self.transmittance(cos_incidence);
+            match self.base.effect_of(hit) {
+                Effect::Absorption => Effect::Absorption,
+                Effect::Emission(c) => Effect::Emission(c * tint),
+                Effect::Redirection(c, direction) => Effect::Redirection(c * tint, direction),
+                Effect::Scattering(c, brdf) => Effect::Scattering(c * tint, brdf),
+                Effect::Relocation(c, position, direction) => Effect::Relocation(c * tint, position, direction),
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::rays::Ray;
+    use crate::basic::vectors::Vec3D;
+    use crate::geometries::{Geometry, Sphere};
+    use crate::materials::Diffusive;
+
+    use super::*;
+
+    /// Returns the hit of a ray reaching the top of the unit sphere at the given incidence.
+    fn hit_at(cos_incidence: f64) -> Hit {
+        let direction = Vec3D::new((1.0 - cos_incidence * cos_incidence).sqrt(), -cos_incidence, 0.0);
+        Sphere.shoot(&Ray::new(Vec3D::new(0.0, 1.0, 0.0) - direction * 2.0, direction, Color::WHITE, 0.0), 0.0, f64::INFINITY).unwrap()
+    }
+
+    #[test]
+    fn reflects_off_the_coating_following_schlick() {
+        let coated = Coated::new(Diffusive(Color::WHITE), 1.5);
+        for (cos_incidence, reflectance) in [(1.0, 0.04), (0.5, 0.04 + 0.96 / 32.0), (0.01, 0.04 + 0.96 * 0.99f64.powi(5))] {
+            let hit = hit_at(cos_incidence);
+            let count = 20000;
+            let reflected = (0 .. count).filter(|_| matches!(coated.effect_of(&hit), Effect::Redirection(..))).count();
+            let fraction = reflected as f64 / count as f64;
+            assert!((fraction - reflectance).abs() < 0.01, "{} vs {} at {}", fraction, reflectance, cos_incidence);
+        }
+    }
+
+    #[test]
+    fn tints_the_light_going_through_the_coating_more_at_grazing_angles() {
+        let coated = Coated::new(Diffusive(Color::WHITE), 1.5).with_absorption(Color::new(0.1, 0.2, 0.4));
+        let tint_at = |cos_incidence: f64| {
+            let hit = hit_at(cos_incidence);
+            loop {
+                if let Effect::Scattering(c, _) = coated.effect_of(&hit) {
+                    return c
+                }
+            }
+        };
+        let normal_tint = tint_at(1.0);
+        for (c, absorption) in [0.1f64, 0.2, 0.4].into_iter().enumerate() {
+            assert!((normal_tint[c] - (-2.0 * absorption).exp()).abs() < 1e-9, "{:?}", normal_tint);
+        }
+        let grazing_tint = tint_at(0.1);
+        for c in 0..3 {
+            assert!(grazing_tint[c] < normal_tint[c], "{:?} vs {:?}", grazing_tint, normal_tint);
+        }
+        assert!(grazing_tint.blue() < grazing_tint.green() && grazing_tint.green() < grazing_tint.red());
+    }
+
+}
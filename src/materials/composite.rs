@@ -1,8 +1,9 @@
-use rand::{rng, RngExt};
+use rand::RngExt;
 
 use crate::geometries::Hit;
 use crate::materials::Effect::Absorption;
 use crate::materials::{Effect, Material};
+use crate::sampling::rng;
 
 pub struct Composite(Vec<(Box<dyn Material>, f64)>);
 
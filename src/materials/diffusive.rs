@@ -1,10 +1,29 @@
 use crate::basic::colors::Color;
-use crate::brdfs::Lambertian;
+use crate::brdfs::{Lambertian, OrenNayar, RetroReflective};
 use crate::geometries::Hit;
 use crate::materials::{Effect, Material};
 
 pub struct Diffusive(pub Color);
 
+/// A diffusive material whose surface is rough, which makes it look flatter than a Lambertian
+/// surface (e.g. clay, moon dust, or cloth). The meaning of the roughness depends on the model,
+/// and a roughness of 0 makes the material Lambertian.
+pub struct RoughDiffusive {
+    pub color: Color,
+    pub roughness: f64,
+    pub model: DiffuseModel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffuseModel {
+    /// The full Oren-Nayar model (see `OrenNayar`).
+    OrenNayar,
+    /// The qualitative Oren-Nayar model, which is cheaper than the full one, but loses more energy.
+    QualitativeOrenNayar,
+    /// Burley's retro-reflective model (see `RetroReflective`).
+    RetroReflective,
+}
+
 impl Material for Diffusive {
 
     fn effect_of(&self, hit: &Hit) -> Effect {
@@ -13,3 +32,38 @@ impl Material for Diffusive {
     }
 
 }
+
+impl Diffusive {
+
+    /// Makes the surface rough, using the full Oren-Nayar model.
+    pub fn with_roughness(self, roughness: f64) -> RoughDiffusive {
+        let Self(color) = self;
+        RoughDiffusive { color, roughness, model: DiffuseModel::OrenNayar }
+    }
+
+}
+
+impl RoughDiffusive {
+
+    pub fn with_model(self, model: DiffuseModel) -> Self {
+        Self { model, ..self }
+    }
+
+}
+
+impl Material for RoughDiffusive {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        let normal = &hit.normal;
+        let incident = &hit.incident_ray.direction;
+        if self.roughness <= 0.0 {
+            return Effect::Scattering(self.color, Box::new(Lambertian::new(normal)))
+        }
+        Effect::Scattering(self.color, match self.model {
+            DiffuseModel::OrenNayar => Box::new(OrenNayar::full(normal, incident, self.roughness, self.color.luminance())),
+            DiffuseModel::QualitativeOrenNayar => Box::new(OrenNayar::qualitative(normal, incident, self.roughness)),
+            DiffuseModel::RetroReflective => Box::new(RetroReflective::new(normal, incident, self.roughness)),
+        })
+    }
+
+}
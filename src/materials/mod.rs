@@ -1,12 +1,16 @@
 use std::sync::Arc;
 
 pub use absorptive::*;
+pub use coated::*;
 pub use composite::*;
 pub use diffusive::*;
 pub use emissive::*;
 pub use holder::*;
+pub use principled::*;
 pub use reflective::*;
 pub use refractive::*;
+pub use subsurface::*;
+pub use thin_film::*;
 
 use crate::basic::colors::Color;
 use crate::basic::vectors::Vec3D;
@@ -19,20 +23,44 @@ mod diffusive;
 mod reflective;
 mod refractive;
 mod composite;
+mod coated;
 mod holder;
+mod principled;
+mod thin_film;
+mod subsurface;
 
 pub trait Material: Send + Sync {
 
     fn effect_of(&self, hit: &Hit) -> Effect;
 
+    /// The effect of the material on light that already went through the interface between the
+    /// outer medium and the material, i.e. without the reflection off that interface, which layers
+    /// covering the material (e.g. thin films) account for instead.
+    fn transmission_of(&self, hit: &Hit) -> Effect {
+        self.effect_of(hit)
+    }
+
+    /// The refraction index of the material, if it is transparent.
+    fn refraction_index(&self) -> Option<f64> {
+        None
+    }
+
 }
 
-impl<M: Material> Material for Arc<M> {
+impl<M: Material + ?Sized> Material for Arc<M> {
 
     fn effect_of(&self, hit: &Hit) -> Effect {
         self.as_ref().effect_of(hit)
     }
 
+    fn transmission_of(&self, hit: &Hit) -> Effect {
+        self.as_ref().transmission_of(hit)
+    }
+
+    fn refraction_index(&self) -> Option<f64> {
+        self.as_ref().refraction_index()
+    }
+
 }
 
 pub enum Effect {
@@ -40,4 +68,22 @@ pub enum Effect {
     Emission(Color),
     Redirection(Color, Vec3D),
     Scattering(Color, Box<dyn BRDF>),
+    /// The light continues from the given position, in the given direction, like when it gets
+    /// scattered somewhere within a medium, before reaching the hit surface.
+    Relocation(Color, Vec3D, Vec3D),
+}
+
+impl Effect {
+
+    /// Returns the same effect, with its color, if any, mapped by the given function.
+    pub fn map_color<F: FnOnce(Color) -> Color>(self, f: F) -> Self {
+        match self {
+            Effect::Absorption => Effect::Absorption,
+            Effect::Emission(c) => Effect::Emission(f(c)),
+            Effect::Redirection(c, direction) => Effect::Redirection(f(c), direction),
+            Effect::Scattering(c, brdf) => Effect::Scattering(f(c), brdf),
+            Effect::Relocation(c, position, direction) => Effect::Relocation(f(c), position, direction),
+        }
+    }
+
 }
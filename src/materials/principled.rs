@@ -0,0 +1,214 @@
+use crate::basic::colors::Color;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::{Lambertian, Microfacet};
+use crate::geometries::Hit;
+use crate::materials::{Effect, Material};
+use crate::sampling::random;
+
+/// A principled material, which covers most real world materials with a handful of intuitive
+/// parameters, as in the principled shaders of other DCC tools. The parameters are:
+///  * `base_color`: The diffuse color of dielectrics, the specular color of metals, or the tint of
+///    transmissive materials.
+///  * `metallic`: How much the material behaves like a metal (1) rather than a dielectric (0).
+///  * `roughness`: The roughness of specular reflections and transmission, from 0 for mirror-like
+///    surfaces, to 1 for very rough ones.
+///  * `specular`: The strength of specular reflections off dielectrics, where the default of 0.5
+///    corresponds to a reflectance of 4% at normal incidence, which suits most dielectrics.
+///  * `transmission`: How much dielectrics let light through, like glass, rather than scatter it.
+///  * `ior`: The refraction index of transmissive materials.
+///  * `clearcoat`: The strength of a clear coating, like lacquer, on top of everything else.
+///  * `clearcoat_roughness`: The roughness of the clear coating.
+///  * `sheen`: The strength of the soft highlights of cloth-like materials at grazing angles.
+///  * `emission`: The light emitted by the material.
+///
+/// Each interaction with the material picks one of its layers stochastically, with probabilities
+/// reflecting how much energy goes to each layer, which keeps the material energy-consistent.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::Principled;
+/// let gold = Principled { metallic: 1.0, roughness: 0.2, ..Principled::new(Color::new(1.0, 0.77, 0.34)) };
+/// let frosted_glass = Principled { transmission: 1.0, roughness: 0.1, ..Principled::new(Color::WHITE) };
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Principled {
+    pub base_color: Color,
+    pub metallic: f64,
+    pub roughness: f64,
+    pub specular: f64,
+    pub transmission: f64,
+    pub ior: f64,
+    pub clearcoat: f64,
+    pub clearcoat_roughness: f64,
+    pub sheen: f64,
+    pub emission: Color,
+}
+
+impl Principled {
+
+    /// Creates a rough, non-metallic, opaque, non-emissive material of the given color.
+    pub fn new(base_color: Color) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            transmission: 0.0,
+            ior: 1.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            sheen: 0.0,
+            emission: Color::BLACK,
+        }
+    }
+
+    fn surface_effect(&self, hit: &Hit) -> Effect {
+        let incident = hit.incident_ray.direction.unit();
+        let normal = hit.normal.unit();
+        let cos_incidence = incident.dot(normal).abs().min(1.0);
+        if hit.outside {
+            if random::<f64>() < self.clearcoat * schlick(0.04, cos_incidence) {
+                return specular(Color::WHITE, &normal, &incident, self.clearcoat_roughness)
+            }
+            if random::<f64>() < self.metallic {
+                let f = |c: f64| schlick(c, cos_incidence);
+                let color = Color::new(f(self.base_color.red()), f(self.base_color.green()), f(self.base_color.blue()));
+                return specular(color, &normal, &incident, self.roughness)
+            }
+        }
+        if random::<f64>() < self.transmission {
+            return self.transmission_effect(hit, &normal, &incident)
+        }
+        if random::<f64>() < schlick(0.08 * self.specular, cos_incidence) {
+            return specular(Color::WHITE, &normal, &incident, self.roughness)
+        }
+        let sheen = self.sheen * (1.0 - cos_incidence).powi(5);
+        Effect::Scattering(self.base_color * (1.0 - sheen) + Color::grey_shade(sheen), Box::new(Lambertian::new(&normal)))
+    }
+
+    fn transmission_effect(&self, hit: &Hit, normal: &Vec3D, incident: &Vec3D) -> Effect {
+        let eta = if hit.outside { 1.0 / self.ior } else { self.ior };
+        let microfacet_normal = if self.roughness > 0.0 {
+            Microfacet::new(normal, incident, self.roughness).microfacet_normal()
+        } else {
+            *normal
+        };
+        let cos_incidence = incident.dot(&microfacet_normal).abs().min(1.0);
+        let cos_refraction_squared = 1.0 - eta * eta * (1.0 - cos_incidence * cos_incidence);
+        if cos_refraction_squared < 0.0 {
+            return specular(Color::WHITE, normal, incident, self.roughness)
+        }
+        let cos_refraction = cos_refraction_squared.sqrt();
+        let f0 = ((self.ior - 1.0) / (self.ior + 1.0)).powi(2);
+        if random::<f64>() < schlick(f0, if hit.outside { cos_incidence } else { cos_refraction }) {
+            return specular(Color::WHITE, normal, incident, self.roughness)
+        }
+        let refraction = eta * incident + (eta * cos_incidence - cos_refraction) * microfacet_normal;
+        if refraction.dot(*normal) < 0.0 {
+            Effect::Redirection(self.base_color, refraction)
+        } else {
+            Effect::Redirection(self.base_color, eta * incident + (eta * cos_incidence - cos_refraction) * normal)
+        }
+    }
+
+}
+
+impl Material for Principled {
+
+    /// Emission is combined with the other layers stochastically as well, with the effects of the
+    /// picked layers scaled up to compensate for the probability of picking them.
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        let emission = self.emission.luminance();
+        if emission <= 0.0 {
+            return self.surface_effect(hit)
+        }
+        let probability = emission / (1.0 + emission);
+        if random::<f64>() < probability {
+            Effect::Emission(self.emission * (1.0 / probability))
+        } else {
+            match self.surface_effect(hit) {
+                Effect::Absorption => Effect::Absorption,
+                Effect::Emission(c) => Effect::Emission(c * (1.0 / (1.0 - probability))),
+                Effect::Redirection(c, direction) => Effect::Redirection(c * (1.0 / (1.0 - probability)), direction),
+                Effect::Scattering(c, brdf) => Effect::Scattering(c * (1.0 / (1.0 - probability)), brdf),
+                Effect::Relocation(c, position, direction) => Effect::Relocation(c * (1.0 / (1.0 - probability)), position, direction),
+            }
+        }
+    }
+
+}
+
+/// Schlick's approximation of Fresnel reflectance, given the reflectance at normal incidence.
+fn schlick(f0: f64, cos_angle: f64) -> f64 {
+    f0 + (1.0 - f0) * (1.0 - cos_angle).powi(5)
+}
+
+fn specular(color: Color, normal: &Vec3D, incident: &Vec3D, roughness: f64) -> Effect {
+    if roughness > 0.0 {
+        Effect::Scattering(color, Box::new(Microfacet::new(normal, incident, roughness)))
+    } else {
+        Effect::Redirection(color, incident - &(2.0 * incident.project_on(normal, true)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::tests::{color, range};
+    use crate::basic::rays::Ray;
+    use crate::geometries::{Geometry, Sphere};
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn reflects_at_most_the_light_it_receives(
+            base_color in color(), metallic in range(), roughness in range(), specular in range(), transmission in range(),
+            clearcoat in range(), sheen in range(), cos_incidence in 0.1..1.0f64,
+        ) {
+            let material = Principled { metallic, roughness, specular, transmission, clearcoat, sheen, ..Principled::new(base_color) };
+            let direction = Vec3D::new((1.0 - cos_incidence * cos_incidence).sqrt(), -cos_incidence, 0.0);
+            let hit = Sphere.shoot(&Ray::new(Vec3D::new(0.0, 1.0, 0.0) - direction * 2.0, direction, Color::WHITE, 0.0), 0.0, f64::INFINITY).unwrap();
+            let count = 5000;
+            let mut albedo = Color::BLACK;
+            for _ in 0..count {
+                albedo += match material.effect_of(&hit) {
+                    Effect::Scattering(c, brdf) => {
+                        let (direction, pdf) = brdf.arbitrary_sample_and_pdf();
+                        if pdf > 0.0 { c * (brdf.eval(&direction) / pdf) } else { Color::BLACK }
+                    },
+                    Effect::Redirection(c, _) => c,
+                    _ => Color::BLACK,
+                };
+            }
+            let albedo = albedo / count as f64;
+            for c in 0..3 {
+                assert!(albedo[c] <= 1.05, "{:?}", albedo);
+            }
+        }
+
+    }
+
+    #[test]
+    fn emits_the_emission_on_average() {
+        let direction = Vec3D::new(1.0, 2.0, -3.0).unit();
+        let hit = Sphere.shoot(&Ray::new(direction * -3.0, direction, Color::WHITE, 0.0), 0.0, f64::INFINITY).unwrap();
+        let material = Principled { emission: Color::new(4.0, 2.0, 1.0), ..Principled::new(Color::WHITE) };
+
+        let samples = 100000;
+        let mut emission = Color::BLACK;
+        for _ in 0..samples {
+            if let Effect::Emission(c) = material.effect_of(&hit) {
+                emission += c * (1.0 / samples as f64);
+            }
+        }
+
+        for c in 0..3 {
+            assert!((emission[c] - material.emission[c]).abs() < 0.05 * material.emission[c]);
+        }
+    }
+
+}
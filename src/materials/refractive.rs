@@ -1,32 +1,69 @@
-use rand::{rng, RngExt};
+use rand::RngExt;
 
 use crate::basic::colors::Color;
+use crate::basic::spectra::terminate_secondary_wavelengths;
 use crate::basic::vectors::{Dot, Vec3D};
 use crate::geometries::Hit;
 use crate::materials::{Effect, Material};
+use crate::sampling::rng;
 
 pub struct Refractive(pub Color, pub RefractionIndex);
-pub struct RefractionIndex(f64, f64, f64);
+
+/// A refraction index, which could vary with the wavelength of light, causing dispersion. When not
+/// traced at a particular wavelength, the index of a dispersive medium is its index at the sodium D
+/// line (589.3 nm).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RefractionIndex(f64, f64, f64, Dispersion);
+
+/// The dispersion formula of a refraction index, in terms of the wavelength in micrometers:
+///  * Cauchy's equation `n = a + b / λ^2`.
+///  * Sellmeier's equation `n^2 = 1 + Σ b_i λ^2 / (λ^2 - c_i)`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dispersion {
+    None,
+    Cauchy(f64, f64),
+    Sellmeier([f64; 3], [f64; 3]),
+}
 
 impl Material for Refractive {
 
     fn effect_of(&self, hit: &Hit) -> Effect {
-        let Self(ref color, ref index) = self;
-        let direction = Self::redirection(&hit.incident_ray.direction, &hit.normal.unit(), index, hit.outside);
-        Effect::Redirection(*color, direction)
+        self.effect(hit, true)
+    }
+
+    fn transmission_of(&self, hit: &Hit) -> Effect {
+        self.effect(hit, false)
+    }
+
+    fn refraction_index(&self) -> Option<f64> {
+        let Self(_, ref index) = self;
+        Some(index.value())
     }
 
 }
 
 impl Refractive {
 
-    fn redirection(incident: &Vec3D, normal: &Vec3D, index: &RefractionIndex, outside: bool) -> Vec3D {
-        let &RefractionIndex(i, _, _) = index;
+    fn effect(&self, hit: &Hit, reflecting: bool) -> Effect {
+        let Self(ref color, ref index) = self;
+        let index = match hit.incident_ray.wavelength {
+            Some(wavelength) if index.is_dispersive() => {
+                terminate_secondary_wavelengths();
+                index.at(wavelength)
+            },
+            _ => *index,
+        };
+        let direction = Self::redirection(&hit.incident_ray.direction, &hit.normal.unit(), &index, hit.outside, reflecting);
+        Effect::Redirection(*color, direction)
+    }
+
+    fn redirection(incident: &Vec3D, normal: &Vec3D, index: &RefractionIndex, outside: bool, reflecting: bool) -> Vec3D {
+        let &RefractionIndex(i, _, _, _) = index;
         let reciprocated_index = if outside { 1.0 / i } else { i };
         let incident_perpendicular_component = incident.project_on(normal, true);
         let incident_tangent_component = incident - &incident_perpendicular_component;
         let refraction_tangent_component = incident_tangent_component * reciprocated_index;
-        let refraction_perpendicular_component_length_squared = Self::refraction_perpendicular_component_length_squared(&refraction_tangent_component, &incident_perpendicular_component, incident, index);
+        let refraction_perpendicular_component_length_squared = Self::refraction_perpendicular_component_length_squared(&refraction_tangent_component, &incident_perpendicular_component, incident, index, reflecting);
         if refraction_perpendicular_component_length_squared >= 0.0 {
             let refraction_perpendicular_component = normal * refraction_perpendicular_component_length_squared.sqrt();
             refraction_tangent_component - refraction_perpendicular_component
@@ -35,10 +72,10 @@ impl Refractive {
         }
     }
 
-    fn refraction_perpendicular_component_length_squared(refraction_tangent_component: &Vec3D, incident_perpendicular_component: &Vec3D, incident_or_refraction: &Vec3D, index: &RefractionIndex) -> f64 {
+    fn refraction_perpendicular_component_length_squared(refraction_tangent_component: &Vec3D, incident_perpendicular_component: &Vec3D, incident_or_refraction: &Vec3D, index: &RefractionIndex, reflecting: bool) -> f64 {
         let incident_or_refraction_length_squared = incident_or_refraction.length_squared();
         let refraction_perpendicular_component_length_squared = incident_or_refraction_length_squared - refraction_tangent_component.length_squared();
-        if refraction_perpendicular_component_length_squared >= 0.0 {
+        if refraction_perpendicular_component_length_squared >= 0.0 && reflecting {
             let cos_angle = (incident_perpendicular_component.length_squared() / incident_or_refraction_length_squared).sqrt();
             if rng().random::<f64>() >= index.schlick_reflectance(cos_angle) {
                 refraction_perpendicular_component_length_squared
@@ -54,15 +91,79 @@ impl Refractive {
 
 impl RefractionIndex {
 
+    const SODIUM_D_LINE: f64 = 589.3;
+
     pub fn of(index: f64) -> Self {
+        Self::dispersive(index, Dispersion::None)
+    }
+
+    /// A dispersive index following Cauchy's equation, with coefficients for wavelengths in
+    /// micrometers.
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self::with_dispersion(Dispersion::Cauchy(a, b))
+    }
+
+    /// A dispersive index following Sellmeier's equation, with coefficients for wavelengths in
+    /// micrometers.
+    ///
+    /// Example:
+    /// ```
+    /// # use photon::materials::RefractionIndex;
+    /// let bk7 = RefractionIndex::sellmeier([1.03961212, 0.231792344, 1.01046945], [0.00600069867, 0.0200179144, 103.560653]);
+    /// assert!((bk7.value() - 1.5168).abs() < 0.001);
+    /// assert!(bk7.at(400.0).value() > bk7.at(700.0).value());
+    /// ```
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self::with_dispersion(Dispersion::Sellmeier(b, c))
+    }
+
+    fn with_dispersion(dispersion: Dispersion) -> Self {
+        Self::dispersive(dispersion.index_at(Self::SODIUM_D_LINE).unwrap_or(1.0), dispersion)
+    }
+
+    fn dispersive(index: f64, dispersion: Dispersion) -> Self {
         let i = (index - 1.0) / (index + 1.0);
         let i2 = i * i;
-        Self(index, i2, 1.0 - i2)
+        Self(index, i2, 1.0 - i2, dispersion)
+    }
+
+    pub fn value(&self) -> f64 {
+        let &Self(index, _, _, _) = self;
+        index
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        let &Self(_, _, _, dispersion) = self;
+        dispersion != Dispersion::None
     }
 
-    fn schlick_reflectance(&self, cos_angle: f64) -> f64 {
-        let &Self(_, c1, c2) = self;
+    /// Returns the (non-dispersive) index at the given wavelength, in nanometers.
+    pub fn at(&self, wavelength: f64) -> Self {
+        let &Self(_, _, _, dispersion) = self;
+        match dispersion.index_at(wavelength) {
+            Some(index) => Self::of(index),
+            None => *self,
+        }
+    }
+
+    pub(crate) fn schlick_reflectance(&self, cos_angle: f64) -> f64 {
+        let &Self(_, c1, c2, _) = self;
         c1 + c2 * (1.0 - cos_angle).powf(5.0)
     }
 
 }
+
+impl Dispersion {
+
+    /// Returns the index at the given wavelength, in nanometers, or nothing if there is no
+    /// dispersion.
+    pub fn index_at(&self, wavelength: f64) -> Option<f64> {
+        let l2 = (wavelength / 1000.0).powi(2);
+        match self {
+            Dispersion::None => None,
+            Dispersion::Cauchy(a, b) => Some(a + b / l2),
+            Dispersion::Sellmeier(b, c) => Some((1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt()),
+        }
+    }
+
+}
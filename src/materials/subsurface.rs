@@ -0,0 +1,114 @@
+use rand::RngExt;
+
+use crate::basic::colors::Color;
+use crate::basic::vectors::Dot;
+use crate::geometries::Hit;
+use crate::materials::{Effect, Material, RefractionIndex, Refractive};
+use crate::sampling::{rng, Space, UniformUnitSphere};
+
+/// A translucent material, like skin, wax, marble, or milk, where light travels below the surface
+/// before leaving it, possibly elsewhere. The inside of the thing having this material (as the
+/// texture of both of its sides) is a scattering medium, through which light takes a random walk.
+///
+/// Rays refract into the medium through the outer surface. Within the medium, they travel, before
+/// getting scattered in a random direction, for a random distance that is, on average, the mean free
+/// path of the medium. At each scattering event, the medium absorbs part of the light, depending on
+/// its albedo. Rays reaching the inner surface before getting scattered refract out of the medium.
+/// The mean free path and albedo are given per color channel, which could make, say, red light
+/// travel further than blue light.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::Subsurface;
+/// let marble = Subsurface::new(Color::new(0.95, 0.93, 0.9), Color::new(0.08, 0.07, 0.06), 1.5);
+/// ```
+pub struct Subsurface {
+    pub albedo: Color,
+    pub mean_free_path: Color,
+    pub index: RefractionIndex,
+}
+
+impl Subsurface {
+
+    pub fn new(albedo: Color, mean_free_path: Color, index: f64) -> Self {
+        Self { albedo, mean_free_path, index: RefractionIndex::of(index) }
+    }
+
+    /// The effect of the medium on a ray that travelled within it, from where it last entered, or
+    /// got scattered, to the given inner surface hit.
+    fn walk(&self, hit: &Hit) -> Effect {
+        let extinction = Color::new(
+            1.0 / self.mean_free_path.red(),
+            1.0 / self.mean_free_path.green(),
+            1.0 / self.mean_free_path.blue(),
+        );
+        let transmittance = |distance: f64| Color::new(
+            (-extinction.red() * distance).exp(),
+            (-extinction.green() * distance).exp(),
+            (-extinction.blue() * distance).exp(),
+        );
+        let average = |c: Color| (c.red() + c.green() + c.blue()) / 3.0;
+
+        let mut rng = rng();
+        let channel = rng.random_range(0..3usize);
+        let distance = -(1.0 - rng.random::<f64>()).ln() / extinction[channel];
+        let direction = hit.incident_ray.direction.unit();
+        let length = hit.distance * hit.incident_ray.direction.length();
+        if distance < length {
+            let attenuation = transmittance(distance) * extinction;
+            let position = hit.incident_ray.origin - direction * (length - distance);
+            Effect::Relocation(self.albedo * attenuation / average(attenuation), position, UniformUnitSphere.arbitrary_sample())
+        } else {
+            let attenuation = transmittance(length);
+            Refractive(attenuation / average(attenuation), self.index).effect_of(hit)
+        }
+    }
+
+}
+
+impl Material for Subsurface {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        if hit.outside {
+            Refractive(Color::WHITE, self.index).effect_of(hit)
+        } else {
+            self.walk(hit)
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::rays::Ray;
+    use crate::basic::vectors::Vec3D;
+    use crate::geometries::{Geometry, Sphere};
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn transmits_light_reaching_the_surface_exponentially_less_with_distance(mean_free_path in 0.5..4.0, radius in 0.5..2.0) {
+            let medium = Subsurface::new(Color::WHITE, Color::new(mean_free_path, 2.0 * mean_free_path, 4.0 * mean_free_path), 1.0);
+            let hit = Sphere.shoot(&Ray::new(Vec3D::zero(), Vec3D::X * radius, Color::WHITE, 0.0), 0.0, f64::INFINITY).unwrap();
+            let count = 20000;
+            let mut sum = Color::BLACK;
+            for _ in 0..count {
+                if let Effect::Redirection(c, _) = medium.effect_of(&hit) {
+                    sum += c;
+                }
+            }
+            let average = sum / count as f64;
+            for c in 0..3 {
+                let expected = (-1.0 / medium.mean_free_path[c]).exp();
+                assert!((average[c] - expected).abs() < 0.03, "{} vs {}", average[c], expected);
+            }
+        }
+
+    }
+
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use crate::basic::colors::Color;
+use crate::basic::spectra::{color_matching, terminate_secondary_wavelengths, xyz_to_color, LONGEST_WAVELENGTH, SHORTEST_WAVELENGTH};
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::geometries::Hit;
+use crate::materials::{Effect, Material};
+use crate::noise::Noise;
+use crate::sampling::random;
+
+/// A material with a thin transparent film over a base material, like a soap bubble, or an oil
+/// slick. Light reflected off the top of the film interferes with light reflected off its bottom,
+/// which makes the reflectance depend on the wavelength, giving the film its iridescent colors.
+/// The reflectance is the Airy summation of all the reflections within the film, averaged over
+/// both polarizations, and depends on the film thickness (in nanometers), the film index, the
+/// index of the substrate underneath it, and the incidence angle. The substrate index is the one
+/// of the base material, if transparent, or 1 otherwise, unless set.
+///
+/// Incident rays get reflected off the film with a probability following its reflectance, and
+/// otherwise go through it, into the base material, whose own reflection off the film to substrate
+/// interface is skipped, being part of the film reflectance already. The film thickness could vary
+/// over the surface, following a noise evaluated in object space.
+///
+/// Example:
+/// ```
+/// # use std::sync::Arc;
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::{Refractive, RefractionIndex, ThinFilm};
+/// # use photon::noise::Perlin;
+/// let bubble = ThinFilm::new(Refractive(Color::WHITE, RefractionIndex::of(1.0)), 1.33, 300.0)
+///     .with_thickness_variation(Arc::new(Perlin::new(7)), 900.0, 2.0);
+/// ```
+pub struct ThinFilm<M: Material> {
+    pub base: M,
+    pub index: f64,
+    pub substrate_index: Option<f64>,
+    pub thickness: (f64, f64),
+    pub noise: Option<Arc<dyn Noise>>,
+    pub detail: f64,
+}
+
+impl<M: Material> ThinFilm<M> {
+
+    const WAVELENGTHS: usize = 32;
+
+    pub fn new(base: M, index: f64, thickness: f64) -> Self {
+        Self { base, index, substrate_index: None, thickness: (thickness, thickness), noise: None, detail: 1.0 }
+    }
+
+    pub fn with_substrate_index(self, substrate_index: f64) -> Self {
+        Self { substrate_index: Some(substrate_index), ..self }
+    }
+
+    /// Makes the thickness vary, following the given noise, between the minimum thickness given to
+    /// `new` and the given maximum thickness.
+    pub fn with_thickness_variation(self, noise: Arc<dyn Noise>, max_thickness: f64, detail: f64) -> Self {
+        Self { thickness: (self.thickness.0, max_thickness), noise: Some(noise), detail, ..self }
+    }
+
+    fn thickness_at(&self, hit: &Hit) -> f64 {
+        let (min, max) = self.thickness;
+        match self.noise {
+            Some(ref noise) => {
+                let point = hit.local_hit().incident_ray.origin * self.detail;
+                min + noise.value_at_time(&point, hit.incident_ray.time).clamp(0.0, 1.0) * (max - min)
+            },
+            None => min,
+        }
+    }
+
+    /// The reflectance of the film, as seen by the sampled wavelength of the incident ray, if any,
+    /// or as seen in RGB otherwise.
+    fn reflectance(&self, hit: &Hit, cos_incidence: f64) -> Color {
+        let thickness = self.thickness_at(hit);
+        match hit.incident_ray.wavelength {
+            Some(wavelength) => {
+                terminate_secondary_wavelengths();
+                Color::grey_shade(self.airy_reflectance(cos_incidence, thickness, wavelength))
+            },
+            None => {
+                let step = (LONGEST_WAVELENGTH - SHORTEST_WAVELENGTH) / Self::WAVELENGTHS as f64;
+                let xyz = (0 .. Self::WAVELENGTHS)
+                    .map(|i| SHORTEST_WAVELENGTH + (i as f64 + 0.5) * step)
+                    .map(|wavelength| color_matching(wavelength) * self.airy_reflectance(cos_incidence, thickness, wavelength))
+                    .fold(Vec3D::zero(), |sum, xyz| sum + xyz);
+                let color = xyz_to_color(&(xyz * step));
+                Color::new(color.red().clamp(0.0, 1.0), color.green().clamp(0.0, 1.0), color.blue().clamp(0.0, 1.0))
+            },
+        }
+    }
+
+    fn airy_reflectance(&self, cos_incidence: f64, thickness: f64, wavelength: f64) -> f64 {
+        let substrate_index = self.substrate_index.or_else(|| self.base.refraction_index()).unwrap_or(1.0);
+        let (n1, n2, n3) = (1.0, self.index, substrate_index);
+        let sin_squared = 1.0 - cos_incidence * cos_incidence;
+        let cos_in = |n: f64| {
+            let cos_squared = 1.0 - sin_squared * (n1 * n1) / (n * n);
+            if cos_squared >= 0.0 { Some(cos_squared.sqrt()) } else { None }
+        };
+        let (Some(cos2), Some(cos3)) = (cos_in(n2), cos_in(n3)) else {
+            return 1.0
+        };
+        let phase = 4.0 * std::f64::consts::PI * n2 * thickness * cos2 / wavelength;
+        let airy = |r12: f64, r23: f64| {
+            let cross = 2.0 * r12 * r23 * phase.cos();
+            (r12 * r12 + r23 * r23 + cross) / (1.0 + r12 * r12 * r23 * r23 + cross)
+        };
+        let s = airy(fresnel(n1 * cos_incidence, n2 * cos2), fresnel(n2 * cos2, n3 * cos3));
+        let p = airy(fresnel(n2 * cos_incidence, n1 * cos2), fresnel(n3 * cos2, n2 * cos3));
+        (s + p) / 2.0
+    }
+
+}
+
+impl<M: Material> Material for ThinFilm<M> {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        if !hit.outside {
+            return self.base.effect_of(hit)
+        }
+        let incident = hit.incident_ray.direction.unit();
+        let normal = hit.normal.unit();
+        let reflectance = self.reflectance(hit, incident.dot(normal).abs());
+        let probability = (reflectance.red() + reflectance.green() + reflectance.blue()) / 3.0;
+        if random::<f64>() < probability {
+            Effect::Redirection(reflectance / probability, incident - 2.0 * incident.project_on(&normal, true))
+        } else {
+            let tint = (Color::WHITE - reflectance) / (1.0 - probability);
+            match self.base.transmission_of(hit) {
+                Effect::Absorption => Effect::Absorption,
+                Effect::Emission(c) => Effect::Emission(c * tint),
+                Effect::Redirection(c, direction) => Effect::Redirection(c * tint, direction),
+                Effect::Scattering(c, brdf) => Effect::Scattering(c * tint, brdf),
+                Effect::Relocation(c, position, direction) => Effect::Relocation(c * tint, position, direction),
+            }
+        }
+    }
+
+}
+
+/// The Fresnel amplitude reflection coefficient between two media, given the products of their
+/// indices and the cosines of the angles of the light in either of them (swapped for the parallel
+/// polarization).
+fn fresnel(a: f64, b: f64) -> f64 {
+    (a - b) / (a + b)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::rays::Ray;
+    use crate::geometries::{Geometry, Sphere};
+    use crate::materials::{Absorptive, Refractive, RefractionIndex};
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn reflects_like_a_single_interface_when_vanishingly_thin(cos_incidence in 0.05..1.0, wavelength in SHORTEST_WAVELENGTH..LONGEST_WAVELENGTH) {
+            let film = ThinFilm::new(Absorptive, 1.33, 0.0).with_substrate_index(1.5);
+            let bare = ThinFilm::new(Absorptive, 1.5, 0.0).with_substrate_index(1.5);
+            let reflectance = film.airy_reflectance(cos_incidence, 0.0, wavelength);
+            assert!((reflectance - bare.airy_reflectance(cos_incidence, 0.0, wavelength)).abs() < 1e-9);
+            assert!((0.0..=1.0).contains(&reflectance));
+        }
+
+    }
+
+    /// A quarter-wave film, whose index is the square root of the substrate index, cancels the
+    /// reflection of the wavelength it is tuned for, at normal incidence.
+    fn anti_reflective_glass() -> ThinFilm<Refractive> {
+        let index = 1.5f64.sqrt();
+        ThinFilm::new(Refractive(Color::WHITE, RefractionIndex::of(1.5)), index, 550.0 / (4.0 * index))
+    }
+
+    #[test]
+    fn cancels_reflections_of_quarter_wave_films() {
+        let film = anti_reflective_glass();
+        let thickness = film.thickness.0;
+
+        assert!(film.airy_reflectance(1.0, thickness, 550.0) < 1e-9);
+        // a half-wave film does not change the reflectance of the bare substrate
+        assert!((film.airy_reflectance(1.0, 2.0 * thickness, 550.0) - 0.04).abs() < 1e-9);
+        assert!(film.airy_reflectance(1.0, thickness, 400.0) > 0.001);
+    }
+
+    #[test]
+    fn transmits_through_the_substrate_interface_without_reflecting_off_it_again() {
+        let film = anti_reflective_glass();
+        let ray = Ray { wavelength: Some(550.0), ..Ray::new(Vec3D::new(0.0, 0.0, 2.0), -Vec3D::Z, Color::WHITE, 0.0) };
+        let hit = Sphere.shoot(&ray, 0.0, f64::INFINITY).unwrap();
+        for _ in 0 .. 1000 {
+            match film.effect_of(&hit) {
+                Effect::Redirection(_, direction) => assert!(direction.z() < 0.0, "{:?}", direction),
+                _ => panic!("expected a redirection"),
+            }
+        }
+    }
+
+}
@@ -0,0 +1,278 @@
+use crate::basic::vectors::Vec3D;
+use crate::noise::{sample, Noise};
+
+/// Decorrelates successive octaves, which would otherwise all have the same value at the origin.
+const OCTAVE_SHIFT: Vec3D = Vec3D::new(0.37, 0.71, 0.13);
+
+/// Shifts that decorrelate the three components of warping displacements.
+const WARP_SHIFTS: [Vec3D; 3] = [Vec3D::new(0.0, 0.0, 0.0), Vec3D::new(5.2, 1.3, 2.8), Vec3D::new(1.7, 9.2, 8.3)];
+
+/// Domain warping, which displaces the points at which a noise is evaluated by a vector field,
+/// whose components are evaluated from another noise. It gives swirly, flowing patterns.
+pub struct Warped<N: Noise, W: Noise> {
+    pub noise: N,
+    pub warp: W,
+    pub strength: f64,
+}
+
+/// Turbulence, which is fractal noise that sums the absolute deviations of its octaves from the
+/// average, giving billowy patterns with sharp creases. Its values lie in the range `[0, 1]`,
+/// assuming the base noise has values in that range, averaging to `0.5`.
+pub struct Turbulence<N: Noise> {
+    pub base: N,
+    pub octaves: u8,
+    pub lacunarity: f64,
+    pub gain: f64,
+}
+
+/// Musgrave's ridged multifractal, which turns the creases of turbulence into sharp ridges, and
+/// lets the ridges of each octave control how much detail the next octaves add. It suits mountain
+/// ranges and cracked surfaces. Its values lie in the range `[0, 1]`, assuming the base noise has
+/// values in that range, averaging to `0.5`.
+pub struct Ridged<N: Noise> {
+    pub base: N,
+    pub octaves: u8,
+    pub lacunarity: f64,
+    pub gain: f64,
+}
+
+/// Maps the values of a noise linearly from one range to another, optionally clamping them to the
+/// target range.
+pub struct Remapped<N: Noise> {
+    pub noise: N,
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub clamped: bool,
+}
+
+/// Combines the values of two noises arithmetically.
+pub struct Combined<A: Noise, B: Noise> {
+    pub first: A,
+    pub second: B,
+    pub operation: Operation,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Sum,
+    Difference,
+    Product,
+    Minimum,
+    Maximum,
+    Average,
+}
+
+impl<N: Noise, W: Noise> Warped<N, W> {
+
+    pub fn new(noise: N, warp: W, strength: f64) -> Self {
+        Self { noise, warp, strength }
+    }
+
+    fn value(&self, point: &Vec3D, time: Option<f64>) -> f64 {
+        let displacement = WARP_SHIFTS.map(|shift| 2.0 * sample(&self.warp, &(point + &shift), time) - 1.0);
+        let [x, y, z] = displacement;
+        let warped = point + &(Vec3D::new(x, y, z) * self.strength);
+        sample(&self.noise, &warped, time)
+    }
+
+}
+
+impl<N: Noise, W: Noise> Noise for Warped<N, W> {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        self.value(point, None)
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.value(point, Some(time))
+    }
+
+}
+
+impl<N: Noise> Turbulence<N> {
+
+    /// Creates turbulence of the given number of octaves, each of twice the frequency, and half the
+    /// amplitude, of the previous one.
+    pub fn new(base: N, octaves: u8) -> Self {
+        Self { base, octaves, lacunarity: 2.0, gain: 0.5 }
+    }
+
+    pub fn with_lacunarity(self, lacunarity: f64) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    pub fn with_gain(self, gain: f64) -> Self {
+        Self { gain, ..self }
+    }
+
+    fn value(&self, point: &Vec3D, time: Option<f64>) -> f64 {
+        let mut point = *point;
+        let (mut sum, mut total, mut amplitude) = (0.0, 0.0, 1.0);
+        for _ in 0..self.octaves.max(1) {
+            sum += amplitude * (2.0 * sample(&self.base, &point, time) - 1.0).abs();
+            total += amplitude;
+            amplitude *= self.gain;
+            point = point * self.lacunarity + OCTAVE_SHIFT;
+        }
+        sum / total
+    }
+
+}
+
+impl<N: Noise> Noise for Turbulence<N> {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        self.value(point, None)
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.value(point, Some(time))
+    }
+
+}
+
+impl<N: Noise> Ridged<N> {
+
+    /// Creates a ridged multifractal of the given number of octaves, each of twice the frequency,
+    /// and half the amplitude, of the previous one.
+    pub fn new(base: N, octaves: u8) -> Self {
+        Self { base, octaves, lacunarity: 2.0, gain: 0.5 }
+    }
+
+    pub fn with_lacunarity(self, lacunarity: f64) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    pub fn with_gain(self, gain: f64) -> Self {
+        Self { gain, ..self }
+    }
+
+    fn value(&self, point: &Vec3D, time: Option<f64>) -> f64 {
+        let mut point = *point;
+        let (mut sum, mut total, mut amplitude, mut weight) = (0.0, 0.0, 1.0, 1.0);
+        for _ in 0..self.octaves.max(1) {
+            let ridge = 1.0 - (2.0 * sample(&self.base, &point, time) - 1.0).abs().min(1.0);
+            let signal = ridge * ridge * weight;
+            weight = (2.0 * signal).clamp(0.0, 1.0);
+            sum += amplitude * signal;
+            total += amplitude;
+            amplitude *= self.gain;
+            point = point * self.lacunarity + OCTAVE_SHIFT;
+        }
+        sum / total
+    }
+
+}
+
+impl<N: Noise> Noise for Ridged<N> {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        self.value(point, None)
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.value(point, Some(time))
+    }
+
+}
+
+impl<N: Noise> Remapped<N> {
+
+    pub fn new(noise: N, from: (f64, f64), to: (f64, f64)) -> Self {
+        Self { noise, from, to, clamped: false }
+    }
+
+    pub fn clamped(self) -> Self {
+        Self { clamped: true, ..self }
+    }
+
+    fn remap(&self, value: f64) -> f64 {
+        let (from_min, from_max) = self.from;
+        let (to_min, to_max) = self.to;
+        let result = to_min + (value - from_min) * (to_max - to_min) / (from_max - from_min);
+        if self.clamped {
+            result.clamp(to_min.min(to_max), to_min.max(to_max))
+        } else {
+            result
+        }
+    }
+
+}
+
+impl<N: Noise> Noise for Remapped<N> {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        self.remap(self.noise.value_at(point))
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.remap(self.noise.value_at_time(point, time))
+    }
+
+}
+
+impl<A: Noise, B: Noise> Combined<A, B> {
+
+    pub fn new(first: A, second: B, operation: Operation) -> Self {
+        Self { first, second, operation }
+    }
+
+}
+
+impl<A: Noise, B: Noise> Noise for Combined<A, B> {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        self.operation.apply(self.first.value_at(point), self.second.value_at(point))
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.operation.apply(self.first.value_at_time(point, time), self.second.value_at_time(point, time))
+    }
+
+}
+
+impl Operation {
+
+    pub fn apply(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Operation::Sum => a + b,
+            Operation::Difference => a - b,
+            Operation::Product => a * b,
+            Operation::Minimum => a.min(b),
+            Operation::Maximum => a.max(b),
+            Operation::Average => 0.5 * (a + b),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use proptest::*;
+
+    use crate::basic::vectors::tests::vec3;
+    use crate::noise::Perlin;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn octave_combinators_keep_values_within_unit_range(seed in 0..1000u64, point in vec3(), octaves in 1..8u8, time in -4.0..4.0) {
+            let point = point * 4.0;
+            let base: Arc<dyn Noise> = Arc::new(Remapped::new(Perlin::new(seed), (0.0, 1.0), (0.0, 1.0)).clamped());
+            let turbulence: Arc<dyn Noise> = Arc::new(Turbulence::new(base.clone(), octaves));
+            let ridged: Arc<dyn Noise> = Arc::new(Ridged::new(base, octaves));
+            let warped = Warped::new(turbulence.clone(), ridged.clone(), 0.5);
+            for noise in [&turbulence as &dyn Noise, &ridged, &warped] {
+                for value in [noise.value_at(&point), noise.value_at_time(&point, time)] {
+                    assert!((0.0..=1.0).contains(&value));
+                }
+            }
+        }
+
+    }
+
+}
@@ -1,6 +1,6 @@
 use crate::basic::matrices::Matrix;
 use crate::basic::vectors::Vec3D;
-use crate::noise::Noise;
+use crate::noise::{sample, Noise};
 
 pub struct Fractal<N: Noise> {
     pub base: N,
@@ -16,7 +16,13 @@ impl<N: Noise> Noise for Fractal<N> {
 
     fn value_at(&self, point: &Vec3D) -> f64 {
         let mut p = *point;
-        self.scalar * self.recursive_value_at(&mut p, self.depth)
+        self.scalar * self.recursive_value_at(&mut p, None, self.depth)
+    }
+
+    /// Evaluates all the layers at the same time.
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        let mut p = *point;
+        self.scalar * self.recursive_value_at(&mut p, Some(time), self.depth)
     }
 
 }
@@ -34,13 +40,13 @@ impl<N: Noise> Fractal<N> {
         }
     }
 
-    fn recursive_value_at(&self, point: &mut Vec3D, depth: u8) -> f64 {
-        let result = self.base.value_at(point);
+    fn recursive_value_at(&self, point: &mut Vec3D, time: Option<f64>, depth: u8) -> f64 {
+        let result = sample(&self.base, point, time);
         if depth == 0 {
             result
         } else {
             *point = (&self.transformation * &*point) + self.displacement;
-            result + self.fraction * self.recursive_value_at(point, depth - 1)
+            result + self.fraction * self.recursive_value_at(point, time, depth - 1)
         }
     }
 
@@ -1,17 +1,32 @@
 use std::sync::Arc;
 
+pub use combinators::*;
 pub use fractal::*;
+pub use perlin::*;
 pub use simple::*;
+pub use simplex::*;
+pub use worley::*;
 
 use crate::basic::vectors::Vec3D;
+use crate::sampling::mix;
 
 mod simple;
 mod fractal;
+mod perlin;
+mod simplex;
+mod worley;
+mod combinators;
 
 pub trait Noise: Send + Sync {
 
     fn value_at(&self, point: &Vec3D) -> f64;
 
+    /// Returns the value of the noise at the given point and time. Noises that do not evolve over
+    /// time ignore the time.
+    fn value_at_time(&self, point: &Vec3D, _: f64) -> f64 {
+        self.value_at(point)
+    }
+
 }
 
 impl Noise for Arc<dyn Noise> {
@@ -20,4 +35,21 @@ impl Noise for Arc<dyn Noise> {
         self.as_ref().value_at(point)
     }
 
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        self.as_ref().value_at_time(point, time)
+    }
+
+}
+
+/// Evaluates the given noise at the given point, and at the given time, if any.
+fn sample<N: Noise + ?Sized>(noise: &N, point: &Vec3D, time: Option<f64>) -> f64 {
+    match time {
+        Some(time) => noise.value_at_time(point, time),
+        None => noise.value_at(point),
+    }
+}
+
+/// Hashes the coordinates of a lattice point, along with a seed, into a pseudo-random number.
+fn lattice_hash(seed: u64, coordinates: &[i64]) -> u64 {
+    coordinates.iter().fold(mix(seed), |hash, &c| mix(hash ^ c as u64))
 }
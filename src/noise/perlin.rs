@@ -0,0 +1,134 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::noise::{lattice_hash, Noise};
+
+/// Perlin's improved gradient noise, which interpolates, using a quintic curve, the dot products
+/// of pseudo-random gradients, assigned to the points of the integer lattice, with the offsets from
+/// those points. Its values lie roughly in the range `[0, 1]`, averaging to `0.5`.
+///
+/// Different seeds give different noises. If a period is specified, the noise repeats itself every
+/// `period` units along each axis, which makes it suitable for tileable textures.
+#[derive(Copy, Clone, Debug)]
+pub struct Perlin {
+    pub seed: u64,
+    pub period: Option<u32>,
+}
+
+const GRADIENTS: [Vec3D; 12] = [
+    Vec3D::new(1.0, 1.0, 0.0), Vec3D::new(-1.0, 1.0, 0.0), Vec3D::new(1.0, -1.0, 0.0), Vec3D::new(-1.0, -1.0, 0.0),
+    Vec3D::new(1.0, 0.0, 1.0), Vec3D::new(-1.0, 0.0, 1.0), Vec3D::new(1.0, 0.0, -1.0), Vec3D::new(-1.0, 0.0, -1.0),
+    Vec3D::new(0.0, 1.0, 1.0), Vec3D::new(0.0, -1.0, 1.0), Vec3D::new(0.0, 1.0, -1.0), Vec3D::new(0.0, -1.0, -1.0),
+];
+
+impl Perlin {
+
+    pub fn new(seed: u64) -> Self {
+        Self { seed, period: None }
+    }
+
+    pub fn tileable(seed: u64, period: u32) -> Self {
+        Self { seed, period: Some(period) }
+    }
+
+    /// Returns the value of the noise at the given point, along with its gradient there.
+    pub fn value_and_gradient_at(&self, point: &Vec3D) -> (f64, Vec3D) {
+        let cell = [point.x().floor(), point.y().floor(), point.z().floor()];
+        let f = Vec3D::new(point.x() - cell[0], point.y() - cell[1], point.z() - cell[2]);
+        let cell = cell.map(|c| c as i64);
+
+        let corner = |i: i64, j: i64, k: i64| {
+            let gradient = self.gradient(cell[0] + i, cell[1] + j, cell[2] + k);
+            let value = gradient.dot(f - Vec3D::new(i as f64, j as f64, k as f64));
+            (value, gradient)
+        };
+        let (va, ga) = corner(0, 0, 0);
+        let (vb, gb) = corner(1, 0, 0);
+        let (vc, gc) = corner(0, 1, 0);
+        let (vd, gd) = corner(1, 1, 0);
+        let (ve, ge) = corner(0, 0, 1);
+        let (vf, gf) = corner(1, 0, 1);
+        let (vg, gg) = corner(0, 1, 1);
+        let (vh, gh) = corner(1, 1, 1);
+
+        let (ux, uy, uz) = (fade(f.x()), fade(f.y()), fade(f.z()));
+        let (dux, duy, duz) = (fade_derivative(f.x()), fade_derivative(f.y()), fade_derivative(f.z()));
+
+        let (k1, k2, k3) = (vb - va, vc - va, ve - va);
+        let (k4, k5, k6) = (va - vb - vc + vd, va - vc - ve + vg, va - vb - ve + vf);
+        let k7 = -va + vb + vc - vd + ve - vf - vg + vh;
+        let value = va + ux * k1 + uy * k2 + uz * k3 + ux * uy * k4 + uy * uz * k5 + uz * ux * k6 + ux * uy * uz * k7;
+
+        let gradient = ga
+            + ux * (gb - ga) + uy * (gc - ga) + uz * (ge - ga)
+            + (ux * uy) * (ga - gb - gc + gd) + (uy * uz) * (ga - gc - ge + gg) + (uz * ux) * (ga - gb - ge + gf)
+            + (ux * uy * uz) * (-ga + gb + gc - gd + ge - gf - gg + gh)
+            + Vec3D::new(
+                dux * (k1 + uy * k4 + uz * k6 + uy * uz * k7),
+                duy * (k2 + uz * k5 + ux * k4 + uz * ux * k7),
+                duz * (k3 + ux * k6 + uy * k5 + ux * uy * k7),
+            );
+
+        (0.5 + 0.5 * value, 0.5 * gradient)
+    }
+
+    fn gradient(&self, i: i64, j: i64, k: i64) -> Vec3D {
+        let [i, j, k] = match self.period {
+            Some(period) => [i, j, k].map(|c| c.rem_euclid(period as i64)),
+            None => [i, j, k],
+        };
+        GRADIENTS[(lattice_hash(self.seed, &[i, j, k]) % 12) as usize]
+    }
+
+}
+
+impl Noise for Perlin {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        let (value, _) = self.value_and_gradient_at(point);
+        value
+    }
+
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn fade_derivative(t: f64) -> f64 {
+    30.0 * t * t * (t * (t - 2.0) + 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::tests::vec3;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn has_analytic_gradient(seed in 0..1000u64, point in vec3()) {
+            let perlin = Perlin::new(seed);
+            let point = point * 4.0;
+            let (value, gradient) = perlin.value_and_gradient_at(&point);
+            let h = 1e-6;
+            for axis in 0..3 {
+                let mut shifted = point;
+                shifted[axis] += h;
+                let estimate = (perlin.value_at(&shifted) - value) / h;
+                assert!((estimate - gradient[axis]).abs() < 1e-4);
+            }
+        }
+
+        #[test]
+        fn is_tileable(seed in 0..1000u64, period in 1..16u32, point in vec3(), i in -3..3i32, j in -3..3i32, k in -3..3i32) {
+            let perlin = Perlin::tileable(seed, period);
+            let point = point * 4.0;
+            let shifted = point + Vec3D::new(i as f64, j as f64, k as f64) * (period as f64);
+            assert!((perlin.value_at(&point) - perlin.value_at(&shifted)).abs() < 1e-9);
+        }
+
+    }
+
+}
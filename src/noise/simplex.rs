@@ -0,0 +1,142 @@
+use crate::basic::vectors::Vec3D;
+use crate::noise::{lattice_hash, Noise};
+
+/// Simplex noise, which sums the radially attenuated contributions of pseudo-random gradients at
+/// the corners of the simplex containing each point. It is cheaper than Perlin noise in higher
+/// dimensions, and has fewer directional artifacts. Its values lie roughly in the range `[0, 1]`,
+/// averaging to `0.5`.
+///
+/// The 4D variant is used for evaluating the noise at different points in time, which makes the
+/// noise evolve smoothly over time, as needed for animated textures.
+#[derive(Copy, Clone, Debug)]
+pub struct Simplex {
+    pub seed: u64,
+}
+
+impl Simplex {
+
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Returns the value of the 3D noise at the given point, along with its gradient there.
+    pub fn value_and_gradient_at(&self, point: &Vec3D) -> (f64, Vec3D) {
+        let (value, [dx, dy, dz]) = simplex(self.seed, [point.x(), point.y(), point.z()], 32.0, gradient_3d);
+        (value, Vec3D::new(dx, dy, dz))
+    }
+
+    /// Returns the value of the 4D noise at the given point and time, along with its spatial
+    /// gradient, and its derivative with respect to time.
+    pub fn value_and_gradient_at_time(&self, point: &Vec3D, time: f64) -> (f64, Vec3D, f64) {
+        let (value, [dx, dy, dz, dt]) = simplex(self.seed, [point.x(), point.y(), point.z(), time], 27.0, gradient_4d);
+        (value, Vec3D::new(dx, dy, dz), dt)
+    }
+
+}
+
+impl Noise for Simplex {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        let (value, _) = self.value_and_gradient_at(point);
+        value
+    }
+
+    fn value_at_time(&self, point: &Vec3D, time: f64) -> f64 {
+        let (value, _, _) = self.value_and_gradient_at_time(point, time);
+        value
+    }
+
+}
+
+/// Evaluates `N`-dimensional simplex noise, and its gradient, mapped to the range `[0, 1]`.
+fn simplex<const N: usize>(seed: u64, point: [f64; N], scale: f64, gradient: fn(u64) -> [f64; N]) -> (f64, [f64; N]) {
+    let n = N as f64;
+    let skew = ((n + 1.0).sqrt() - 1.0) / n;
+    let unskew = (1.0 - 1.0 / (n + 1.0).sqrt()) / n;
+
+    let s = point.iter().sum::<f64>() * skew;
+    let cell = point.map(|c| (c + s).floor());
+    let t = cell.iter().sum::<f64>() * unskew;
+    let mut offset = [0.0; N];
+    for axis in 0..N {
+        offset[axis] = point[axis] - (cell[axis] - t);
+    }
+
+    // The simplex corners are reached from the cell origin by stepping along the axes in the
+    // descending order of the offset components.
+    let mut axes: [usize; N] = std::array::from_fn(|axis| axis);
+    axes.sort_by(|&a, &b| offset[b].total_cmp(&offset[a]));
+
+    let mut value = 0.0;
+    let mut derivatives = [0.0; N];
+    let mut corner = cell.map(|c| c as i64);
+    for m in 0 ..= N {
+        if m > 0 {
+            corner[axes[m - 1]] += 1;
+        }
+        let mut d = [0.0; N];
+        for axis in 0..N {
+            d[axis] = point[axis] - (corner[axis] as f64 - t - (m as f64) * unskew);
+        }
+        let attenuation = 0.6 - d.iter().map(|c| c * c).sum::<f64>();
+        if attenuation <= 0.0 {
+            continue
+        }
+        let g = gradient(lattice_hash(seed, &corner));
+        let g_dot_d = g.iter().zip(d.iter()).map(|(a, b)| a * b).sum::<f64>();
+        let a2 = attenuation * attenuation;
+        let a4 = a2 * a2;
+        value += a4 * g_dot_d;
+        for axis in 0..N {
+            derivatives[axis] += a4 * g[axis] - 8.0 * a2 * attenuation * g_dot_d * d[axis];
+        }
+    }
+    (0.5 + 0.5 * scale * value, derivatives.map(|d| 0.5 * scale * d))
+}
+
+fn gradient_3d(hash: u64) -> [f64; 3] {
+    let sign = |bit: u64| if hash & bit == 0 { 1.0 } else { -1.0 };
+    match (hash >> 2) % 3 {
+        0 => [sign(1), sign(2), 0.0],
+        1 => [sign(1), 0.0, sign(2)],
+        _ => [0.0, sign(1), sign(2)],
+    }
+}
+
+fn gradient_4d(hash: u64) -> [f64; 4] {
+    let sign = |bit: u64| if hash & bit == 0 { 1.0 } else { -1.0 };
+    let mut gradient = [sign(1), sign(2), sign(4), 0.0];
+    gradient.rotate_right(((hash >> 3) % 4) as usize);
+    gradient
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::tests::vec3;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn has_analytic_gradients(seed in 0..1000u64, point in vec3(), time in -4.0..4.0) {
+            let noise = Simplex::new(seed);
+            let point = point * 4.0;
+            let h = 1e-6;
+
+            let (value, gradient) = noise.value_and_gradient_at(&point);
+            let (value_4d, gradient_4d, time_derivative) = noise.value_and_gradient_at_time(&point, time);
+            for axis in 0..3 {
+                let mut shifted = point;
+                shifted[axis] += h;
+                assert!(((noise.value_at(&shifted) - value) / h - gradient[axis]).abs() < 1e-4);
+                assert!(((noise.value_at_time(&shifted, time) - value_4d) / h - gradient_4d[axis]).abs() < 1e-4);
+            }
+            assert!(((noise.value_at_time(&point, time + h) - value_4d) / h - time_derivative).abs() < 1e-4);
+        }
+
+    }
+
+}
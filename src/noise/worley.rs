@@ -0,0 +1,151 @@
+use crate::basic::vectors::Vec3D;
+use crate::noise::{lattice_hash, Noise};
+
+/// Worley's cellular noise, which scatters one pseudo-random feature point in every cell of the
+/// integer lattice, and measures the distances from each point to its nearest feature points. The
+/// distance to the nearest feature point (`F1`) gives a pattern of rounded cells, while the
+/// difference between the distances to the two nearest ones (`F2 - F1`) gives a pattern of cracks
+/// along the cell borders. Distances are mostly within the range `[0, 1]`, but could occasionally
+/// exceed it, especially for `F2`.
+#[derive(Copy, Clone, Debug)]
+pub struct Worley {
+    pub seed: u64,
+    pub metric: Metric,
+    pub feature: Feature,
+}
+
+/// How distances to feature points are measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// The straight line distance, giving round cells.
+    Euclidean,
+    /// The sum of the distances along the axes, giving diamond shaped cells.
+    Manhattan,
+    /// The largest of the distances along the axes, giving boxy cells.
+    Chebyshev,
+}
+
+/// Which distance, or combination of distances, the noise evaluates to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// The distance to the nearest feature point.
+    F1,
+    /// The distance to the second nearest feature point.
+    F2,
+    /// The difference between the distances to the two nearest feature points.
+    Difference,
+}
+
+impl Worley {
+
+    /// The offsets of the cells searched for feature points along each axis, nearest first.
+    const OFFSETS: [i64; 5] = [0, -1, 1, -2, 2];
+
+    pub fn new(seed: u64) -> Self {
+        Self { seed, metric: Metric::Euclidean, feature: Feature::F1 }
+    }
+
+    pub fn with_metric(self, metric: Metric) -> Self {
+        Self { metric, ..self }
+    }
+
+    pub fn with_feature(self, feature: Feature) -> Self {
+        Self { feature, ..self }
+    }
+
+    /// Returns the distances from the given point to the nearest, and second nearest, feature
+    /// points. The second nearest one could lie two cells away, so the cells within two cells of
+    /// the point are searched, nearest first, skipping the ones too far to hold either.
+    pub fn distances_at(&self, point: &Vec3D) -> (f64, f64) {
+        let cell = [point.x().floor() as i64, point.y().floor() as i64, point.z().floor() as i64];
+        let fraction = [point.x() - cell[0] as f64, point.y() - cell[1] as f64, point.z() - cell[2] as f64];
+        // the distance along an axis from the point to the cell at the given offset
+        let gap = |axis: usize, offset: i64| match offset {
+            0 => 0.0,
+            _ if offset > 0 => offset as f64 - fraction[axis],
+            _ => fraction[axis] - (offset + 1) as f64,
+        };
+        let mut nearest = (f64::INFINITY, f64::INFINITY);
+        for i in Self::OFFSETS {
+            for j in Self::OFFSETS {
+                for k in Self::OFFSETS {
+                    if self.metric.length(&Vec3D::new(gap(0, i), gap(1, j), gap(2, k))) >= nearest.1 {
+                        continue
+                    }
+                    let offset = self.feature_point([cell[0] + i, cell[1] + j, cell[2] + k]) - *point;
+                    let distance = self.metric.length(&offset);
+                    if distance < nearest.0 {
+                        nearest = (distance, nearest.0);
+                    } else if distance < nearest.1 {
+                        nearest.1 = distance;
+                    }
+                }
+            }
+        }
+        nearest
+    }
+
+    fn feature_point(&self, cell: [i64; 3]) -> Vec3D {
+        let hash = lattice_hash(self.seed, &cell);
+        let fraction = |shift: u32| ((hash >> shift) & 0x1FFFFF) as f64 / (1 << 21) as f64;
+        Vec3D::new(cell[0] as f64 + fraction(0), cell[1] as f64 + fraction(21), cell[2] as f64 + fraction(42))
+    }
+
+}
+
+impl Noise for Worley {
+
+    fn value_at(&self, point: &Vec3D) -> f64 {
+        let (f1, f2) = self.distances_at(point);
+        match self.feature {
+            Feature::F1 => f1,
+            Feature::F2 => f2,
+            Feature::Difference => f2 - f1,
+        }
+    }
+
+}
+
+impl Metric {
+
+    fn length(&self, v: &Vec3D) -> f64 {
+        let (x, y, z) = (v.x().abs(), v.y().abs(), v.z().abs());
+        match self {
+            Metric::Euclidean => (x * x + y * y + z * z).sqrt(),
+            Metric::Manhattan => x + y + z,
+            Metric::Chebyshev => x.max(y).max(z),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::tests::vec3;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn finds_the_distances_to_the_two_nearest_feature_points(seed in 0..1000u64, point in vec3()) {
+            let point = point * 8.0;
+            for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+                let noise = Worley::new(seed).with_metric(metric);
+                let cell = [point.x().floor() as i64, point.y().floor() as i64, point.z().floor() as i64];
+                let mut distances: Vec<f64> = (-2..=2)
+                    .flat_map(|i| (-2..=2).flat_map(move |j| (-2..=2).map(move |k| [cell[0] + i, cell[1] + j, cell[2] + k])))
+                    .map(|neighbour| metric.length(&(noise.feature_point(neighbour) - point)))
+                    .collect();
+                distances.sort_by(f64::total_cmp);
+                let (f1, f2) = noise.distances_at(&point);
+                assert_eq!((f1, f2), (distances[0], distances[1]));
+                assert_eq!(noise.with_feature(Feature::Difference).value_at(&point), f2 - f1);
+            }
+        }
+
+    }
+
+}
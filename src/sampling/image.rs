@@ -0,0 +1,108 @@
+use rand::prelude::Distribution;
+use rand::{Rng, RngExt};
+
+use crate::basic::vectors::Vec3D;
+use crate::imaging::Image;
+use crate::sampling::PDF;
+
+/// A distribution over the square `[-1, 1] x [-1, 1]`, whose density is proportional to the
+/// luminance of the pixels of an image stretched over that square. The top row of the image maps
+/// to `y = 1`.
+pub struct ImageDistribution {
+    width: usize,
+    height: usize,
+    cumulative_weights: Vec<f64>,
+}
+
+impl ImageDistribution {
+
+    /// Returns the distribution over the given image, or `None` if the image has no pixels with a
+    /// positive luminance, which leaves nothing to sample.
+    pub fn new(image: &Image) -> Option<Self> {
+        let mut sum = 0.0;
+        let cumulative_weights = image.pixel_position_iterator()
+            .map(|ref p| {
+                sum += image[p].luminance().max(0.0);
+                sum
+            })
+            .collect();
+        let distribution = Self { width: image.width(), height: image.height(), cumulative_weights };
+        (distribution.total_weight() > 0.0).then_some(distribution)
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.cumulative_weights.last().copied().unwrap_or(0.0)
+    }
+
+    fn weight_at(&self, index: usize) -> f64 {
+        let previous = if index > 0 { self.cumulative_weights[index - 1] } else { 0.0 };
+        self.cumulative_weights[index] - previous
+    }
+
+    fn index_of(&self, value: &Vec3D) -> Option<usize> {
+        let column = ((value.x() + 1.0) * 0.5 * self.width as f64).floor();
+        let row = ((1.0 - value.y()) * 0.5 * self.height as f64).floor();
+        if column >= 0.0 && row >= 0.0 && (column as usize) < self.width && (row as usize) < self.height {
+            Some((row as usize) * self.width + (column as usize))
+        } else {
+            None
+        }
+    }
+
+}
+
+impl Distribution<Vec3D> for ImageDistribution {
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3D {
+        let choice = rng.random::<f64>() * self.total_weight();
+        let index = self.cumulative_weights
+            .partition_point(|&w| w <= choice)
+            .min(self.cumulative_weights.len() - 1);
+        let column = (index % self.width) as f64 + rng.random::<f64>();
+        let row = (index / self.width) as f64 + rng.random::<f64>();
+        Vec3D::new(
+            2.0 * column / (self.width as f64) - 1.0,
+            1.0 - 2.0 * row / (self.height as f64),
+            0.0
+        )
+    }
+
+}
+
+impl PDF<Vec3D> for ImageDistribution {
+
+    fn pdf(&self, value: &Vec3D) -> f64 {
+        match self.index_of(value) {
+            Some(index) => {
+                let pixel_area = 4.0 / ((self.width * self.height) as f64);
+                self.weight_at(index) / (self.total_weight() * pixel_area)
+            },
+            None => 0.0
+        }
+    }
+
+    fn contains(&self, value: &Vec3D) -> bool {
+        value.z() == 0.0 && self.index_of(value).is_some()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::colors::Color;
+
+    use super::*;
+
+    #[test]
+    fn rejects_images_without_light() {
+        assert!(ImageDistribution::new(&Image::new(0, 0)).is_none());
+        assert!(ImageDistribution::new(&Image::new(4, 4)).is_none());
+
+        let mut image = Image::new(4, 4);
+        let position = image.pixel_position_iterator().nth(5).unwrap();
+        image[&position] = Color::WHITE;
+        let distribution = ImageDistribution::new(&image).unwrap();
+        assert_eq!(distribution.pdf(&Vec3D::new(-0.25, 0.25, 0.0)), 4.0);
+    }
+
+}
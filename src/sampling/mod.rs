@@ -1,10 +1,18 @@
 pub use circle::*;
+pub use image::*;
+pub use polygon::*;
+pub use primary::*;
+pub use random::*;
 pub use sphere::*;
 pub use square::*;
 
 mod circle;
 mod square;
 mod sphere;
+mod polygon;
+mod image;
+mod random;
+mod primary;
 
 pub trait Space<T>: PDF<T> {
 
@@ -0,0 +1,99 @@
+use std::f64::consts::PI;
+
+use rand::prelude::Distribution;
+use rand::{Rng, RngExt};
+
+use crate::basic::vectors::Vec3D;
+use crate::EPSILON;
+use crate::sampling::{UniformSolidUnitSquare, PDF};
+
+/// A uniform distribution over a regular polygon, inscribed in the unit circle, and rotated by the
+/// given angle (in radians) around its center.
+#[derive(Clone)]
+pub struct UniformSolidPolygon {
+    sides: u8,
+    rotation: f64,
+}
+
+impl UniformSolidPolygon {
+
+    /// Returns the distribution over the polygon with the given number of sides, or `None` if there
+    /// are less than 3 sides, which makes no polygon.
+    pub fn new(sides: u8, rotation: f64) -> Option<Self> {
+        (sides >= 3).then_some(Self { sides, rotation })
+    }
+
+    fn vertex(&self, index: u8) -> Vec3D {
+        let angle = self.rotation + 2.0 * PI * (index as f64) / (self.sides as f64);
+        Vec3D::new(angle.cos(), angle.sin(), 0.0)
+    }
+
+    fn area(&self) -> f64 {
+        let n = self.sides as f64;
+        0.5 * n * (2.0 * PI / n).sin()
+    }
+
+}
+
+impl Distribution<Vec3D> for UniformSolidPolygon {
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3D {
+        let triangle = rng.random_range(0..self.sides);
+        let v1 = self.vertex(triangle);
+        let v2 = self.vertex((triangle + 1) % self.sides);
+        let square_sample = rng.sample(UniformSolidUnitSquare);
+        let (a, b) = if square_sample.x() + square_sample.y() > 1.0 {
+            (1.0 - square_sample.x(), 1.0 - square_sample.y())
+        } else {
+            (square_sample.x(), square_sample.y())
+        };
+        v1 * a + v2 * b
+    }
+
+}
+
+impl PDF<Vec3D> for UniformSolidPolygon {
+
+    fn pdf(&self, _: &Vec3D) -> f64 {
+        1.0 / self.area()
+    }
+
+    fn contains(&self, value: &Vec3D) -> bool {
+        value.z() == 0.0 && (0..self.sides).all(|i| {
+            let v1 = self.vertex(i);
+            let v2 = self.vertex((i + 1) % self.sides);
+            (v2 - v1).cross(&(value - &v1)).z() >= -EPSILON
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+    use rand::rng;
+
+    use super::*;
+
+    #[test]
+    fn rejects_degenerate_polygons() {
+        for sides in 0 .. 3 {
+            assert!(UniformSolidPolygon::new(sides, 0.0).is_none());
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn generates_samples_inside_the_polygon(sides in 3u8..12, rotation in -PI..PI) {
+            let polygon = UniformSolidPolygon::new(sides, rotation).unwrap();
+
+            let sample = rng().sample(&polygon);
+
+            assert!(polygon.contains(&sample));
+            assert!(sample.x() * sample.x() + sample.y() * sample.y() <= 1.0);
+        }
+
+    }
+
+}
@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use rand::rngs::SmallRng;
+use rand::{RngExt, SeedableRng};
+
+/// The primary samples of a path, i.e. the uniform random numbers that all the random decisions
+/// taken while tracing it come from, when replayed (see `replay`). Mutating them mutates the path,
+/// as primary sample space Metropolis light transport does. Samples are drawn in the order they
+/// are used, and only get mutated when they are used, which spares mutating samples that paths end
+/// before using.
+///
+/// Every iteration either redraws all the samples (a large step), or perturbs each of them by a
+/// normally distributed offset of the given standard deviation (a small step). Iterations are then
+/// accepted, or rejected, which restores the samples of the previous iteration.
+pub struct PrimarySamples {
+    samples: Vec<PrimarySample>,
+    index: usize,
+    iteration: u64,
+    large_step: bool,
+    /// Whether the current iteration is being replayed, without mutating the samples again.
+    replaying: bool,
+    last_large_step: u64,
+    sigma: f64,
+    large_step_probability: f64,
+    rng: SmallRng,
+}
+
+#[derive(Copy, Clone, Default)]
+struct PrimarySample {
+    value: f64,
+    /// The iteration that last modified the sample.
+    modified: u64,
+    backup: f64,
+    backup_modified: u64,
+}
+
+impl PrimarySamples {
+
+    /// Creates primary samples drawn from a generator seeded with the given seed, so that the same
+    /// seed gives the same samples, mutated the same way.
+    pub fn new(seed: u64, sigma: f64, large_step_probability: f64) -> Self {
+        Self {
+            samples: Vec::new(),
+            index: 0,
+            iteration: 0,
+            large_step: true,
+            replaying: false,
+            last_large_step: 0,
+            sigma,
+            large_step_probability,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Starts a new iteration, which mutates the samples as they get used, and replays them from
+    /// the first one.
+    pub fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rng.random::<f64>() < self.large_step_probability;
+        self.replaying = false;
+        self.index = 0;
+    }
+
+    /// Replays the samples of the current iteration from the first one, without mutating them, e.g.
+    /// to trace the same path again once the world changed. Samples the iteration did not use yet
+    /// still get mutated.
+    pub fn replay_iteration(&mut self) {
+        self.replaying = true;
+        self.index = 0;
+    }
+
+    pub fn is_large_step(&self) -> bool {
+        self.large_step
+    }
+
+    /// Keeps the samples of the current iteration.
+    pub fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step = self.iteration;
+        }
+    }
+
+    /// Restores the samples of the previous iteration.
+    pub fn reject(&mut self) {
+        let iteration = self.iteration;
+        for sample in self.samples.iter_mut().filter(|sample| sample.modified == iteration) {
+            sample.value = sample.backup;
+            sample.modified = sample.backup_modified;
+        }
+        self.iteration -= 1;
+    }
+
+    /// Returns the next sample of the current iteration, applying to it the mutations it missed
+    /// since it was last used.
+    pub(crate) fn next(&mut self) -> f64 {
+        if self.index == self.samples.len() {
+            self.samples.push(PrimarySample::default());
+        }
+        let mut sample = self.samples[self.index];
+        if self.replaying && sample.modified == self.iteration {
+            self.index += 1;
+            return sample.value
+        }
+        if sample.modified < self.last_large_step {
+            sample.value = self.rng.random();
+            sample.modified = self.last_large_step;
+        }
+        sample.backup = sample.value;
+        sample.backup_modified = sample.modified;
+        if self.large_step {
+            sample.value = self.rng.random();
+        } else {
+            // the small steps missed since the sample was last used add up to a single one
+            let steps = (self.iteration - sample.modified) as f64;
+            sample.value += self.normal() * self.sigma * steps.sqrt();
+            sample.value -= sample.value.floor();
+        }
+        sample.modified = self.iteration;
+        self.samples[self.index] = sample;
+        self.index += 1;
+        sample.value
+    }
+
+    /// Returns a standard normally distributed number, using the Box-Muller transform.
+    fn normal(&mut self) -> f64 {
+        let u: f64 = 1.0 - self.rng.random::<f64>();
+        let v: f64 = self.rng.random();
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+
+}
+
+impl Default for PrimarySamples {
+
+    fn default() -> Self {
+        Self::new(0, 0.01, 0.3)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::sampling::{random, replay};
+
+    use super::*;
+
+    fn draw(samples: &mut PrimarySamples) -> [f64; 3] {
+        replay(samples, || [random(), random(), random()])
+    }
+
+    proptest! {
+
+        #[test]
+        fn restores_the_samples_of_rejected_iterations(seed in 0u64..1000) {
+            let mut samples = PrimarySamples::new(seed, 0.01, 0.5);
+            let original = draw(&mut samples);
+            for _ in 0 .. 10 {
+                samples.start_iteration();
+                let mutated = draw(&mut samples);
+                if !samples.is_large_step() {
+                    for (a, b) in original.iter().zip(mutated) {
+                        let distance = (a - b).abs();
+                        prop_assert!(distance.min(1.0 - distance) < 0.1, "{:?} {:?}", original, mutated);
+                    }
+                }
+                samples.reject();
+                for (a, sample) in original.iter().zip(&samples.samples) {
+                    prop_assert!((a - sample.value).abs() < 1e-12, "{:?}", original);
+                }
+            }
+        }
+
+        #[test]
+        fn replays_iterations_without_mutating_them(seed in 0u64..1000) {
+            let mut samples = PrimarySamples::new(seed, 0.01, 0.5);
+            draw(&mut samples);
+            for _ in 0 .. 10 {
+                samples.start_iteration();
+                let mutated = draw(&mut samples);
+                samples.replay_iteration();
+                prop_assert_eq!(draw(&mut samples), mutated);
+                samples.accept();
+            }
+        }
+
+    }
+
+}
@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::SmallRng;
+use rand::{Rng, RngExt, SeedableRng, TryRng};
+
+use crate::sampling::PrimarySamples;
+
+static SEEDED: AtomicBool = AtomicBool::new(false);
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static GENERATOR: RefCell<SmallRng> = RefCell::new(SmallRng::from_rng(&mut rand::rng()));
+    static REPLAYED: RefCell<Option<PrimarySamples>> = const { RefCell::new(None) };
+}
+
+/// A handle to the random number generator of the current thread, which all the random decisions
+/// of the renderer go through. Unless a seed is set using `seed`, the generator is seeded from
+/// system entropy, and renders are not reproducible. While primary samples are replayed (see
+/// `replay`), the generator gives them instead.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LocalRng;
+
+pub fn rng() -> LocalRng {
+    LocalRng
+}
+
+pub fn random<T>() -> T where StandardUniform: Distribution<T> {
+    rng().random()
+}
+
+/// Sets, or clears, the seed from which the random number generators get reseeded by `reseed`.
+pub fn seed(seed: Option<u64>) {
+    SEED.store(seed.unwrap_or(0), Ordering::SeqCst);
+    SEEDED.store(seed.is_some(), Ordering::SeqCst);
+}
+
+/// Reseeds the generator of the current thread for the given unit of work (e.g. a frame and a
+/// pixel index), if a seed is set, so that the random decisions taken for that unit of work do not
+/// depend on which thread it runs on, or on what ran before it on that thread.
+pub fn reseed(stream: u64, index: u64) {
+    if SEEDED.load(Ordering::Relaxed) {
+        let state = mix(mix(SEED.load(Ordering::Relaxed) ^ mix(stream)) ^ index);
+        GENERATOR.with(|generator| *generator.borrow_mut() = SmallRng::seed_from_u64(state));
+    }
+}
+
+/// Runs the given function, with the random decisions it takes on the current thread coming from
+/// the given primary samples, each of which gives the bits of one `u32` or `u64`, so that the same
+/// samples replay the same decisions (e.g. the same path).
+pub fn replay<R, F: FnOnce() -> R>(samples: &mut PrimarySamples, f: F) -> R {
+    REPLAYED.with(|replayed| *replayed.borrow_mut() = Some(std::mem::take(samples)));
+    let result = f();
+    *samples = REPLAYED.with(|replayed| replayed.borrow_mut().take()).unwrap_or_default();
+    result
+}
+
+/// Returns the next primary sample being replayed, if any.
+fn replayed() -> Option<f64> {
+    REPLAYED.with(|replayed| replayed.borrow_mut().as_mut().map(|samples| samples.next()))
+}
+
+/// The SplitMix64 finalizer, which scrambles nearby inputs into unrelated outputs.
+pub(crate) fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+impl TryRng for LocalRng {
+
+    type Error = Infallible;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        if let Some(sample) = replayed() {
+            return Ok((sample * 4294967296.0) as u32)
+        }
+        Ok(GENERATOR.with(|generator| generator.borrow_mut().next_u32()))
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        if let Some(sample) = replayed() {
+            return Ok((sample * 18446744073709551616.0) as u64)
+        }
+        Ok(GENERATOR.with(|generator| generator.borrow_mut().next_u64()))
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        if REPLAYED.with(|replayed| replayed.borrow().is_some()) {
+            for chunk in dst.chunks_mut(8) {
+                let bytes = self.try_next_u64()?.to_le_bytes();
+                chunk.copy_from_slice(&bytes[.. chunk.len()]);
+            }
+            return Ok(())
+        }
+        GENERATOR.with(|generator| generator.borrow_mut().fill_bytes(dst));
+        Ok(())
+    }
+
+}
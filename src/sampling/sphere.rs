@@ -1,11 +1,11 @@
 use std::f64::consts::{FRAC_1_PI, PI};
 
 use rand::prelude::Distribution;
-use rand::{rng, Rng, RngExt};
+use rand::{Rng, RngExt};
 
 use crate::basic::vectors::{Dot, Vec3D};
 use crate::rough_equality;
-use crate::sampling::{Space, UniformSolidUnitSquare, PDF};
+use crate::sampling::{rng, Space, UniformSolidUnitSquare, PDF};
 
 pub struct UniformUnitSphere;
 
@@ -39,9 +39,9 @@ impl UniformUnitSphere {
         let phi = 2.0 * PI * unit_square_sample.y();
         let (sin_phi, cos_phi) = phi.sin_cos();
         Vec3D::new(
-            cos_theta * cos_phi,
-            cos_theta * sin_phi,
-            sin_theta
+            sin_theta * cos_phi,
+            sin_theta * sin_phi,
+            cos_theta
         )
     }
 
@@ -58,3 +58,27 @@ impl PDF<Vec3D> for UniformUnitSphere {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_the_whole_sphere_uniformly() {
+        // Uniformly distributed points on the unit sphere have uniformly distributed heights, and
+        // are equally likely to be in any quadrant around the z-axis.
+        let count = 100_000;
+        let mut heights = [0.0; 4];
+        let mut quadrants = [0.0; 4];
+        for _ in 0 .. count {
+            let point = UniformUnitSphere.arbitrary_sample();
+            assert!(rough_equality(point.length(), 1.0));
+            heights[(((point.z() + 1.0) * 2.0) as usize).min(3)] += 1.0 / count as f64;
+            quadrants[(point.x() > 0.0) as usize * 2 + (point.y() > 0.0) as usize] += 1.0 / count as f64;
+        }
+        for fraction in heights.iter().chain(&quadrants) {
+            assert!((fraction - 0.25).abs() < 0.01, "{:?} {:?}", heights, quadrants);
+        }
+    }
+
+}
@@ -0,0 +1,1045 @@
+use std::collections::HashMap;
+use std::f64::consts::SQRT_2;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::ImageResult;
+
+use crate::basic::colors::Color;
+use crate::basic::matrices::Matrix;
+use crate::basic::vectors::Vec3D;
+use crate::builders::Building;
+use crate::imaging::{Filtering, Image, ImageMap, Wrapping};
+use crate::geometries::{Geometry, Sphere};
+use crate::lights::{DirectionalLight, DiskLight, Light, Lights, PointLight, Profile, RectangleLight, SphereLight, SpotLight};
+use crate::materials::{Absorptive, Coated, Composite, DiffuseModel, Diffusive, Emissive, Material, Reflective, RefractionIndex, Refractive, Subsurface, ThinFilm};
+use crate::noise::{Combined, Feature, Fractal, Metric, Noise, Operation, Perlin, Remapped, Ridged, Simple, Simplex, Turbulence, Warped, Worley};
+use crate::sampling::{ImageDistribution, UniformSolidPolygon};
+use crate::scenes::{Argument, Location, Node, RenderSettings, Scene, SceneError, SceneWorld, Value};
+use crate::textures::procedural::{Bands, Checker, Domain, Gradient, Marble, Pattern, Procedural, Ridges, Rings, Stripes, Threshold};
+use crate::textures::{AlphaMap, Black, BumpMap, Constant, Cutout, ImageParameter, ImageTexture, NoiseMask, NoiseParameter, NormalMap, Parameter, Perturbed, PrincipledTexture, Same, Texture};
+use crate::things::{AtomicThing, Thing, Things};
+use crate::transforms::{Affine, AffineTransformation, Linear, Translation};
+use crate::viewing::{ApertureShape, Camera, Equirectangular, Exposure, Fisheye, FisheyeMapping, Lens, Metropolis, OmniDirectionalStereo, Orthographic, Perspective, Projection, Sensor};
+use crate::worlds::{Aspect, World};
+
+type Result<T> = std::result::Result<T, SceneError>;
+
+/// The ways the light of a world could be traced.
+enum Integrator {
+    Path,
+    Bidirectional,
+    PhotonMapping { photons: usize, radius: f64 },
+    AmbientOcclusion { radius: f64 },
+    Inspection(Aspect),
+}
+
+/// Turns the nodes of a scene description into the runtime objects they describe, keeping track of
+/// the named definitions encountered along the way.
+pub struct Loader {
+    directory: PathBuf,
+    materials: HashMap<String, Arc<dyn Material>>,
+}
+
+/// A cursor over the arguments of a node, which reports missing, extra, or mistyped arguments.
+struct Arguments<'a> {
+    node: &'a Node,
+    index: usize,
+}
+
+impl Loader {
+
+    /// Creates a loader that looks up the files referenced by scenes relative to the given
+    /// directory.
+    pub fn new(directory: &Path) -> Self {
+        Self { directory: directory.to_path_buf(), materials: HashMap::new() }
+    }
+
+    pub fn scene(&mut self, nodes: &[Node]) -> Result<Scene> {
+        let mut camera = None;
+        let mut world = None;
+        let mut render = None;
+        for node in nodes {
+            match node.name.as_str() {
+                "camera" => once(&mut camera, node, |n| self.camera(n))?,
+                "world" => once(&mut world, node, |n| self.world(n))?,
+                "render" => once(&mut render, node, |n| self.render(n))?,
+                "material" => {
+                    let mut arguments = Arguments::of(node);
+                    let (name, location) = arguments.symbol("a material name")?;
+                    let material = self.material(&mut arguments)?;
+                    if self.materials.insert(name.to_string(), material).is_some() {
+                        return Err(SceneError::invalid(location, format!("the material `{}` is already defined", name)))
+                    }
+                },
+                _ => return Err(unknown(node, &["camera", "world", "render", "material"])),
+            }
+        }
+        let start = Location { line: 1, column: 1 };
+        Ok(Scene {
+            camera: camera.ok_or_else(|| SceneError::invalid(start, "the scene has no `camera`"))?,
+            world: world.ok_or_else(|| SceneError::invalid(start, "the scene has no `world`"))?,
+            render: render.unwrap_or_default(),
+        })
+    }
+
+    fn camera(&self, node: &Node) -> Result<Camera<Box<dyn Projection>>> {
+        Arguments::of(node).done()?;
+        let mut camera = Camera {
+            projection: Box::new(Perspective) as Box<dyn Projection>,
+            lens: Lens::ideal(1.0),
+            sensor: Sensor::new(960, 720, 1.0),
+            exposure: Exposure(0.0),
+            samples_per_pixel: 16,
+        };
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "projection" => camera.projection = self.projection(&mut arguments)?,
+                "lens" => camera.lens = self.lens(child)?,
+                "sensor" => {
+                    let width = arguments.whole("a width", 1, u16::MAX as u64)?;
+                    let height = arguments.whole("a height", 1, u16::MAX as u64)?;
+                    let gain = arguments.optional_number(1.0)?;
+                    let spectral = match arguments.optional_symbol()? {
+                        None | Some(("rgb", _)) => false,
+                        Some(("spectral", _)) => true,
+                        Some((other, location)) => return Err(unknown_kind(location, "sensor response", other, &["rgb", "spectral"])),
+                    };
+                    camera.sensor = Sensor::new(width as usize, height as usize, gain).with_spectral_response(spectral);
+                },
+                "exposure" => camera.exposure = Exposure(arguments.number("an exposure time")?),
+                "samples" => camera.samples_per_pixel = arguments.whole("a number of samples per pixel", 1, u16::MAX as u64)? as u16,
+                _ => return Err(unknown(child, &["projection", "lens", "sensor", "exposure", "samples"])),
+            }
+            arguments.done()?;
+        }
+        Ok(camera)
+    }
+
+    fn projection(&self, arguments: &mut Arguments) -> Result<Box<dyn Projection>> {
+        let (kind, location) = arguments.symbol("a projection")?;
+        Ok(match kind {
+            "perspective" => Box::new(Perspective),
+            "orthographic" => Box::new(Orthographic { half_height: arguments.number("a half height")? }),
+            "fisheye" => {
+                let field_of_view = arguments.number("a field of view")?.to_radians();
+                let mapping = match arguments.optional_symbol()? {
+                    None | Some(("equidistant", _)) => FisheyeMapping::Equidistant,
+                    Some(("equisolid", _)) => FisheyeMapping::Equisolid,
+                    Some((other, location)) => return Err(unknown_kind(location, "fisheye mapping", other, &["equidistant", "equisolid"])),
+                };
+                Box::new(Fisheye { field_of_view, mapping })
+            },
+            "equirectangular" => Box::new(Equirectangular),
+            "stereo" => Box::new(OmniDirectionalStereo { interpupillary_distance: arguments.number("an interpupillary distance")? }),
+            _ => return Err(unknown_kind(location, "projection", kind, &["perspective", "orthographic", "fisheye", "equirectangular", "stereo"])),
+        })
+    }
+
+    fn lens(&self, node: &Node) -> Result<Lens> {
+        Arguments::of(node).done()?;
+        let mut focal_length = 1.0;
+        let mut focal_distance = None;
+        let mut aperture = 0.0;
+        let mut aperture_shape = ApertureShape::Circular;
+        let mut vignetting = 0.0;
+        let mut chromatic_aberration = (0.0, 0.0);
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "focal_length" => focal_length = arguments.number("a focal length")?,
+                "focal_distance" => focal_distance = Some(arguments.number("a focal plane distance")?),
+                "aperture" => aperture = arguments.number("an aperture")?,
+                "aperture_shape" => aperture_shape = match arguments.symbol("an aperture shape")? {
+                    ("circular", _) => ApertureShape::Circular,
+                    ("polygonal", _) => {
+                        let sides = arguments.whole("a number of sides", 3, u8::MAX as u64)? as u8;
+                        let rotation = arguments.optional_number(0.0)?.to_radians();
+                        ApertureShape::Polygonal(UniformSolidPolygon::new(sides, rotation).unwrap())
+                    },
+                    ("masked", _) => {
+                        let location = arguments.location();
+                        let mask = ImageDistribution::new(&self.image(&mut arguments)?)
+                            .ok_or_else(|| SceneError::invalid(location, "the aperture mask is black"))?;
+                        ApertureShape::Masked(Arc::new(mask))
+                    },
+                    (other, location) => return Err(unknown_kind(location, "aperture shape", other, &["circular", "polygonal", "masked"])),
+                },
+                "vignetting" => vignetting = arguments.number("a vignetting strength")?,
+                "chromatic_aberration" => chromatic_aberration = (
+                    arguments.number("a lateral aberration")?,
+                    arguments.number("an axial aberration")?,
+                ),
+                _ => return Err(unknown(child, &["focal_length", "focal_distance", "aperture", "aperture_shape", "vignetting", "chromatic_aberration"])),
+            }
+            arguments.done()?;
+        }
+        let (lateral, axial) = chromatic_aberration;
+        Ok(Lens::new(aperture, focal_length, focal_distance.unwrap_or(focal_length))
+            .with_aperture_shape(aperture_shape)
+            .with_vignetting(vignetting)
+            .with_chromatic_aberration(lateral, axial))
+    }
+
+    fn world(&self, node: &Node) -> Result<SceneWorld> {
+        let mut depth = 8;
+        let mut environment = Color::BLACK;
+        let mut lights = Vec::new();
+        let mut integrator = Integrator::Path;
+        let transformation = match node.children.iter().find(|child| child.name == "transform") {
+            Some(child) => Some(self.transformation(child)?),
+            None => None,
+        };
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "depth" => depth = arguments.whole("a depth", 1, u8::MAX as u64)? as u8,
+                "environment" => environment = arguments.color()?,
+                "light" => lights.push(self.light(&mut arguments, transformation.as_ref())?),
+                "integrator" => integrator = match arguments.symbol("an integrator")? {
+                    ("path", _) => Integrator::Path,
+                    ("bidirectional", _) => Integrator::Bidirectional,
+                    ("photon_mapping", _) => {
+                        let photons = arguments.optional_whole("a number of photons per pass", 1, u32::MAX as u64)?.unwrap_or(100_000);
+                        let radius = arguments.optional_positive("a radius", 0.1)?;
+                        Integrator::PhotonMapping { photons: photons as usize, radius }
+                    },
+                    ("ambient_occlusion", _) => Integrator::AmbientOcclusion { radius: arguments.optional_positive("a radius", 1.0)? },
+                    ("normals", _) => Integrator::Inspection(Aspect::Normals),
+                    ("surface_coordinates", _) => Integrator::Inspection(Aspect::SurfaceCoordinates),
+                    ("distances", _) => Integrator::Inspection(Aspect::Distances(arguments.optional_positive("a distance", 10.0)?)),
+                    ("things_tested", _) => {
+                        let max = arguments.optional_whole("a number of things", 1, u32::MAX as u64)?.unwrap_or(16);
+                        Integrator::Inspection(Aspect::ThingsTested(max as u32))
+                    },
+                    ("material_ids", _) => Integrator::Inspection(Aspect::MaterialIds),
+                    (other, location) => return Err(unknown_kind(location, "integrator", other, &[
+                        "path", "bidirectional", "photon_mapping", "ambient_occlusion", "normals", "surface_coordinates", "distances", "things_tested", "material_ids",
+                    ])),
+                },
+                _ => continue,
+            }
+            arguments.done()?;
+        }
+        let things: Arc<dyn Thing> = Arc::from(self.group(node, &["depth", "environment", "light", "integrator"])?);
+        let environment = Arc::new(environment) as Arc<dyn World>;
+        let lights = Lights::new(lights);
+        Ok(match integrator {
+            Integrator::Path => SceneWorld::PathTraced(Building(things)
+                .path_traced()
+                .with_environment(environment)
+                .with_depth(depth)
+                .with_lights(lights)
+                .done()),
+            Integrator::Bidirectional => SceneWorld::Bidirectional(Building(things)
+                .bidirectional_path_traced()
+                .with_environment(environment)
+                .with_depth(depth)
+                .with_lights(lights)
+                .done()),
+            Integrator::PhotonMapping { photons, radius } => SceneWorld::PhotonMapped(Building(things)
+                .photon_mapped()
+                .with_environment(environment)
+                .with_depth(depth)
+                .with_lights(lights)
+                .with_photons(photons)
+                .with_radius(radius)
+                .done()),
+            Integrator::AmbientOcclusion { radius } => SceneWorld::AmbientOccluded(Building(things)
+                .ambient_occluded()
+                .with_radius(radius)
+                .done()),
+            Integrator::Inspection(aspect) => SceneWorld::Inspected(Building(things)
+                .inspected()
+                .with_aspect(aspect)
+                .done()),
+        })
+    }
+
+    /// Builds a light, placing it using the given transformation, if any, like the things of the
+    /// world are placed.
+    fn light(&self, arguments: &mut Arguments, transformation: Option<&Affine>) -> Result<Arc<dyn Light>> {
+        let (kind, location) = arguments.symbol("a light")?;
+        let identity = Affine(Linear::omni_scaling(1.0), Translation::ZERO);
+        let Affine(Linear(ref matrix, ref anti_matrix, _), Translation(ref displacement)) = *transformation.unwrap_or(&identity);
+        let point = |p: Vec3D| &(matrix * &p) + displacement;
+        let vector = |v: Vec3D| matrix * &v;
+        let length = |l: f64| l * matrix.det().abs().cbrt();
+        Ok(match kind {
+            "point" => Arc::new(PointLight::new(point(arguments.vector("a position")?), arguments.color()?)),
+            "spot" => {
+                let position = point(arguments.vector("a position")?);
+                let direction = vector(arguments.vector("a direction")?);
+                let intensity = arguments.color()?;
+                let inner_angle = arguments.number("an inner cone angle")?.to_radians();
+                let outer_angle = arguments.number("an outer cone angle")?.to_radians();
+                let mut intensities = Vec::new();
+                loop {
+                    match arguments.optional_number(f64::NAN)? {
+                        intensity if intensity.is_nan() => break,
+                        intensity => intensities.push(intensity),
+                    }
+                }
+                let light = SpotLight::new(position, direction, intensity, inner_angle, outer_angle);
+                if intensities.is_empty() {
+                    Arc::new(light)
+                } else {
+                    Arc::new(light.with_profile(Profile { max_angle: outer_angle, intensities }))
+                }
+            },
+            "directional" => {
+                let direction = vector(arguments.vector("a direction")?);
+                let irradiance = arguments.color()?;
+                let angular_radius = arguments.optional_number(0.0)?.to_radians();
+                Arc::new(DirectionalLight::new(direction, irradiance).with_angular_radius(angular_radius))
+            },
+            "rectangle" => {
+                let center = point(arguments.vector("a center")?);
+                let half_edge_1 = vector(arguments.vector("a half edge")?);
+                let half_edge_2 = vector(arguments.vector("a half edge")?);
+                Arc::new(RectangleLight::new(center, half_edge_1, half_edge_2, arguments.color()?))
+            },
+            "disk" => {
+                let center = point(arguments.vector("a center")?);
+                let normal = anti_matrix * &arguments.vector("a normal")?;
+                let radius = length(arguments.number("a radius")?);
+                Arc::new(DiskLight::new(center, normal, radius, arguments.color()?))
+            },
+            "sphere" => {
+                let center = point(arguments.vector("a center")?);
+                let radius = length(arguments.number("a radius")?);
+                Arc::new(SphereLight::new(center, radius, arguments.color()?))
+            },
+            _ => return Err(unknown_kind(location, "light", kind, &["point", "spot", "directional", "rectangle", "disk", "sphere"])),
+        })
+    }
+
+    /// Builds the things nested in the given node, skipping the node children that have the given
+    /// names, as they are expected to be handled by the caller.
+    fn group(&self, node: &Node, handled: &[&str]) -> Result<Box<dyn Thing>> {
+        Arguments::of(node).done()?;
+        let mut things = Vec::new();
+        let mut transformation = None;
+        let mut id = None;
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "thing" => {
+                    things.push(self.thing(child)?);
+                    continue
+                },
+                "group" => {
+                    things.push(self.group(child, &[])?);
+                    continue
+                },
+                "transform" => once(&mut transformation, child, |n| self.transformation(n))?,
+                "id" => id = Some(arguments.whole("an object id", 0, u32::MAX as u64)? as u32),
+                name if handled.contains(&name) => continue,
+                _ => return Err(unknown(child, &[handled, &["thing", "group", "transform", "id"]].concat())),
+            }
+            arguments.done()?;
+        }
+        Ok(placed(Box::new(Things(things)), transformation, id))
+    }
+
+    fn thing(&self, node: &Node) -> Result<Box<dyn Thing>> {
+        let mut arguments = Arguments::of(node);
+        let (kind, location) = arguments.symbol("a geometry")?;
+        let geometry: Arc<dyn Geometry> = match kind {
+            "sphere" => Arc::new(Sphere),
+            _ => return Err(unknown_kind(location, "geometry", kind, &["sphere"])),
+        };
+        arguments.done()?;
+        let mut texture = None;
+        let mut outer_texture = None;
+        let mut inner_texture = None;
+        let mut transformation = None;
+        let mut id = None;
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "texture" => once(&mut texture, child, |_| self.texture(&mut arguments))?,
+                "outer_texture" => once(&mut outer_texture, child, |_| self.texture(&mut arguments))?,
+                "inner_texture" => once(&mut inner_texture, child, |_| self.texture(&mut arguments))?,
+                "transform" => once(&mut transformation, child, |n| self.transformation(n))?,
+                "id" => id = Some(arguments.whole("an object id", 0, u32::MAX as u64)? as u32),
+                _ => return Err(unknown(child, &["texture", "outer_texture", "inner_texture", "transform", "id"])),
+            }
+            arguments.done()?;
+        }
+        let (outer_texture, inner_texture): (Arc<dyn Texture>, Arc<dyn Texture>) = match (texture, outer_texture, inner_texture) {
+            (Some(texture), None, None) => (texture, Arc::new(Same)),
+            (None, Some(outer), None) => (outer, Arc::new(Black)),
+            (None, None, Some(inner)) => (Arc::new(Black), inner),
+            (None, Some(outer), Some(inner)) => (outer, inner),
+            (None, None, None) => return Err(SceneError::invalid(node.location, "the thing has no `texture`, `outer_texture`, or `inner_texture`")),
+            _ => return Err(SceneError::invalid(node.location, "the thing cannot have a `texture` together with an `outer_texture` or an `inner_texture`")),
+        };
+        let thing = Box::new(AtomicThing { geometry, outer_texture, inner_texture });
+        Ok(placed(thing, transformation, id))
+    }
+
+    fn texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        match arguments.peek_symbol() {
+            Some("image") => self.image_texture(arguments),
+            Some("procedural") => self.procedural_texture(arguments),
+            Some("bumped") => self.bumped_texture(arguments),
+            Some("normal_mapped") => self.normal_mapped_texture(arguments),
+            Some("cutout") => self.cutout_texture(arguments),
+            Some("alpha_mapped") => self.alpha_mapped_texture(arguments),
+            Some("principled") => self.principled_texture(arguments),
+            _ => Ok(Arc::new(Constant(self.material(arguments)?))),
+        }
+    }
+
+    fn image_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let (kind, location) = arguments.symbol("a material")?;
+        let material: Box<dyn Fn(Color) -> Arc<dyn Material> + Send + Sync> = match kind {
+            "diffusive" => Box::new(|color| Arc::new(Diffusive(color))),
+            "reflective" => Box::new(|color| Arc::new(Reflective(color))),
+            "emissive" => Box::new(|color| Arc::new(Emissive(color))),
+            _ => return Err(unknown_kind(location, "image texture material", kind, &["diffusive", "reflective", "emissive"])),
+        };
+        let mut texture = ImageTexture::new(ImageMap::new(self.image(arguments)?), material);
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "filtering" => texture.map.filtering = match arguments.symbol("a filtering")? {
+                    ("nearest", _) => Filtering::Nearest,
+                    ("bilinear", _) => Filtering::Bilinear,
+                    ("bicubic", _) => Filtering::Bicubic,
+                    (other, location) => return Err(unknown_kind(location, "filtering", other, &["nearest", "bilinear", "bicubic"])),
+                },
+                "wrapping" => texture.map.wrapping = match arguments.symbol("a wrapping")? {
+                    ("repeat", _) => Wrapping::Repeat,
+                    ("clamp", _) => Wrapping::Clamp,
+                    ("mirror", _) => Wrapping::Mirror,
+                    (other, location) => return Err(unknown_kind(location, "wrapping", other, &["repeat", "clamp", "mirror"])),
+                },
+                "uv" => {
+                    texture.uv_scale = (arguments.number("a u scale")?, arguments.number("a v scale")?);
+                    texture.uv_offset = (arguments.number("a u offset")?, arguments.number("a v offset")?);
+                },
+                _ => return Err(unknown(child, &["filtering", "wrapping", "uv"])),
+            }
+            arguments.done()?;
+        }
+        Ok(Arc::new(texture))
+    }
+
+    fn bumped_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let strength = arguments.number("a strength")?;
+        let mut bump_map = BumpMap::new(self.noise(arguments)?, strength);
+        let mut texture = None;
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "texture" => once(&mut texture, child, |_| self.texture(&mut arguments))?,
+                "detail" => bump_map.detail = arguments.number("a detail factor")?,
+                _ => return Err(unknown(child, &["texture", "detail"])),
+            }
+            arguments.done()?;
+        }
+        let texture = texture.ok_or_else(|| SceneError::invalid(arguments.node.location, "the bumped texture has no `texture`"))?;
+        Ok(Arc::new(Perturbed::new(texture, bump_map)))
+    }
+
+    fn normal_mapped_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let mut normal_map = NormalMap::new(ImageMap::new(self.data_image(arguments)?));
+        let mut texture = None;
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "texture" => once(&mut texture, child, |_| self.texture(&mut arguments))?,
+                "strength" => normal_map.strength = arguments.number("a strength")?,
+                "uv" => {
+                    normal_map.uv_scale = (arguments.number("a u scale")?, arguments.number("a v scale")?);
+                    normal_map.uv_offset = (arguments.number("a u offset")?, arguments.number("a v offset")?);
+                },
+                _ => return Err(unknown(child, &["texture", "strength", "uv"])),
+            }
+            arguments.done()?;
+        }
+        let texture = texture.ok_or_else(|| SceneError::invalid(arguments.node.location, "the normal mapped texture has no `texture`"))?;
+        Ok(Arc::new(Perturbed::new(texture, normal_map)))
+    }
+
+    fn cutout_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let threshold = arguments.number("a threshold")?;
+        let mut mask = NoiseMask::new(self.noise(arguments)?, threshold);
+        let mut texture = None;
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "texture" => once(&mut texture, child, |_| self.texture(&mut arguments))?,
+                "detail" => mask.detail = arguments.number("a detail factor")?,
+                "smoothness" => mask.smoothness = arguments.number("a smoothness")?,
+                _ => return Err(unknown(child, &["texture", "detail", "smoothness"])),
+            }
+            arguments.done()?;
+        }
+        let texture = texture.ok_or_else(|| SceneError::invalid(arguments.node.location, "the cutout texture has no `texture`"))?;
+        Ok(Arc::new(Cutout::new(texture, mask)))
+    }
+
+    fn alpha_mapped_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let mut mask = AlphaMap::new(ImageMap::new(self.load_image(arguments, Image::load_alpha)?));
+        let mut texture = None;
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "texture" => once(&mut texture, child, |_| self.texture(&mut arguments))?,
+                "uv" => {
+                    mask.uv_scale = (arguments.number("a u scale")?, arguments.number("a v scale")?);
+                    mask.uv_offset = (arguments.number("a u offset")?, arguments.number("a v offset")?);
+                },
+                _ => return Err(unknown(child, &["texture", "uv"])),
+            }
+            arguments.done()?;
+        }
+        let texture = texture.ok_or_else(|| SceneError::invalid(arguments.node.location, "the alpha mapped texture has no `texture`"))?;
+        Ok(Arc::new(Cutout::new(texture, mask)))
+    }
+
+    fn principled_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let mut texture = PrincipledTexture::new(Color::new(0.8, 0.8, 0.8));
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "base_color" => texture.base_color = self.color_parameter(&mut arguments)?,
+                "metallic" => texture.metallic = self.scalar_parameter(&mut arguments)?,
+                "roughness" => texture.roughness = self.scalar_parameter(&mut arguments)?,
+                "specular" => texture.specular = self.scalar_parameter(&mut arguments)?,
+                "transmission" => texture.transmission = self.scalar_parameter(&mut arguments)?,
+                "ior" => texture.ior = self.scalar_parameter(&mut arguments)?,
+                "clearcoat" => texture.clearcoat = self.scalar_parameter(&mut arguments)?,
+                "clearcoat_roughness" => texture.clearcoat_roughness = self.scalar_parameter(&mut arguments)?,
+                "sheen" => texture.sheen = self.scalar_parameter(&mut arguments)?,
+                "emission" => texture.emission = self.color_parameter(&mut arguments)?,
+                _ => return Err(unknown(child, &[
+                    "base_color", "metallic", "roughness", "specular", "transmission", "ior", "clearcoat", "clearcoat_roughness",
+                    "sheen", "emission",
+                ])),
+            }
+            arguments.done()?;
+        }
+        Ok(Arc::new(texture))
+    }
+
+    fn color_parameter(&self, arguments: &mut Arguments) -> Result<Arc<dyn Parameter<Color>>> {
+        match arguments.peek_symbol() {
+            Some("image") => {
+                arguments.symbol("a parameter")?;
+                Ok(Arc::new(self.image_parameter(arguments, Image::load)?))
+            },
+            _ => Ok(Arc::new(arguments.color()?)),
+        }
+    }
+
+    fn scalar_parameter(&self, arguments: &mut Arguments) -> Result<Arc<dyn Parameter<f64>>> {
+        match arguments.peek_symbol() {
+            Some("image") => {
+                arguments.symbol("a parameter")?;
+                Ok(Arc::new(self.image_parameter(arguments, Image::load_data)?))
+            },
+            Some("noise") => {
+                arguments.symbol("a parameter")?;
+                let (low, high) = (arguments.number("a low value")?, arguments.number("a high value")?);
+                let detail = arguments.number("a detail factor")?;
+                Ok(Arc::new(NoiseParameter::new(self.noise(arguments)?, low, high).with_detail(detail)))
+            },
+            _ => Ok(Arc::new(arguments.number("a value")?)),
+        }
+    }
+
+    fn image_parameter(&self, arguments: &mut Arguments, load: fn(&str) -> ImageResult<Image>) -> Result<ImageParameter> {
+        let parameter = ImageParameter::new(ImageMap::new(self.load_image(arguments, load)?));
+        Ok(match arguments.optional_number(f64::NAN)? {
+            scale_u if scale_u.is_nan() => parameter,
+            scale_u => parameter.with_uv_transform(scale_u, arguments.number("a v scale")?, arguments.number("a u offset")?, arguments.number("a v offset")?),
+        })
+    }
+
+    fn procedural_texture(&self, arguments: &mut Arguments) -> Result<Arc<dyn Texture>> {
+        arguments.symbol("a texture")?;
+        let pattern = self.pattern(arguments)?;
+        let mut first = None;
+        let mut second = None;
+        let mut noise = None;
+        let mut texture = Procedural::new(pattern, Arc::new(Simple) as Arc<dyn Noise>, Arc::new(Absorptive) as Arc<dyn Material>, Arc::new(Absorptive) as Arc<dyn Material>);
+        for child in &arguments.node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "first" => once(&mut first, child, |_| self.material(&mut arguments))?,
+                "second" => once(&mut second, child, |_| self.material(&mut arguments))?,
+                "noise" => once(&mut noise, child, |_| self.noise(&mut arguments))?,
+                "domain" => texture.domain = match arguments.symbol("a domain")? {
+                    ("surface", _) => Domain::Surface,
+                    ("object", _) => Domain::Object,
+                    ("world", _) => Domain::World,
+                    (other, location) => return Err(unknown_kind(location, "domain", other, &["surface", "object", "world"])),
+                },
+                "detail" => texture.detail = arguments.number("a detail factor")?,
+                "turbulence" => texture.turbulence = arguments.number("a turbulence factor")?,
+                "cutoff" => texture.cutoff = Some(arguments.number("a cutoff")?),
+                "modulated" => texture.modulated = true,
+                _ => return Err(unknown(child, &["first", "second", "noise", "domain", "detail", "turbulence", "cutoff", "modulated"])),
+            }
+            arguments.done()?;
+        }
+        let missing = |name: &str| SceneError::invalid(arguments.node.location, format!("the procedural texture has no `{}` material", name));
+        texture.first = first.ok_or_else(|| missing("first"))?;
+        texture.second = second.ok_or_else(|| missing("second"))?;
+        texture.noise = noise.unwrap_or(texture.noise);
+        Ok(Arc::new(texture))
+    }
+
+    fn pattern(&self, arguments: &mut Arguments) -> Result<Arc<dyn Pattern>> {
+        let (kind, location) = arguments.symbol("a pattern")?;
+        Ok(match kind {
+            "checker" => Arc::new(Checker {
+                scale: arguments.number("a scale")?,
+                offset: Vec3D::new(arguments.optional_number(0.0)?, arguments.optional_number(0.0)?, arguments.optional_number(0.0)?),
+            }),
+            "stripes" => Arc::new(Stripes { axis: arguments.vector("an axis")?, frequency: arguments.number("a frequency")? }),
+            "rings" => Arc::new(Rings { axis: arguments.vector("an axis")?, frequency: arguments.number("a frequency")? }),
+            "marble" => Arc::new(Marble { axis: arguments.vector("an axis")?, frequency: arguments.number("a frequency")? }),
+            "gradient" => Arc::new(Gradient {
+                axis: arguments.vector("an axis")?,
+                start: arguments.number("a start")?,
+                end: arguments.number("an end")?,
+            }),
+            "threshold" => Arc::new(Threshold { threshold: arguments.number("a threshold")?, smoothness: arguments.optional_number(0.0)? }),
+            "bands" => Arc::new(Bands),
+            "ridges" => Arc::new(Ridges),
+            _ => return Err(unknown_kind(location, "pattern", kind, &["checker", "stripes", "rings", "marble", "gradient", "threshold", "bands", "ridges"])),
+        })
+    }
+
+    fn noise(&self, arguments: &mut Arguments) -> Result<Arc<dyn Noise>> {
+        let (kind, location) = arguments.symbol("a noise")?;
+        Ok(match kind {
+            "simple" => Arc::new(Simple),
+            "perlin" => {
+                let seed = arguments.optional_whole("a seed", 0, u32::MAX as u64)?.unwrap_or(0);
+                match arguments.optional_whole("a period", 1, u32::MAX as u64)? {
+                    Some(period) => Arc::new(Perlin::tileable(seed, period as u32)),
+                    None => Arc::new(Perlin::new(seed)),
+                }
+            },
+            "simplex" => Arc::new(Simplex::new(arguments.optional_whole("a seed", 0, u32::MAX as u64)?.unwrap_or(0))),
+            "worley" => {
+                // The feature and the metric are optional, so they get consumed only if recognized.
+                let feature = match arguments.peek_symbol() {
+                    Some("f1") => Some(Feature::F1),
+                    Some("f2") => Some(Feature::F2),
+                    Some("difference") => Some(Feature::Difference),
+                    _ => None,
+                };
+                if feature.is_some() {
+                    arguments.symbol("a feature")?;
+                }
+                let metric = match arguments.peek_symbol() {
+                    Some("euclidean") => Some(Metric::Euclidean),
+                    Some("manhattan") => Some(Metric::Manhattan),
+                    Some("chebyshev") => Some(Metric::Chebyshev),
+                    _ => None,
+                };
+                if metric.is_some() {
+                    arguments.symbol("a metric")?;
+                }
+                let seed = arguments.optional_whole("a seed", 0, u32::MAX as u64)?.unwrap_or(0);
+                Arc::new(Worley::new(seed).with_feature(feature.unwrap_or(Feature::F1)).with_metric(metric.unwrap_or(Metric::Euclidean)))
+            },
+            "warped" => {
+                let strength = arguments.number("a strength")?;
+                Arc::new(Warped::new(self.noise(arguments)?, self.noise(arguments)?, strength))
+            },
+            "turbulence" => {
+                let octaves = arguments.whole("a number of octaves", 1, u8::MAX as u64)? as u8;
+                Arc::new(Turbulence::new(self.noise(arguments)?, octaves))
+            },
+            "ridged" => {
+                let octaves = arguments.whole("a number of octaves", 1, u8::MAX as u64)? as u8;
+                Arc::new(Ridged::new(self.noise(arguments)?, octaves))
+            },
+            "remapped" | "clamped" => {
+                let from = (arguments.number("a range")?, arguments.number("a range")?);
+                let to = (arguments.number("a range")?, arguments.number("a range")?);
+                let remapped = Remapped::new(self.noise(arguments)?, from, to);
+                Arc::new(if kind == "clamped" { remapped.clamped() } else { remapped })
+            },
+            "sum" | "difference" | "product" | "minimum" | "maximum" | "average" => {
+                let operation = match kind {
+                    "sum" => Operation::Sum,
+                    "difference" => Operation::Difference,
+                    "product" => Operation::Product,
+                    "minimum" => Operation::Minimum,
+                    "maximum" => Operation::Maximum,
+                    _ => Operation::Average,
+                };
+                Arc::new(Combined::new(self.noise(arguments)?, self.noise(arguments)?, operation))
+            },
+            "fractal" => {
+                let depth = arguments.whole("a depth", 0, u8::MAX as u64)? as u8;
+                let base = self.noise(arguments)?;
+                let s = SQRT_2;
+                let transformation = &Matrix::with_z_alignment(&Vec3D::new(3.0, 2.0, 1.0)) * &Matrix::diagonal(s, s, s);
+                Arc::new(Fractal::new(base, transformation, Vec3D::new(0.4, 0.5, 0.6), 1.0 / s, depth))
+            },
+            _ => return Err(unknown_kind(location, "noise", kind, &[
+                "simple", "perlin", "simplex", "worley", "fractal", "warped", "turbulence", "ridged", "remapped", "clamped",
+                "sum", "difference", "product", "minimum", "maximum", "average",
+            ])),
+        })
+    }
+
+    fn image(&self, arguments: &mut Arguments) -> Result<Image> {
+        self.load_image(arguments, Image::load)
+    }
+
+    fn data_image(&self, arguments: &mut Arguments) -> Result<Image> {
+        self.load_image(arguments, Image::load_data)
+    }
+
+    fn load_image(&self, arguments: &mut Arguments, load: fn(&str) -> ImageResult<Image>) -> Result<Image> {
+        let location = arguments.location();
+        let file = arguments.text("an image file")?;
+        let path = self.directory.join(&file);
+        load(&path.to_string_lossy())
+            .map_err(|error| SceneError::invalid(location, format!("cannot load the image \"{}\": {}", file, error)))
+    }
+
+    fn material(&self, arguments: &mut Arguments) -> Result<Arc<dyn Material>> {
+        let (kind, location) = arguments.symbol("a material")?;
+        Ok(match kind {
+            "absorptive" => Arc::new(Absorptive),
+            "diffusive" => {
+                let diffusive = Diffusive(arguments.color()?);
+                match arguments.optional_number(f64::NAN)? {
+                    roughness if roughness.is_nan() => Arc::new(diffusive),
+                    roughness => {
+                        let model = match arguments.peek_symbol() {
+                            Some("oren_nayar") => Some(DiffuseModel::OrenNayar),
+                            Some("qualitative") => Some(DiffuseModel::QualitativeOrenNayar),
+                            Some("retro_reflective") => Some(DiffuseModel::RetroReflective),
+                            _ => None,
+                        };
+                        if model.is_some() {
+                            arguments.symbol("a diffuse model")?;
+                        }
+                        Arc::new(diffusive.with_roughness(roughness).with_model(model.unwrap_or(DiffuseModel::OrenNayar)))
+                    },
+                }
+            },
+            "reflective" => Arc::new(Reflective(arguments.color()?)),
+            "refractive" => {
+                let color = arguments.color()?;
+                let index = match arguments.peek_symbol() {
+                    Some("cauchy") => {
+                        arguments.symbol("a dispersion formula")?;
+                        RefractionIndex::cauchy(arguments.number("a Cauchy coefficient")?, arguments.number("a Cauchy coefficient")?)
+                    },
+                    Some("sellmeier") => {
+                        arguments.symbol("a dispersion formula")?;
+                        let mut coefficients = [0.0; 6];
+                        for coefficient in &mut coefficients {
+                            *coefficient = arguments.number("a Sellmeier coefficient")?;
+                        }
+                        let [b1, b2, b3, c1, c2, c3] = coefficients;
+                        RefractionIndex::sellmeier([b1, b2, b3], [c1, c2, c3])
+                    },
+                    _ => RefractionIndex::of(arguments.number("a refraction index")?),
+                };
+                Arc::new(Refractive(color, index))
+            },
+            "emissive" => Arc::new(Emissive(arguments.color()?)),
+            "subsurface" => {
+                let albedo = arguments.color()?;
+                let mean_free_path = arguments.color()?;
+                Arc::new(Subsurface::new(albedo, mean_free_path, arguments.number("a refraction index")?))
+            },
+            "coated" => {
+                let index = arguments.number("a refraction index")?;
+                let roughness = arguments.optional_number(0.0)?;
+                let absorption = match arguments.optional_number(f64::NAN)? {
+                    red if red.is_nan() => Color::BLACK,
+                    red => Color::new(red, arguments.number("a green component")?, arguments.number("a blue component")?),
+                };
+                Arc::new(Coated::new(self.material(arguments)?, index).with_roughness(roughness).with_absorption(absorption))
+            },
+            "thin_film" => {
+                let index = arguments.number("a film refraction index")?;
+                let substrate_index = arguments.number("a substrate refraction index")?;
+                let thickness = arguments.number("a film thickness")?;
+                let max_thickness = arguments.optional_number(f64::NAN)?;
+                let variation = if max_thickness.is_nan() {
+                    None
+                } else {
+                    Some((max_thickness, arguments.number("a detail factor")?, self.noise(arguments)?))
+                };
+                let film = ThinFilm::new(self.material(arguments)?, index, thickness).with_substrate_index(substrate_index);
+                match variation {
+                    Some((max_thickness, detail, noise)) => Arc::new(film.with_thickness_variation(noise, max_thickness, detail)),
+                    None => Arc::new(film),
+                }
+            },
+            "composite" => {
+                let mut materials: Vec<(Box<dyn Material>, f64)> = Vec::new();
+                for child in &arguments.node.children {
+                    if child.name != "choice" {
+                        return Err(unknown(child, &["choice"]))
+                    }
+                    let mut choice_arguments = Arguments::of(child);
+                    let weight = choice_arguments.number("a weight")?;
+                    materials.push((Box::new(self.material(&mut choice_arguments)?), weight));
+                    choice_arguments.done()?;
+                }
+                if materials.is_empty() {
+                    return Err(SceneError::invalid(location, "the composite material has no `choice`"))
+                }
+                Arc::new(Composite::new(materials))
+            },
+            name => match self.materials.get(name) {
+                Some(material) => material.clone(),
+                None => return Err(unknown_kind(location, "material", name, &["absorptive", "diffusive", "reflective", "refractive", "emissive", "subsurface", "coated", "thin_film", "composite", "<a defined material name>"])),
+            },
+        })
+    }
+
+    fn transformation(&self, node: &Node) -> Result<Affine> {
+        Arguments::of(node).done()?;
+        let mut transformation = Affine(Linear::omni_scaling(1.0), Translation::ZERO);
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            transformation = match child.name.as_str() {
+                "scale" => {
+                    let x = arguments.number("a scale factor")?;
+                    match arguments.optional_number(f64::NAN)? {
+                        y if y.is_nan() => transformation.then_omni_scaling(x),
+                        y => transformation.then_scaling(x, y, arguments.number("a z scale factor")?),
+                    }
+                },
+                "rotate" => {
+                    let axis = arguments.vector("a rotation axis")?;
+                    transformation.then_rotation(&axis, arguments.number("an angle")?.to_radians())
+                },
+                "translate" => transformation.then_translation(arguments.vector("a displacement")?),
+                "matrix" => {
+                    let x = arguments.vector("a matrix column")?;
+                    let y = arguments.vector("a matrix column")?;
+                    let z = arguments.vector("a matrix column")?;
+                    transformation.then_linear(Matrix::new(&x, &y, &z))
+                },
+                _ => return Err(unknown(child, &["scale", "rotate", "translate", "matrix"])),
+            };
+            arguments.done()?;
+        }
+        Ok(transformation)
+    }
+
+    fn render(&self, node: &Node) -> Result<RenderSettings> {
+        Arguments::of(node).done()?;
+        let mut settings = RenderSettings::default();
+        for child in &node.children {
+            let mut arguments = Arguments::of(child);
+            match child.name.as_str() {
+                "stack" => settings.stack_size = arguments.whole("a stack size", 1, u16::MAX as u64)? as u16,
+                "bloom" => settings.bloom_depth = arguments.whole("a bloom depth", 0, u8::MAX as u64)? as u8,
+                "output" => settings.output = Some(arguments.text("an output file")?),
+                "metropolis" => {
+                    let defaults = Metropolis::default();
+                    let bootstrap_samples = arguments.optional_whole("a number of bootstrap paths", 1, u32::MAX as u64)?;
+                    let chains = arguments.optional_whole("a number of chains", 1, u32::MAX as u64)?;
+                    settings.metropolis = Some(Metropolis {
+                        bootstrap_samples: bootstrap_samples.map_or(defaults.bootstrap_samples, |n| n as usize),
+                        chains: chains.map_or(defaults.chains, |n| n as usize),
+                        ..defaults
+                    });
+                },
+                _ => return Err(unknown(child, &["stack", "bloom", "output", "metropolis"])),
+            }
+            arguments.done()?;
+        }
+        Ok(settings)
+    }
+
+}
+
+impl<'a> Arguments<'a> {
+
+    fn of(node: &'a Node) -> Self {
+        Self { node, index: 0 }
+    }
+
+    fn next(&mut self, expectation: &str) -> Result<&'a Argument> {
+        let argument = self.node.arguments.get(self.index).ok_or_else(|| {
+            let location = self.node.arguments.last().map_or(self.node.location, |a| a.location);
+            SceneError::invalid(location, format!("`{}` expects {} here", self.node.name, expectation))
+        })?;
+        self.index += 1;
+        Ok(argument)
+    }
+
+    fn number(&mut self, expectation: &str) -> Result<f64> {
+        let argument = self.next(expectation)?;
+        match argument.value {
+            Value::Number(n) => Ok(n),
+            ref other => Err(mismatch(argument.location, expectation, other)),
+        }
+    }
+
+    fn optional_number(&mut self, default: f64) -> Result<f64> {
+        match self.node.arguments.get(self.index) {
+            Some(Argument { value: Value::Number(n), .. }) => {
+                self.index += 1;
+                Ok(*n)
+            },
+            _ => Ok(default),
+        }
+    }
+
+    fn optional_positive(&mut self, expectation: &str, default: f64) -> Result<f64> {
+        let location = self.location();
+        let n = self.optional_number(default)?;
+        if n > 0.0 {
+            Ok(n)
+        } else {
+            Err(SceneError::invalid(location, format!("expected {}, which is a positive number, but found `{}`", expectation, n)))
+        }
+    }
+
+    fn whole(&mut self, expectation: &str, min: u64, max: u64) -> Result<u64> {
+        let location = self.location();
+        let n = self.number(expectation)?;
+        if n.fract() == 0.0 && n >= min as f64 && n <= max as f64 {
+            Ok(n as u64)
+        } else {
+            Err(SceneError::invalid(location, format!("expected {}, which is a whole number between {} and {}, but found `{}`", expectation, min, max, n)))
+        }
+    }
+
+    fn optional_whole(&mut self, expectation: &str, min: u64, max: u64) -> Result<Option<u64>> {
+        match self.node.arguments.get(self.index) {
+            Some(Argument { value: Value::Number(_), .. }) => self.whole(expectation, min, max).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn vector(&mut self, expectation: &str) -> Result<Vec3D> {
+        Ok(Vec3D::new(self.number(expectation)?, self.number(expectation)?, self.number(expectation)?))
+    }
+
+    fn color(&mut self) -> Result<Color> {
+        Ok(Color::new(self.number("a red component")?, self.number("a green component")?, self.number("a blue component")?))
+    }
+
+    fn text(&mut self, expectation: &str) -> Result<String> {
+        let argument = self.next(expectation)?;
+        match argument.value {
+            Value::Text(ref t) => Ok(t.clone()),
+            ref other => Err(mismatch(argument.location, expectation, other)),
+        }
+    }
+
+    fn symbol(&mut self, expectation: &str) -> Result<(&'a str, Location)> {
+        let argument = self.next(expectation)?;
+        match argument.value {
+            Value::Symbol(ref s) => Ok((s.as_str(), argument.location)),
+            ref other => Err(mismatch(argument.location, expectation, other)),
+        }
+    }
+
+    fn peek_symbol(&self) -> Option<&'a str> {
+        match self.node.arguments.get(self.index) {
+            Some(Argument { value: Value::Symbol(s), .. }) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    fn location(&self) -> Location {
+        self.node.arguments.get(self.index).map_or(self.node.location, |a| a.location)
+    }
+
+    fn optional_symbol(&mut self) -> Result<Option<(&'a str, Location)>> {
+        match self.node.arguments.get(self.index) {
+            Some(Argument { value: Value::Symbol(_), .. }) => self.symbol("").map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn done(self) -> Result<()> {
+        match self.node.arguments.get(self.index) {
+            Some(argument) => Err(SceneError::invalid(argument.location, format!("unexpected {} in `{}`", argument.value.describe(), self.node.name))),
+            None => Ok(()),
+        }
+    }
+
+}
+
+fn once<T, F: FnOnce(&Node) -> Result<T>>(slot: &mut Option<T>, node: &Node, loader: F) -> Result<()> {
+    if slot.is_some() {
+        return Err(SceneError::invalid(node.location, format!("`{}` is specified more than once", node.name)))
+    }
+    *slot = Some(loader(node)?);
+    Ok(())
+}
+
+fn placed(thing: Box<dyn Thing>, transformation: Option<Affine>, id: Option<u32>) -> Box<dyn Thing> {
+    let thing: Box<dyn Thing> = match transformation {
+        Some(transformation) => Building(Arc::<dyn Thing>::from(thing)).transformed(transformation).boxed(),
+        None => thing,
+    };
+    match id {
+        Some(id) => Building(Arc::<dyn Thing>::from(thing)).tagged(id).boxed(),
+        None => thing,
+    }
+}
+
+fn unknown(node: &Node, expected: &[&str]) -> SceneError {
+    SceneError::invalid(node.location, format!("unknown setting `{}`, expected one of: {}", node.name, expected.join(", ")))
+}
+
+fn unknown_kind(location: Location, kind: &str, name: &str, expected: &[&str]) -> SceneError {
+    SceneError::invalid(location, format!("unknown {} `{}`, expected one of: {}", kind, name, expected.join(", ")))
+}
+
+fn mismatch(location: Location, expectation: &str, found: &Value) -> SceneError {
+    SceneError::invalid(location, format!("expected {}, but found {}", expectation, found.describe()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scenes::Scene;
+
+    #[test]
+    fn loads_example_scene() {
+        let scene = Scene::parse(include_str!("../../examples/scenes/example1.scene")).unwrap();
+
+        assert_eq!(scene.camera.sensor.width, 960);
+        assert_eq!(scene.camera.samples_per_pixel, 64);
+        assert_eq!(scene.world.depth(), 16);
+        assert_eq!(scene.render.stack_size, 16);
+        assert_eq!(scene.render.output.as_deref(), Some("_image_1.png"));
+    }
+
+    #[test]
+    fn reports_semantic_errors_with_locations() {
+        let error = |source: &str| Scene::parse(source).err().unwrap().to_string();
+
+        assert_eq!(error("camera {}\nworld {\n  thing cube\n}"), "3:9: unknown geometry `cube`, expected one of: sphere");
+        assert_eq!(error("camera { sensor 960 }\nworld {}"), "1:17: `sensor` expects a height here");
+        assert_eq!(error("camera { samples 0.5 }\nworld {}"), "1:18: expected a number of samples per pixel, which is a whole number between 1 and 65535, but found `0.5`");
+        assert_eq!(error("camera {}\nworld { thing sphere { texture glass } }"), "2:32: unknown material `glass`, expected one of: absorptive, diffusive, reflective, refractive, emissive, subsurface, coated, thin_film, composite, <a defined material name>");
+        assert_eq!(error("camera {}\nworld { integrator ambient_occlusion -1 }"), "2:38: expected a radius, which is a positive number, but found `-1`");
+        assert_eq!(error("camera {}"), "1:1: the scene has no `world`");
+    }
+
+}
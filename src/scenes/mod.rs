@@ -0,0 +1,281 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+
+pub use syntax::*;
+
+use crate::imaging::Image;
+use crate::things::Thing;
+use crate::viewing::{BoxFilter, Camera, Film, Metropolis, Projection};
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::viewing::CameraLink;
+use crate::worlds::{AmbientOccluded, BidirectionalPathTraced, FirstHit, Inspected, Omnidirectional, PathTraced, PhotonMapped, World};
+
+mod syntax;
+mod loader;
+
+/// A scene loaded from a text description, which spares having to compile a program for every
+/// scene. See `Scene::parse` for the description format.
+pub struct Scene {
+    pub camera: Camera<Box<dyn Projection>>,
+    pub world: SceneWorld,
+    pub render: RenderSettings,
+}
+
+/// The world of a scene, traced by the integrator the scene chooses.
+pub enum SceneWorld {
+    PathTraced(PathTraced<Arc<dyn World>, Arc<dyn Thing>, Omnidirectional>),
+    Bidirectional(BidirectionalPathTraced<Arc<dyn World>, Arc<dyn Thing>>),
+    PhotonMapped(PhotonMapped<Arc<dyn World>, Arc<dyn Thing>>),
+    AmbientOccluded(AmbientOccluded<Arc<dyn Thing>>),
+    Inspected(Inspected<Arc<dyn Thing>>),
+}
+
+impl SceneWorld {
+
+    /// The depth of the paths traced in the world. Worlds shading the first hits of the camera
+    /// rays, regardless of the light, have a depth of 1, which `with_depth` does not change.
+    pub fn depth(&self) -> u8 {
+        match self {
+            SceneWorld::PathTraced(world) => world.depth,
+            SceneWorld::Bidirectional(world) => world.depth,
+            SceneWorld::PhotonMapped(world) => world.depth,
+            SceneWorld::AmbientOccluded(_) | SceneWorld::Inspected(_) => 1,
+        }
+    }
+
+    pub fn with_depth(self, depth: u8) -> Self {
+        match self {
+            SceneWorld::PathTraced(world) => SceneWorld::PathTraced(PathTraced { depth, ..world }),
+            SceneWorld::Bidirectional(world) => SceneWorld::Bidirectional(BidirectionalPathTraced { depth, ..world }),
+            SceneWorld::PhotonMapped(world) => SceneWorld::PhotonMapped(PhotonMapped { depth, ..world }),
+            world @ (SceneWorld::AmbientOccluded(_) | SceneWorld::Inspected(_)) => world,
+        }
+    }
+
+    fn world(&self) -> &dyn World {
+        match self {
+            SceneWorld::PathTraced(world) => world,
+            SceneWorld::Bidirectional(world) => world,
+            SceneWorld::PhotonMapped(world) => world,
+            SceneWorld::AmbientOccluded(world) => world,
+            SceneWorld::Inspected(world) => world,
+        }
+    }
+
+}
+
+impl World for SceneWorld {
+
+    fn trace(&self, ray: &Ray) -> Color {
+        self.world().trace(ray)
+    }
+
+    fn trace_linked(&self, ray: &Ray, link: &dyn CameraLink) -> Color {
+        self.world().trace_linked(ray, link)
+    }
+
+    fn trace_sample(&self, ray: &Ray, sample: usize, link: &dyn CameraLink) -> Color {
+        self.world().trace_sample(ray, sample, link)
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        self.world().first_hit(ray)
+    }
+
+    fn trace_with_first_hit(&self, ray: &Ray) -> (Color, Option<FirstHit>) {
+        self.world().trace_with_first_hit(ray)
+    }
+
+    fn prepare(&self, exposure: u64) {
+        self.world().prepare(exposure)
+    }
+
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderSettings {
+    pub stack_size: u16,
+    pub bloom_depth: u8,
+    pub output: Option<String>,
+    /// Whether to render using Metropolis light transport, rather than by exposing the camera, which
+    /// `Scene::shoot` and the command line renderer do, while the rendering methods of `Camera`
+    /// always expose it.
+    pub metropolis: Option<Metropolis>,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Unreadable(std::io::Error),
+    Invalid(Location, String),
+}
+
+impl Scene {
+
+    /// Builds a scene out of the given description, which consists of the following top level
+    /// nodes (see `Node` for the syntax):
+    ///
+    /// - `camera { ... }`, with the optional children:
+    ///   - `projection perspective | orthographic <half height> | fisheye <degrees> [equidistant | equisolid] | equirectangular | stereo <interpupillary distance>`
+    ///   - `lens { ... }`, with the optional children `focal_length <f>`, `focal_distance <d>`,
+    ///     `aperture <a>`,
+    ///     `aperture_shape circular | polygonal <sides> [<rotation degrees>] | masked "<image file>"`,
+    ///     `vignetting <v>`, and `chromatic_aberration <lateral> <axial>`
+    ///   - `sensor <width> <height> [<gain>] [rgb | spectral]`, where spectral sensors trace rays at
+    ///     sampled wavelengths, which is needed for dispersion.
+    ///   - `exposure <time>`
+    ///   - `samples <samples per pixel>`
+    /// - `world { ... }`, with the optional children `depth <d>`, `environment <r> <g> <b>`,
+    ///   `integrator path | bidirectional | photon_mapping [<photons per pass> [<initial radius>]] | ambient_occlusion [<radius>] | normals | surface_coordinates | distances [<max distance>] | things_tested [<max things>] | material_ids`,
+    ///   `transform { ... }`, and any number of `thing`, `group`, and `light` nodes. Bidirectional
+    ///   path tracing connects paths traced from the camera to paths traced from the lights (but
+    ///   not from emissive things), which helps with caustics and with lights that are hard to
+    ///   reach from the camera. Photon mapping gathers, at the first scattering surfaces seen by
+    ///   the camera, photons emitted from the lights and the emissive things, within a radius that
+    ///   shrinks as every pixel sample gathers more photons, which renders caustics seen through mirrors and glass as well.
+    ///   The other integrators ignore the lights and the environment, and quickly render the
+    ///   ambient occlusion of the things (see `AmbientOccluded`), or some aspect of the surfaces
+    ///   seen by the camera (see `Aspect`), for debugging scenes.
+    /// - `material <name> <material>`, which defines a named material that could be used by things
+    ///   defined after it.
+    /// - `render { ... }`, with the optional children `stack <frames>`, `bloom <depth>`,
+    ///   `output "<file>"`, and `metropolis [<bootstrap paths> [<chains>]]`, which renders using
+    ///   Metropolis light transport (see `Metropolis`), whose mutations per pixel are the samples
+    ///   per pixel. It helps with light reaching the camera through narrow paths.
+    ///
+    /// Things are declared as `thing sphere { ... }`, with the children `texture <texture>`, or
+    /// `outer_texture <texture>` and/or `inner_texture <texture>`, and the optional children
+    /// `transform { ... }` and `id <object id>`. Groups are declared as `group { ... }`, with an
+    /// optional transform, id, and any number of nested things and groups.
+    ///
+    /// Transforms are chains of `scale <f> | scale <x> <y> <z>`, `rotate <x> <y> <z> <degrees>`,
+    /// `translate <x> <y> <z>`, and `matrix <x column> <y column> <z column>`, applied in order.
+    /// Materials are `absorptive`,
+    /// `diffusive <r> <g> <b> [<roughness> [oren_nayar | qualitative | retro_reflective]]`,
+    /// `reflective <r> <g> <b>`,
+    /// `refractive <r> <g> <b> <index> | cauchy <a> <b> | sellmeier <b1> <b2> <b3> <c1> <c2> <c3>`
+    /// (with dispersion coefficients for wavelengths in micrometers), `emissive <r> <g> <b>`,
+    /// `subsurface <albedo r> <g> <b> <mean free path r> <g> <b> <index>` (for both sides of things),
+    /// `coated <index> [<roughness> [<absorption r> <g> <b>]] <base material>`,
+    /// `thin_film <film index> <substrate index> <thickness nm> [<max thickness nm> <detail> <noise>]
+    /// <base material>`,
+    /// `composite { choice <weight> <material>; ... }`, or the name of a defined material.
+    ///
+    /// Lights are declared as `light <light>`, where lights are `point <position> <intensity r g b>`,
+    /// `spot <position> <direction> <intensity r g b> <inner degrees> <outer degrees> [<profile>...]`
+    /// (where the optional profile is a list of relative intensities at evenly spaced angles, up to
+    /// the outer one), `directional <direction> <irradiance r g b> [<angular radius degrees>]`,
+    /// `rectangle <center> <half edge> <half edge> <radiance r g b>`,
+    /// `disk <center> <normal> <radius> <radiance r g b>`, or `sphere <center> <radius> <radiance r g b>`.
+    /// Lights are placed using the transform of the world. Point, spot, and directional lights with
+    /// no angular radius light surfaces, but could not be seen by rays. Path tracing samples the
+    /// lights directly, along with the emissive things, all picked through a light tree, so that
+    /// scenes lit by many small emitters render well either way. The other integrators only sample
+    /// the lights directly.
+    ///
+    /// Textures are either materials, which are the same all over the surface, or image textures,
+    /// declared as `image diffusive | reflective | emissive "<image file>" { ... }`, with the optional
+    /// children `filtering nearest | bilinear | bicubic`, `wrapping repeat | clamp | mirror`, and
+    /// `uv <u scale> <v scale> <u offset> <v offset>`. Procedural textures are declared as
+    /// `procedural <pattern> { ... }`, with the children `first <material>` and
+    /// `second <material>`, and the optional children `domain surface | object | world`,
+    /// `noise <noise>`, `detail <factor>`, `turbulence <factor>`, `cutoff <value>`, and `modulated`
+    /// (see `Procedural`). Patterns are `checker <scale> [<x offset> <y offset> <z offset>]`,
+    /// `stripes <x> <y> <z> <frequency>`, `rings <x> <y> <z> <frequency>`,
+    /// `marble <x> <y> <z> <frequency>`, `gradient <x> <y> <z> <start> <end>`,
+    /// `threshold <threshold> [<smoothness>]`, `bands`, or `ridges`. Noises are `simple`, `perlin [<seed> [<period>]]`,
+    /// `simplex [<seed>]`, `worley [f1 | f2 | difference] [euclidean | manhattan | chebyshev] [<seed>]`,
+    /// `fractal <depth> <noise>`, `turbulence <octaves> <noise>`, `ridged <octaves> <noise>`,
+    /// `warped <strength> <noise> <warping noise>`, `remapped | clamped <from min> <from max> <to min>
+    /// <to max> <noise>`, or `sum | difference | product | minimum | maximum | average <noise> <noise>`.
+    ///
+    /// Textures could get bumpy, with `bumped <strength> <noise> { ... }`, which has a child
+    /// `texture <texture>` and an optional child `detail <factor>`, or get normal mapped, with
+    /// `normal_mapped "<normal map image>" { ... }`, which has a child `texture <texture>` and the
+    /// optional children `strength <factor>` and `uv <u scale> <v scale> <u offset> <v offset>`.
+    /// Parts of textures could be cut out, where a noise is below a threshold, with
+    /// `cutout <threshold> <noise> { ... }`, which has a child `texture <texture>` and the optional
+    /// children `detail <factor>` and `smoothness <width>`, or where the alpha channel of an image is
+    /// transparent, with `alpha_mapped "<image file>" { ... }`, which has a child `texture <texture>`
+    /// and an optional child `uv <u scale> <v scale> <u offset> <v offset>`.
+    ///
+    /// Principled textures are declared as `principled { ... }`, with the optional children
+    /// `base_color`, `metallic`, `roughness`, `specular`, `transmission`, `ior`, `clearcoat`,
+    /// `clearcoat_roughness`, `sheen`, and `emission`, each taking either a constant (a color for
+    /// `base_color` and `emission`, or a number otherwise), an image lookup
+    /// `image "<image file>" [<u scale> <v scale> <u offset> <v offset>]`, or, for numbers, a noise
+    /// `noise <low> <high> <detail> <noise>`.
+    pub fn parse(source: &str) -> Result<Self, SceneError> {
+        loader::Loader::new(Path::new("")).scene(&parse(source)?)
+    }
+
+    /// Loads the scene described in the given file. Files referenced by the scene are looked up
+    /// relative to the directory of the scene file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(SceneError::Unreadable)?;
+        loader::Loader::new(path.parent().unwrap_or(Path::new(""))).scene(&parse(&source)?)
+    }
+
+    /// Renders the scene, using Metropolis light transport if the render settings say so, in which
+    /// case stacked frames add to the mutations per pixel.
+    pub fn shoot(&self) -> Image {
+        let Some(ref metropolis) = self.render.metropolis else {
+            return self.camera.shoot(&self.world, self.render.stack_size, self.render.bloom_depth)
+        };
+        let sensor = &self.camera.sensor;
+        let film = Film::new(sensor.width, sensor.height, BoxFilter { radius: 0.5 });
+        let passes = self.render.stack_size as usize * self.camera.samples_per_pixel as usize;
+        let mut chains = metropolis.start(&self.camera, &self.world);
+        for _ in 0 .. passes {
+            chains.expose(&film);
+        }
+        self.camera.develop(&film.develop(1.0 / passes as f64), self.render.bloom_depth)
+    }
+
+}
+
+impl Default for RenderSettings {
+
+    fn default() -> Self {
+        Self {
+            stack_size: 1,
+            bloom_depth: 0,
+            output: None,
+            metropolis: None,
+        }
+    }
+
+}
+
+impl SceneError {
+
+    pub fn invalid<S: Into<String>>(location: Location, message: S) -> Self {
+        SceneError::Invalid(location, message.into())
+    }
+
+}
+
+impl Display for SceneError {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Unreadable(error) => write!(f, "could not read the scene: {}", error),
+            SceneError::Invalid(location, message) => write!(f, "{}:{}: {}", location.line, location.column, message),
+        }
+    }
+
+}
+
+impl Error for SceneError {
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Unreadable(error) => Some(error),
+            SceneError::Invalid(_, _) => None,
+        }
+    }
+
+}
@@ -0,0 +1,307 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::scenes::SceneError;
+
+/// A position in a scene description, where both lines and columns are counted from 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A scene description is a tree of nodes. Each node has a name, followed by zero or more arguments
+/// on the same line, optionally followed by a block of child nodes between braces. Nodes are
+/// separated by line breaks or semicolons, and comments start with `#` and extend to the end of the
+/// line:
+///
+/// ```text
+/// # A reflective sphere
+/// thing sphere {
+///     transform { scale 2; translate 0 1 -5 }
+///     texture reflective 0.8 0.8 0.8
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub location: Location,
+    pub arguments: Vec<Argument>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Argument {
+    pub value: Value,
+    pub location: Location,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Symbol(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Symbol(String),
+    Number(f64),
+    Text(String),
+    Open,
+    Close,
+    Separator,
+    End,
+}
+
+struct Lexer<'a> {
+    chars: Peekable<Chars<'a>>,
+    location: Location,
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    token: Token,
+    location: Location,
+}
+
+/// Parses a scene description into its top level nodes.
+pub fn parse(source: &str) -> Result<Vec<Node>, SceneError> {
+    let mut parser = Parser::new(source)?;
+    let nodes = parser.nodes()?;
+    match parser.token {
+        Token::End => Ok(nodes),
+        _ => Err(parser.unexpected("a setting name")),
+    }
+}
+
+impl Value {
+
+    pub fn describe(&self) -> String {
+        match self {
+            Value::Number(n) => format!("the number `{}`", n),
+            Value::Text(t) => format!("the text \"{}\"", t),
+            Value::Symbol(s) => format!("`{}`", s),
+        }
+    }
+
+}
+
+impl<'a> Lexer<'a> {
+
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            location: Location { line: 1, column: 1 },
+        }
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.location.line += 1;
+            self.location.column = 1;
+        } else if c.is_some() {
+            self.location.column += 1;
+        }
+        c
+    }
+
+    fn next_token(&mut self) -> Result<(Token, Location), SceneError> {
+        self.skip_blanks();
+        let location = self.location;
+        let token = match self.chars.peek().copied() {
+            None => Token::End,
+            Some('\n') | Some(';') => {
+                self.advance();
+                Token::Separator
+            },
+            Some('{') => {
+                self.advance();
+                Token::Open
+            },
+            Some('}') => {
+                self.advance();
+                Token::Close
+            },
+            Some('"') => Token::Text(self.text(location)?),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => Token::Number(self.number(location)?),
+            Some(c) if c.is_alphabetic() || c == '_' => Token::Symbol(self.word()),
+            Some(c) => return Err(SceneError::invalid(location, format!("unexpected character `{}`", c))),
+        };
+        Ok((token, location))
+    }
+
+    fn skip_blanks(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '#' {
+                while self.chars.peek().is_some_and(|&c| c != '\n') {
+                    self.advance();
+                }
+            } else if c.is_whitespace() && c != '\n' {
+                self.advance();
+            } else {
+                break
+            }
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                word.push(c);
+                self.advance();
+            } else {
+                break
+            }
+        }
+        word
+    }
+
+    fn number(&mut self, location: Location) -> Result<f64, SceneError> {
+        let mut literal = String::new();
+        while let Some(&c) = self.chars.peek() {
+            let exponent_sign = (c == '-' || c == '+') && literal.ends_with(['e', 'E']);
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign || literal.is_empty() {
+                literal.push(c);
+                self.advance();
+            } else {
+                break
+            }
+        }
+        literal.parse().map_err(|_| SceneError::invalid(location, format!("`{}` is not a valid number", literal)))
+    }
+
+    fn text(&mut self, location: Location) -> Result<String, SceneError> {
+        let mut text = String::new();
+        self.advance();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.advance() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c @ ('"' | '\\')) => text.push(c),
+                    _ => return Err(SceneError::invalid(self.location, "invalid escape sequence in text")),
+                },
+                Some('\n') | None => return Err(SceneError::invalid(location, "unterminated text")),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+}
+
+impl<'a> Parser<'a> {
+
+    fn new(source: &'a str) -> Result<Self, SceneError> {
+        let mut lexer = Lexer::new(source);
+        let (token, location) = lexer.next_token()?;
+        Ok(Self { lexer, token, location })
+    }
+
+    fn advance(&mut self) -> Result<Token, SceneError> {
+        let (token, location) = self.lexer.next_token()?;
+        self.location = location;
+        Ok(std::mem::replace(&mut self.token, token))
+    }
+
+    fn nodes(&mut self) -> Result<Vec<Node>, SceneError> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.token {
+                Token::Separator => {
+                    self.advance()?;
+                },
+                Token::Symbol(_) => nodes.push(self.node()?),
+                _ => return Ok(nodes),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, SceneError> {
+        let location = self.location;
+        let name = match self.advance()? {
+            Token::Symbol(name) => name,
+            _ => unreachable!(),
+        };
+        let mut arguments = Vec::new();
+        loop {
+            let argument_location = self.location;
+            let value = match self.token {
+                Token::Number(n) => Value::Number(n),
+                Token::Text(ref t) => Value::Text(t.clone()),
+                Token::Symbol(ref s) => Value::Symbol(s.clone()),
+                _ => break,
+            };
+            arguments.push(Argument { value, location: argument_location });
+            self.advance()?;
+        }
+        let children = if self.token == Token::Open {
+            let open_location = self.location;
+            self.advance()?;
+            let children = self.nodes()?;
+            match self.token {
+                Token::Close => {
+                    self.advance()?;
+                },
+                Token::End => return Err(SceneError::invalid(open_location, format!("the block of `{}` is never closed", name))),
+                _ => return Err(self.unexpected("a setting name or `}`")),
+            }
+            children
+        } else {
+            Vec::new()
+        };
+        match self.token {
+            Token::Separator | Token::Close | Token::End => Ok(Node { name, location, arguments, children }),
+            _ => Err(self.unexpected("a line break or `;`")),
+        }
+    }
+
+    fn unexpected(&self, expectation: &str) -> SceneError {
+        let found = match self.token {
+            Token::Symbol(ref s) => format!("`{}`", s),
+            Token::Number(n) => format!("the number `{}`", n),
+            Token::Text(_) => "a text".to_string(),
+            Token::Open => "`{`".to_string(),
+            Token::Close => "`}`".to_string(),
+            Token::Separator => "a line break".to_string(),
+            Token::End => "the end of the scene".to_string(),
+        };
+        SceneError::invalid(self.location, format!("expected {}, but found {}", expectation, found))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_nodes() {
+        let nodes = parse("a 1 -2.5e1 \"x\" b { c; d {\n} }\n# comment\ne").unwrap();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].name, "a");
+        assert_eq!(nodes[0].arguments.iter().map(|a| a.value.clone()).collect::<Vec<_>>(), vec![
+            Value::Number(1.0),
+            Value::Number(-25.0),
+            Value::Text("x".to_string()),
+            Value::Symbol("b".to_string()),
+        ]);
+        assert_eq!(nodes[0].children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["c", "d"]);
+        assert_eq!(nodes[1].name, "e");
+        assert_eq!(nodes[1].location, Location { line: 4, column: 1 });
+    }
+
+    #[test]
+    fn reports_error_locations() {
+        let error = parse("a {\n  b 1 }\n  c 2 {").unwrap_err();
+
+        assert_eq!(error.to_string(), "3:7: the block of `c` is never closed");
+        assert_eq!(parse("a 1 } b").unwrap_err().to_string(), "1:5: expected a setting name, but found `}`");
+        assert_eq!(parse("a 1.2.3").unwrap_err().to_string(), "1:3: `1.2.3` is not a valid number");
+    }
+
+}
@@ -0,0 +1,160 @@
+use crate::geometries::{Geometry, Hit};
+use crate::imaging::ImageMap;
+use crate::noise::Noise;
+use crate::textures::{MaterialHolder, Texture};
+
+/// A texture that makes parts of the surfaces of another texture transparent, e.g. to cut leaves
+/// out of flat shapes, or to punch holes into surfaces. Fractional opacities make surfaces
+/// translucent, by letting rays pass through them stochastically.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::Diffusive;
+/// # use photon::noise::Perlin;
+/// # use photon::textures::{Constant, Cutout, NoiseMask};
+/// let holes = Cutout::new(Constant(Diffusive(Color::WHITE)), NoiseMask::new(Perlin::new(7), 0.4).with_detail(4.0));
+/// ```
+pub struct Cutout<T: Texture, M: Mask> {
+    pub texture: T,
+    pub mask: M,
+}
+
+/// Masks determine the opacity of surfaces at hit points, as a value in the range `[0, 1]`.
+pub trait Mask: Send + Sync {
+
+    fn opacity_at(&self, hit: &Hit, geometry: &dyn Geometry) -> f64;
+
+}
+
+/// A mask that makes surfaces opaque where a noise, evaluated in object space at points scaled by
+/// the detail factor, exceeds a threshold. The smoothness widens the transition between transparent
+/// and opaque parts into a translucent band.
+pub struct NoiseMask<N: Noise> {
+    pub noise: N,
+    pub threshold: f64,
+    pub smoothness: f64,
+    pub detail: f64,
+}
+
+/// A mask that looks up opacities from the red channel of an image, e.g. one loaded using
+/// `Image::load_alpha`. UV coordinates are derived from surface coordinates as in `ImageTexture`.
+pub struct AlphaMap {
+    pub map: ImageMap,
+    pub uv_scale: (f64, f64),
+    pub uv_offset: (f64, f64),
+}
+
+impl<T: Texture, M: Mask> Cutout<T, M> {
+
+    pub fn new(texture: T, mask: M) -> Self {
+        Self { texture, mask }
+    }
+
+}
+
+impl<T: Texture, M: Mask> Texture for Cutout<T, M> {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, other_side_texture: &'a dyn Texture) -> MaterialHolder<'a> {
+        self.texture.material(hit, geometry, other_side_texture)
+    }
+
+    fn material_id(&self) -> u32 {
+        self.texture.material_id()
+    }
+
+    fn opacity(&self, hit: &Hit, geometry: &dyn Geometry, other_side_texture: &dyn Texture) -> f64 {
+        self.mask.opacity_at(hit, geometry) * self.texture.opacity(hit, geometry, other_side_texture)
+    }
+
+}
+
+impl<N: Noise> NoiseMask<N> {
+
+    pub fn new(noise: N, threshold: f64) -> Self {
+        Self { noise, threshold, smoothness: 0.0, detail: 1.0 }
+    }
+
+    pub fn with_smoothness(self, smoothness: f64) -> Self {
+        Self { smoothness, ..self }
+    }
+
+    pub fn with_detail(self, detail: f64) -> Self {
+        Self { detail, ..self }
+    }
+
+}
+
+impl<N: Noise> Mask for NoiseMask<N> {
+
+    fn opacity_at(&self, hit: &Hit, _: &dyn Geometry) -> f64 {
+        let point = hit.local_hit().incident_ray.origin * self.detail;
+        let value = self.noise.value_at_time(&point, hit.incident_ray.time);
+        if self.smoothness > 0.0 {
+            let t = ((value - self.threshold) / self.smoothness + 0.5).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        } else if value >= self.threshold {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+}
+
+impl AlphaMap {
+
+    pub fn new(map: ImageMap) -> Self {
+        Self { map, uv_scale: (0.5, 1.0), uv_offset: (0.5, 0.5) }
+    }
+
+    pub fn with_uv_transform(self, scale_u: f64, scale_v: f64, offset_u: f64, offset_v: f64) -> Self {
+        Self { uv_scale: (scale_u, scale_v), uv_offset: (offset_u, offset_v), ..self }
+    }
+
+}
+
+impl Mask for AlphaMap {
+
+    fn opacity_at(&self, hit: &Hit, geometry: &dyn Geometry) -> f64 {
+        let point = geometry.surface_coordinates(&hit.local_hit().incident_ray.origin);
+        let u = point.x() * self.uv_scale.0 + self.uv_offset.0;
+        let v = point.y() * self.uv_scale.1 + self.uv_offset.1;
+        self.map.color_at(u, v).red().clamp(0.0, 1.0)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::Color;
+    use crate::basic::rays::Ray;
+    use crate::basic::vectors::tests::unit_vec3;
+    use crate::geometries::Sphere;
+    use crate::materials::Diffusive;
+    use crate::noise::Simple;
+    use crate::textures::{Black, Constant};
+    use crate::things::{AtomicThing, Thing};
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn rays_pass_through_transparent_surfaces(direction in unit_vec3()) {
+            let ray = Ray::new(direction * -3.0, direction, Color::WHITE, 0.0);
+            let transparent = Cutout::new(Constant(Diffusive(Color::WHITE)), NoiseMask::new(Simple, 2.0));
+            let opaque = Cutout::new(Constant(Diffusive(Color::WHITE)), NoiseMask::new(Simple, -1.0));
+
+            let hollow = AtomicThing { geometry: Sphere, outer_texture: transparent, inner_texture: Black };
+            let solid = AtomicThing { geometry: Sphere, outer_texture: opaque, inner_texture: Black };
+
+            assert!(!hollow.shoot(&ray, 0.0, f64::INFINITY).unwrap().hit.outside);
+            assert!(solid.shoot(&ray, 0.0, f64::INFINITY).unwrap().hit.outside);
+        }
+
+    }
+
+}
@@ -0,0 +1,41 @@
+use crate::basic::colors::Color;
+use crate::geometries::{Geometry, Hit};
+use crate::imaging::ImageMap;
+use crate::materials::Material;
+use crate::textures::{MaterialHolder, Texture};
+
+/// A texture that looks up the color at each hit point from an image map, and makes a material out
+/// of it, e.g. `ImageTexture::new(map, Diffusive)` for an albedo map.
+///
+/// The UV coordinates of a hit point are the surface coordinates of its geometry, scaled then
+/// offset by the UV transform. The default transform maps the surface coordinates of spheres,
+/// whose longitudes span `[-1, 1]` and whose latitudes span `[-0.5, 0.5]`, onto the whole image.
+pub struct ImageTexture<M: Material, F: Fn(Color) -> M + Send + Sync> {
+    pub map: ImageMap,
+    pub material: F,
+    pub uv_scale: (f64, f64),
+    pub uv_offset: (f64, f64),
+}
+
+impl<M: Material, F: Fn(Color) -> M + Send + Sync> ImageTexture<M, F> {
+
+    pub fn new(map: ImageMap, material: F) -> Self {
+        Self { map, material, uv_scale: (0.5, 1.0), uv_offset: (0.5, 0.5) }
+    }
+
+    pub fn with_uv_transform(self, scale_u: f64, scale_v: f64, offset_u: f64, offset_v: f64) -> Self {
+        Self { uv_scale: (scale_u, scale_v), uv_offset: (offset_u, offset_v), ..self }
+    }
+
+}
+
+impl<M: Material + 'static, F: Fn(Color) -> M + Send + Sync> Texture for ImageTexture<M, F> {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
+        let point = geometry.surface_coordinates(&hit.local_hit().incident_ray.origin);
+        let u = point.x() * self.uv_scale.0 + self.uv_offset.0;
+        let v = point.y() * self.uv_scale.1 + self.uv_offset.1;
+        MaterialHolder::Owning(Box::new((self.material)(self.map.color_at(u, v))))
+    }
+
+}
@@ -2,25 +2,56 @@ use std::sync::Arc;
 
 pub use black::*;
 pub use constant::*;
+pub use cutout::*;
+pub use image::*;
+pub use parameter::*;
+pub use perturbed::*;
+pub use principled::*;
 pub use same::*;
 
 use crate::geometries::{Geometry, Hit};
 use crate::materials::MaterialHolder;
 
+pub mod procedural;
+
 mod constant;
 mod black;
 mod same;
+mod image;
+mod perturbed;
+mod cutout;
+mod parameter;
+mod principled;
+mod tagged;
 
 pub trait Texture: Send + Sync {
 
     fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, other_side_texture: &'a dyn Texture) -> MaterialHolder<'a>;
 
+    fn material_id(&self) -> u32 {
+        0
+    }
+
+    /// Returns the probability that rays hitting the surface at the given hit point stop there,
+    /// rather than passing through as if the surface were not there.
+    fn opacity(&self, _: &Hit, _: &dyn Geometry, _: &dyn Texture) -> f64 {
+        1.0
+    }
+
 }
 
-impl<T: Texture> Texture for Arc<T> {
+impl<T: Texture + ?Sized> Texture for Arc<T> {
 
     fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, other_side_texture: &'a dyn Texture) -> MaterialHolder<'a> {
         self.as_ref().material(hit, geometry, other_side_texture)
     }
 
+    fn material_id(&self) -> u32 {
+        self.as_ref().material_id()
+    }
+
+    fn opacity(&self, hit: &Hit, geometry: &dyn Geometry, other_side_texture: &dyn Texture) -> f64 {
+        self.as_ref().opacity(hit, geometry, other_side_texture)
+    }
+
 }
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use crate::basic::colors::Color;
+use crate::geometries::{Geometry, Hit};
+use crate::imaging::ImageMap;
+use crate::noise::Noise;
+
+/// Parameters are values (e.g. colors, or scalars like roughness) that could vary over surfaces,
+/// to drive the parameters of materials made by textures like `PrincipledTexture`. Plain colors and
+/// numbers are parameters that are the same everywhere.
+pub trait Parameter<T>: Send + Sync {
+
+    fn value_at(&self, hit: &Hit, geometry: &dyn Geometry) -> T;
+
+}
+
+impl Parameter<f64> for f64 {
+
+    fn value_at(&self, _: &Hit, _: &dyn Geometry) -> f64 {
+        *self
+    }
+
+}
+
+impl Parameter<Color> for Color {
+
+    fn value_at(&self, _: &Hit, _: &dyn Geometry) -> Color {
+        *self
+    }
+
+}
+
+impl<T, P: Parameter<T> + ?Sized> Parameter<T> for Arc<P> {
+
+    fn value_at(&self, hit: &Hit, geometry: &dyn Geometry) -> T {
+        self.as_ref().value_at(hit, geometry)
+    }
+
+}
+
+/// A parameter looked up from an image map, where UV coordinates are derived from surface
+/// coordinates as in `ImageTexture`. Scalar parameters are taken from the red channel, so images
+/// of scalar parameters (e.g. roughness maps) should be loaded using `Image::load_data`.
+pub struct ImageParameter {
+    pub map: ImageMap,
+    pub uv_scale: (f64, f64),
+    pub uv_offset: (f64, f64),
+}
+
+/// A scalar parameter that varies between two values, following a noise that is evaluated in object
+/// space, at points scaled by the detail factor.
+pub struct NoiseParameter<N: Noise> {
+    pub noise: N,
+    pub range: (f64, f64),
+    pub detail: f64,
+}
+
+impl ImageParameter {
+
+    pub fn new(map: ImageMap) -> Self {
+        Self { map, uv_scale: (0.5, 1.0), uv_offset: (0.5, 0.5) }
+    }
+
+    pub fn with_uv_transform(self, scale_u: f64, scale_v: f64, offset_u: f64, offset_v: f64) -> Self {
+        Self { uv_scale: (scale_u, scale_v), uv_offset: (offset_u, offset_v), ..self }
+    }
+
+    fn color_at(&self, hit: &Hit, geometry: &dyn Geometry) -> Color {
+        let point = geometry.surface_coordinates(&hit.local_hit().incident_ray.origin);
+        let u = point.x() * self.uv_scale.0 + self.uv_offset.0;
+        let v = point.y() * self.uv_scale.1 + self.uv_offset.1;
+        self.map.color_at(u, v)
+    }
+
+}
+
+impl Parameter<Color> for ImageParameter {
+
+    fn value_at(&self, hit: &Hit, geometry: &dyn Geometry) -> Color {
+        self.color_at(hit, geometry)
+    }
+
+}
+
+impl Parameter<f64> for ImageParameter {
+
+    fn value_at(&self, hit: &Hit, geometry: &dyn Geometry) -> f64 {
+        self.color_at(hit, geometry).red()
+    }
+
+}
+
+impl<N: Noise> NoiseParameter<N> {
+
+    pub fn new(noise: N, low: f64, high: f64) -> Self {
+        Self { noise, range: (low, high), detail: 1.0 }
+    }
+
+    pub fn with_detail(self, detail: f64) -> Self {
+        Self { detail, ..self }
+    }
+
+}
+
+impl<N: Noise> Parameter<f64> for NoiseParameter<N> {
+
+    fn value_at(&self, hit: &Hit, _: &dyn Geometry) -> f64 {
+        let point = hit.local_hit().incident_ray.origin * self.detail;
+        let value = self.noise.value_at_time(&point, hit.incident_ray.time).clamp(0.0, 1.0);
+        let (low, high) = self.range;
+        low + value * (high - low)
+    }
+
+}
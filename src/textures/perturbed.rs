@@ -0,0 +1,227 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::geometries::{Geometry, Hit};
+use crate::imaging::ImageMap;
+use crate::materials::{Effect, Material};
+use crate::noise::Noise;
+use crate::textures::{MaterialHolder, Texture};
+
+/// A texture that perturbs the shading normals of the materials of another texture, giving the
+/// illusion of surface detail (e.g. bumps, scratches, or engraved patterns) without altering the
+/// geometry. The materials get hits whose normals are perturbed, while the geometric normals are
+/// still used for everything else.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::materials::Diffusive;
+/// # use photon::noise::Perlin;
+/// # use photon::textures::{BumpMap, Constant, Perturbed};
+/// let bumpy = Perturbed::new(Constant(Diffusive(Color::WHITE)), BumpMap::new(Perlin::new(7), 0.5).with_detail(8.0));
+/// ```
+pub struct Perturbed<T: Texture, P: Perturbation> {
+    pub texture: T,
+    pub perturbation: P,
+}
+
+/// Perturbations produce the perturbed normals at hit points, in tangent space, i.e. relative to
+/// the tangent frame of each hit, where the tangent is the `x` axis, and the unperturbed normal is
+/// the `z` axis.
+pub trait Perturbation: Send + Sync {
+
+    fn tangent_space_normal(&self, hit: &Hit, geometry: &dyn Geometry) -> Vec3D;
+
+}
+
+/// Bump mapping, which treats a noise as a height field over the surface, and tilts the normals
+/// along the slopes of that height field. The height field is evaluated in object space, at points
+/// scaled by the detail factor.
+pub struct BumpMap<N: Noise> {
+    pub noise: N,
+    pub strength: f64,
+    pub detail: f64,
+}
+
+/// Normal mapping, which looks up tangent space normals from an image, whose red, green, and blue
+/// components encode the `x`, `y`, and `z` components of the normals, mapped from `[-1, 1]` to
+/// `[0, 1]`. Normal maps should therefore be loaded using `Image::load_data`.
+///
+/// UV coordinates are derived from surface coordinates as in `ImageTexture`, and the strength
+/// scales the tilt of the normals.
+pub struct NormalMap {
+    pub map: ImageMap,
+    pub strength: f64,
+    pub uv_scale: (f64, f64),
+    pub uv_offset: (f64, f64),
+}
+
+struct PerturbedMaterial<'a> {
+    material: MaterialHolder<'a>,
+    normal: Vec3D,
+}
+
+impl<T: Texture, P: Perturbation> Perturbed<T, P> {
+
+    pub fn new(texture: T, perturbation: P) -> Self {
+        Self { texture, perturbation }
+    }
+
+}
+
+impl<T: Texture, P: Perturbation> Texture for Perturbed<T, P> {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, other_side_texture: &'a dyn Texture) -> MaterialHolder<'a> {
+        let material = self.texture.material(hit, geometry, other_side_texture);
+        let normal = &hit.tangent_frame() * &self.perturbation.tangent_space_normal(hit, geometry);
+        // Normals tilted away from the incident ray would make surfaces look lit from behind.
+        if normal.dot(hit.incident_ray.direction) * hit.normal.dot(hit.incident_ray.direction) > 0.0 {
+            MaterialHolder::Owning(Box::new(PerturbedMaterial { material, normal }))
+        } else {
+            material
+        }
+    }
+
+    fn material_id(&self) -> u32 {
+        self.texture.material_id()
+    }
+
+    fn opacity(&self, hit: &Hit, geometry: &dyn Geometry, other_side_texture: &dyn Texture) -> f64 {
+        self.texture.opacity(hit, geometry, other_side_texture)
+    }
+
+}
+
+impl Material for PerturbedMaterial<'_> {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        self.material.effect_of(&hit.with_normal(&self.normal))
+    }
+
+}
+
+impl<N: Noise> BumpMap<N> {
+
+    pub fn new(noise: N, strength: f64) -> Self {
+        Self { noise, strength, detail: 1.0 }
+    }
+
+    pub fn with_detail(self, detail: f64) -> Self {
+        Self { detail, ..self }
+    }
+
+    fn height_at(&self, point: &Vec3D, time: f64) -> f64 {
+        self.noise.value_at_time(&(point * self.detail), time)
+    }
+
+}
+
+impl<N: Noise> Perturbation for BumpMap<N> {
+
+    fn tangent_space_normal(&self, hit: &Hit, _: &dyn Geometry) -> Vec3D {
+        let local_hit = hit.local_hit();
+        let point = local_hit.incident_ray.origin;
+        let time = hit.incident_ray.time;
+        let frame = local_hit.tangent_frame();
+        let h = 1e-4 / self.detail;
+        let slope = |direction: &Vec3D| (self.height_at(&(point + direction * h), time) - self.height_at(&(point - direction * h), time)) / (2.0 * h);
+        Vec3D::new(-self.strength * slope(frame.x()), -self.strength * slope(frame.y()), 1.0).unit()
+    }
+
+}
+
+impl NormalMap {
+
+    pub fn new(map: ImageMap) -> Self {
+        Self { map, strength: 1.0, uv_scale: (0.5, 1.0), uv_offset: (0.5, 0.5) }
+    }
+
+    pub fn with_strength(self, strength: f64) -> Self {
+        Self { strength, ..self }
+    }
+
+    pub fn with_uv_transform(self, scale_u: f64, scale_v: f64, offset_u: f64, offset_v: f64) -> Self {
+        Self { uv_scale: (scale_u, scale_v), uv_offset: (offset_u, offset_v), ..self }
+    }
+
+}
+
+impl Perturbation for NormalMap {
+
+    fn tangent_space_normal(&self, hit: &Hit, geometry: &dyn Geometry) -> Vec3D {
+        let point = geometry.surface_coordinates(&hit.local_hit().incident_ray.origin);
+        let u = point.x() * self.uv_scale.0 + self.uv_offset.0;
+        let v = point.y() * self.uv_scale.1 + self.uv_offset.1;
+        let color = self.map.color_at(u, v);
+        let x = self.strength * (2.0 * color.red() - 1.0);
+        let y = self.strength * (2.0 * color.green() - 1.0);
+        let z = (2.0 * color.blue() - 1.0).max(1e-3);
+        Vec3D::new(x, y, z).unit()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::Color;
+    use crate::basic::rays::Ray;
+    use crate::basic::vectors::tests::unit_vec3;
+    use crate::geometries::Sphere;
+    use crate::imaging::Image;
+    use crate::rough_equality;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn flat_normal_maps_keep_geometric_normals(direction in unit_vec3()) {
+            let ray = Ray::new(direction * -3.0, direction, Color::WHITE, 0.0);
+            let hit = Sphere.shoot(&ray, 0.0, f64::INFINITY).unwrap();
+            let flat = NormalMap::new(ImageMap::new(Image::solid(Color::new(0.5, 0.5, 1.0), 2, 2)));
+
+            let normal = &hit.tangent_frame() * &flat.tangent_space_normal(&hit, &Sphere);
+
+            assert!(rough_equality(normal.dot(hit.normal.unit()), 1.0));
+        }
+
+        #[test]
+        fn bump_maps_tilt_normals_along_the_slopes_of_linear_heights(direction in unit_vec3(), gradient in unit_vec3(), strength in 0.0..2.0, detail in 0.5..4.0) {
+            let ray = Ray::new(direction * -3.0, direction, Color::WHITE, 0.0);
+            let hit = Sphere.shoot(&ray, 0.0, f64::INFINITY).unwrap();
+            let bump_map = BumpMap::new(Ramp(gradient), strength).with_detail(detail);
+
+            let normal = bump_map.tangent_space_normal(&hit, &Sphere);
+
+            let frame = hit.local_hit().tangent_frame();
+            let slope = |axis: &Vec3D| gradient.dot(*axis) * detail;
+            let expected = Vec3D::new(-strength * slope(frame.x()), -strength * slope(frame.y()), 1.0).unit();
+            assert!((normal - expected).length() < 1e-6, "{:?} vs {:?}", normal, expected);
+        }
+
+        #[test]
+        fn bump_maps_without_strength_keep_geometric_normals(direction in unit_vec3(), gradient in unit_vec3()) {
+            let ray = Ray::new(direction * -3.0, direction, Color::WHITE, 0.0);
+            let hit = Sphere.shoot(&ray, 0.0, f64::INFINITY).unwrap();
+            let flat = BumpMap::new(Ramp(gradient), 0.0);
+
+            let normal = &hit.tangent_frame() * &flat.tangent_space_normal(&hit, &Sphere);
+
+            assert!(rough_equality(normal.dot(hit.normal.unit()), 1.0));
+        }
+
+    }
+
+    /// Heights rising linearly along a gradient.
+    struct Ramp(Vec3D);
+
+    impl Noise for Ramp {
+
+        fn value_at(&self, point: &Vec3D) -> f64 {
+            let Self(ref gradient) = self;
+            gradient.dot(point)
+        }
+
+    }
+
+}
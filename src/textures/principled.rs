@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::basic::colors::Color;
+use crate::geometries::{Geometry, Hit};
+use crate::materials::Principled;
+use crate::textures::{MaterialHolder, Parameter, Texture};
+
+/// A texture making `Principled` materials, whose parameters are driven by other parameters, like
+/// image maps or noises. Parameters that are not set take the defaults of `Principled::new`.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::noise::Perlin;
+/// # use photon::textures::{NoiseParameter, PrincipledTexture};
+/// let worn_metal = PrincipledTexture::new(Color::new(0.9, 0.6, 0.4))
+///     .with_metallic(1.0)
+///     .with_roughness(NoiseParameter::new(Perlin::new(7), 0.1, 0.6).with_detail(8.0));
+/// ```
+pub struct PrincipledTexture {
+    pub base_color: Arc<dyn Parameter<Color>>,
+    pub metallic: Arc<dyn Parameter<f64>>,
+    pub roughness: Arc<dyn Parameter<f64>>,
+    pub specular: Arc<dyn Parameter<f64>>,
+    pub transmission: Arc<dyn Parameter<f64>>,
+    pub ior: Arc<dyn Parameter<f64>>,
+    pub clearcoat: Arc<dyn Parameter<f64>>,
+    pub clearcoat_roughness: Arc<dyn Parameter<f64>>,
+    pub sheen: Arc<dyn Parameter<f64>>,
+    pub emission: Arc<dyn Parameter<Color>>,
+}
+
+impl PrincipledTexture {
+
+    pub fn new<P: Parameter<Color> + 'static>(base_color: P) -> Self {
+        let defaults = Principled::new(Color::BLACK);
+        Self {
+            base_color: Arc::new(base_color),
+            metallic: Arc::new(defaults.metallic),
+            roughness: Arc::new(defaults.roughness),
+            specular: Arc::new(defaults.specular),
+            transmission: Arc::new(defaults.transmission),
+            ior: Arc::new(defaults.ior),
+            clearcoat: Arc::new(defaults.clearcoat),
+            clearcoat_roughness: Arc::new(defaults.clearcoat_roughness),
+            sheen: Arc::new(defaults.sheen),
+            emission: Arc::new(defaults.emission),
+        }
+    }
+
+    pub fn with_metallic<P: Parameter<f64> + 'static>(self, metallic: P) -> Self {
+        Self { metallic: Arc::new(metallic), ..self }
+    }
+
+    pub fn with_roughness<P: Parameter<f64> + 'static>(self, roughness: P) -> Self {
+        Self { roughness: Arc::new(roughness), ..self }
+    }
+
+    pub fn with_specular<P: Parameter<f64> + 'static>(self, specular: P) -> Self {
+        Self { specular: Arc::new(specular), ..self }
+    }
+
+    pub fn with_transmission<P: Parameter<f64> + 'static>(self, transmission: P) -> Self {
+        Self { transmission: Arc::new(transmission), ..self }
+    }
+
+    pub fn with_ior<P: Parameter<f64> + 'static>(self, ior: P) -> Self {
+        Self { ior: Arc::new(ior), ..self }
+    }
+
+    pub fn with_clearcoat<P: Parameter<f64> + 'static>(self, clearcoat: P) -> Self {
+        Self { clearcoat: Arc::new(clearcoat), ..self }
+    }
+
+    pub fn with_clearcoat_roughness<P: Parameter<f64> + 'static>(self, clearcoat_roughness: P) -> Self {
+        Self { clearcoat_roughness: Arc::new(clearcoat_roughness), ..self }
+    }
+
+    pub fn with_sheen<P: Parameter<f64> + 'static>(self, sheen: P) -> Self {
+        Self { sheen: Arc::new(sheen), ..self }
+    }
+
+    pub fn with_emission<P: Parameter<Color> + 'static>(self, emission: P) -> Self {
+        Self { emission: Arc::new(emission), ..self }
+    }
+
+    pub fn material_at(&self, hit: &Hit, geometry: &dyn Geometry) -> Principled {
+        Principled {
+            base_color: self.base_color.value_at(hit, geometry),
+            metallic: self.metallic.value_at(hit, geometry),
+            roughness: self.roughness.value_at(hit, geometry),
+            specular: self.specular.value_at(hit, geometry),
+            transmission: self.transmission.value_at(hit, geometry),
+            ior: self.ior.value_at(hit, geometry),
+            clearcoat: self.clearcoat.value_at(hit, geometry),
+            clearcoat_roughness: self.clearcoat_roughness.value_at(hit, geometry),
+            sheen: self.sheen.value_at(hit, geometry),
+            emission: self.emission.value_at(hit, geometry),
+        }
+    }
+
+}
+
+impl Texture for PrincipledTexture {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
+        MaterialHolder::Owning(Box::new(self.material_at(hit, geometry)))
+    }
+
+}
@@ -0,0 +1,14 @@
+use crate::basic::vectors::Vec3D;
+use crate::textures::procedural::Pattern;
+
+/// Ramps from 0 to 1 as the shift, i.e. the noise scaled by turbulence, goes through each unit,
+/// like the growth rings of wood do, when the noise is smooth.
+pub struct Bands;
+
+impl Pattern for Bands {
+
+    fn value_at(&self, _: &Vec3D, shift: f64) -> f64 {
+        shift - shift.floor()
+    }
+
+}
@@ -0,0 +1,19 @@
+use crate::basic::vectors::Vec3D;
+use crate::textures::procedural::Pattern;
+
+/// Alternates between 0 and 1 in a 3D checker board of cubes whose side is `1 / scale`, shifted by
+/// the given offset, in units of cubes. In the surface domain, where the third coordinate is 0, it
+/// is a 2D checker board.
+pub struct Checker {
+    pub scale: f64,
+    pub offset: Vec3D,
+}
+
+impl Pattern for Checker {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        let cell = |c: f64, offset: f64| (c * self.scale + offset + shift).floor() as i64;
+        (cell(point.x(), self.offset.x()) + cell(point.y(), self.offset.y()) + cell(point.z(), self.offset.z())).rem_euclid(2) as f64
+    }
+
+}
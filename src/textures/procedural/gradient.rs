@@ -0,0 +1,18 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::textures::procedural::Pattern;
+
+/// Ramps linearly from 0 to 1 along the given axis, between the points whose projections on the
+/// axis are `start` and `end`.
+pub struct Gradient {
+    pub axis: Vec3D,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Pattern for Gradient {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        ((point.dot(&self.axis) + shift - self.start) / (self.end - self.start)).clamp(0.0, 1.0)
+    }
+
+}
@@ -0,0 +1,20 @@
+use std::f64::consts::PI;
+
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::textures::procedural::Pattern;
+
+/// Smooth sinusoidal veins perpendicular to the given axis, which get the look of marble when
+/// perturbed by turbulent noise.
+pub struct Marble {
+    pub axis: Vec3D,
+    pub frequency: f64,
+}
+
+impl Pattern for Marble {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        let t = point.dot(&self.axis) * self.frequency + shift;
+        0.5 + 0.5 * (2.0 * PI * t).sin()
+    }
+
+}
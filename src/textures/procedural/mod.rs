@@ -0,0 +1,288 @@
+use std::sync::Arc;
+
+pub use bands::*;
+pub use checker::*;
+pub use gradient::*;
+pub use marble::*;
+pub use ridges::*;
+pub use rings::*;
+pub use stripes::*;
+pub use threshold::*;
+
+use crate::basic::vectors::Vec3D;
+use crate::basic::colors::Color;
+use crate::geometries::{Geometry, Hit};
+use crate::materials::{Diffusive, Effect, Material, Reflective};
+use crate::noise::Noise;
+use crate::sampling::random;
+use crate::textures::{MaterialHolder, Texture};
+
+mod checker;
+mod stripes;
+mod rings;
+mod marble;
+mod gradient;
+mod threshold;
+mod bands;
+mod ridges;
+
+/// A pattern maps points to values in the range `[0, 1]`, which procedural textures use to blend
+/// between two materials. Patterns get the point at which they are evaluated, along with a shift,
+/// which is the noise value at that point scaled by the turbulence of the texture. Patterns use
+/// the shift to perturb themselves.
+pub trait Pattern: Send + Sync {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64;
+
+}
+
+impl<P: Pattern + ?Sized> Pattern for Arc<P> {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        self.as_ref().value_at(point, shift)
+    }
+
+}
+
+/// The coordinates in which procedural textures get evaluated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Domain {
+    /// The surface coordinates of the geometry, which makes the texture stick to the surface, as a
+    /// 2D pattern.
+    Surface,
+    /// The coordinates of the hit point before the thing is transformed, which makes the texture
+    /// move, rotate, and scale with the thing, as if it was carved out of a solid block.
+    Object,
+    /// The coordinates of the hit point after the thing is transformed, which makes things look as
+    /// if they were carved out of the same solid block.
+    World,
+}
+
+/// A texture that blends two materials according to a pattern. The first material is used where
+/// the pattern value is 0, and the second where it is 1. In between, each hit picks one of the
+/// two materials at random, with the pattern value being the probability of picking the second,
+/// which averages into a smooth blend, unless there is a cutoff, above which the second material
+/// is picked, and below which the first one is. The colors of the second material could also be
+/// modulated by (i.e. multiplied by) the pattern value.
+///
+/// Example:
+/// ```
+/// # use photon::basic::colors::Color;
+/// # use photon::basic::vectors::Vec3D;
+/// # use photon::materials::{Diffusive, Reflective};
+/// # use photon::noise::Simple;
+/// # use photon::textures::procedural::{Checker, Domain, Procedural};
+/// let checker = Checker { scale: 5.0, offset: Vec3D::zero() };
+/// let checker_board = Procedural::new(checker, Simple, Diffusive(Color::WHITE), Reflective(Color::WHITE))
+///     .in_domain(Domain::Surface);
+/// ```
+pub struct Procedural<P: Pattern, N: Noise, A: Material, B: Material> {
+    pub pattern: P,
+    pub noise: N,
+    pub first: A,
+    pub second: B,
+    pub domain: Domain,
+    /// The factor by which points are scaled before evaluating the noise at them.
+    pub detail: f64,
+    /// The factor by which noise values are scaled into the shifts passed to the pattern.
+    pub turbulence: f64,
+    pub cutoff: Option<f64>,
+    pub modulated: bool,
+}
+
+impl<P: Pattern, N: Noise, A: Material, B: Material> Procedural<P, N, A, B> {
+
+    pub fn new(pattern: P, noise: N, first: A, second: B) -> Self {
+        Self {
+            pattern,
+            noise,
+            first,
+            second,
+            domain: Domain::Object,
+            detail: 1.0,
+            turbulence: 0.0,
+            cutoff: None,
+            modulated: false,
+        }
+    }
+
+    pub fn in_domain(self, domain: Domain) -> Self {
+        Self { domain, ..self }
+    }
+
+    pub fn with_detail(self, detail: f64) -> Self {
+        Self { detail, ..self }
+    }
+
+    pub fn with_turbulence(self, turbulence: f64) -> Self {
+        Self { turbulence, ..self }
+    }
+
+    pub fn with_cutoff(self, cutoff: f64) -> Self {
+        Self { cutoff: Some(cutoff), ..self }
+    }
+
+    pub fn modulated(self) -> Self {
+        Self { modulated: true, ..self }
+    }
+
+    /// Returns the value of the pattern, perturbed by the noise, at the given hit.
+    pub fn value_at(&self, hit: &Hit, geometry: &dyn Geometry) -> f64 {
+        let point = match self.domain {
+            Domain::Surface => geometry.surface_coordinates(&hit.local_hit().incident_ray.origin),
+            Domain::Object => hit.local_hit().incident_ray.origin,
+            Domain::World => hit.incident_ray.origin,
+        };
+        let shift = if self.turbulence != 0.0 {
+            self.turbulence * self.noise.value_at_time(&(point * self.detail), hit.incident_ray.time)
+        } else {
+            0.0
+        };
+        self.pattern.value_at(&point, shift)
+    }
+
+}
+
+impl<P: Pattern, N: Noise, A: Material, B: Material> Texture for Procedural<P, N, A, B> {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, _: &'a dyn Texture) -> MaterialHolder<'a> {
+        let value = self.value_at(hit, geometry);
+        let second = match self.cutoff {
+            Some(cutoff) => value > cutoff,
+            None => value > 0.0 && (value >= 1.0 || random::<f64>() < value),
+        };
+        if !second {
+            MaterialHolder::Borrowing(&self.first)
+        } else if self.modulated {
+            MaterialHolder::Owning(Box::new(Modulated(&self.second, value)))
+        } else {
+            MaterialHolder::Borrowing(&self.second)
+        }
+    }
+
+}
+
+impl<N: Noise> Procedural<Bands, N, Diffusive, Diffusive> {
+
+    /// Wood, made of bands following the noise, with `frequency` bands per unit of noise, each of
+    /// which brightens from black to the given color.
+    pub fn wood(noise: N, color: Color, frequency: f64, detail: f64) -> Self {
+        Self::new(Bands, noise, Diffusive(Color::BLACK), Diffusive(color))
+            .in_domain(Domain::World)
+            .with_detail(detail)
+            .with_turbulence(frequency)
+            .with_cutoff(0.0)
+            .modulated()
+    }
+
+}
+
+impl<N: Noise> Procedural<Ridges, N, Reflective, Diffusive> {
+
+    /// The crust of a planet, with land darkening down to the sea, which is reflective, along the
+    /// ridges of twice the noise, down to the given sea level.
+    pub fn planet_crust(noise: N, land: Color, sea: Color, sea_level: f64, detail: f64) -> Self {
+        Self::new(Ridges, noise, Reflective(sea), Diffusive(land))
+            .in_domain(Domain::World)
+            .with_detail(detail)
+            .with_turbulence(2.0)
+            .with_cutoff(sea_level)
+            .modulated()
+    }
+
+}
+
+/// A material whose colors are multiplied by the given factor.
+struct Modulated<'a, M: Material>(&'a M, f64);
+
+impl<'a, M: Material> Material for Modulated<'a, M> {
+
+    fn effect_of(&self, hit: &Hit) -> Effect {
+        let &Self(material, factor) = self;
+        material.effect_of(hit).map_color(|c| c * factor)
+    }
+
+    fn transmission_of(&self, hit: &Hit) -> Effect {
+        let &Self(material, factor) = self;
+        material.transmission_of(hit).map_color(|c| c * factor)
+    }
+
+    fn refraction_index(&self) -> Option<f64> {
+        self.0.refraction_index()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::rays::Ray;
+    use crate::basic::vectors::tests::{unit_vec3, vec3};
+    use crate::geometries::Sphere;
+    use crate::noise::Simple;
+
+    use super::*;
+
+    /// Returns the hit of a ray shot at the unit sphere, from outside, towards its center.
+    fn hit(direction: &Vec3D) -> Hit {
+        let ray = Ray::new(direction * 2.0, -direction, Color::WHITE, 0.0);
+        Sphere.shoot(&ray, 0.0001, f64::INFINITY).unwrap()
+    }
+
+    fn effect<T: Texture>(texture: &T, hit: &Hit) -> (Option<Color>, bool) {
+        match texture.material(hit, &Sphere, texture).effect_of(hit) {
+            Effect::Scattering(c, _) => (Some(c), true),
+            Effect::Redirection(c, _) => (Some(c), false),
+            _ => (None, false),
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn presets_match_the_textures_of_the_examples(direction in unit_vec3()) {
+            let hit = hit(&direction);
+            let point = hit.incident_ray.origin;
+            let (land, sea, color) = (Color::new(0.4, 0.8, 0.2), Color::new(0.1, 1.0, 1.0), Color::new(0.2, 0.4, 0.8));
+
+            let noise = Simple.value_at(&(point * 0.5)) * 32.0;
+            let wood = Procedural::wood(Simple, color, 32.0, 0.5);
+            prop_assert_eq!(effect(&wood, &hit), (Some(noise.fract() * color), true));
+
+            let noise = Simple.value_at(&(point * 0.5)) * 2.0;
+            let level = ((noise - noise.floor()) * 2.0 - 1.0).abs();
+            let smooth_level = level * level * (3.0 - 2.0 * level);
+            let planet_crust = Procedural::planet_crust(Simple, land, sea, 0.5, 0.5);
+            let expected = if smooth_level > 0.5 { (Some(smooth_level * land), true) } else { (Some(sea), false) };
+            prop_assert_eq!(effect(&planet_crust, &hit), expected);
+
+            let coordinates = Sphere.surface_coordinates(&point);
+            let (x, y) = ((5.0 * coordinates.x() + 0.5).floor() as i32, (5.0 * coordinates.y()).floor() as i32);
+            let checker = Checker { scale: 5.0, offset: Vec3D::new(0.5, 0.0, 0.0) };
+            let checker_board = Procedural::new(checker, Simple, Reflective(sea), Diffusive(land)).in_domain(Domain::Surface);
+            let expected = if (x + y) & 1 == 0 { (Some(sea), false) } else { (Some(land), true) };
+            prop_assert_eq!(effect(&checker_board, &hit), expected);
+        }
+
+        #[test]
+        fn patterns_have_values_within_unit_range(point in vec3(), axis in unit_vec3(), frequency in 0.1..10.0, shift in -2.0..2.0) {
+            let patterns: Vec<Box<dyn Pattern>> = vec![
+                Box::new(Checker { scale: frequency, offset: axis }),
+                Box::new(Stripes { axis, frequency }),
+                Box::new(Rings { axis, frequency }),
+                Box::new(Marble { axis, frequency }),
+                Box::new(Gradient { axis, start: -0.5, end: 0.5 }),
+                Box::new(Threshold { threshold: 0.5, smoothness: 0.1 }),
+                Box::new(Bands),
+                Box::new(Ridges),
+            ];
+            for pattern in patterns {
+                let value = pattern.value_at(&(point * 10.0), shift);
+                assert!((0.0..=1.0).contains(&value));
+            }
+        }
+
+    }
+
+}
@@ -0,0 +1,16 @@
+use crate::basic::vectors::Vec3D;
+use crate::textures::procedural::Pattern;
+
+/// Falls smoothly from 1 to 0, and rises back to 1, as the shift, i.e. the noise scaled by
+/// turbulence, goes through each unit, which makes ridges, like mountain ranges, along the
+/// integer level sets of the noise.
+pub struct Ridges;
+
+impl Pattern for Ridges {
+
+    fn value_at(&self, _: &Vec3D, shift: f64) -> f64 {
+        let level = ((shift - shift.floor()) * 2.0 - 1.0).abs();
+        level * level * (3.0 - 2.0 * level)
+    }
+
+}
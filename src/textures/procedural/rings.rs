@@ -0,0 +1,20 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::textures::procedural::Pattern;
+
+/// Concentric cylindrical rings around the given axis, passing through the origin, like the
+/// growth rings of wood. The value ramps from 0 to 1 across each ring, with `frequency` rings per
+/// unit length of the radius.
+pub struct Rings {
+    pub axis: Vec3D,
+    pub frequency: f64,
+}
+
+impl Pattern for Rings {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        let radius = point.reject(&self.axis.unit(), true).length();
+        let t = radius * self.frequency + shift;
+        t - t.floor()
+    }
+
+}
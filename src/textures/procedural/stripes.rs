@@ -0,0 +1,18 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::textures::procedural::Pattern;
+
+/// Alternates between 0 and 1 in layers perpendicular to the given axis, with `frequency` pairs of
+/// layers per unit length of the axis.
+pub struct Stripes {
+    pub axis: Vec3D,
+    pub frequency: f64,
+}
+
+impl Pattern for Stripes {
+
+    fn value_at(&self, point: &Vec3D, shift: f64) -> f64 {
+        let t = point.dot(&self.axis) * self.frequency + shift;
+        if t - t.floor() < 0.5 { 0.0 } else { 1.0 }
+    }
+
+}
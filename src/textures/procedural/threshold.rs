@@ -0,0 +1,21 @@
+use crate::basic::vectors::Vec3D;
+use crate::textures::procedural::Pattern;
+
+/// Thresholds the shift, i.e. the noise scaled by turbulence, giving 0 below the threshold and 1
+/// above it, with a smooth transition of the given width around the threshold.
+pub struct Threshold {
+    pub threshold: f64,
+    pub smoothness: f64,
+}
+
+impl Pattern for Threshold {
+
+    fn value_at(&self, _: &Vec3D, shift: f64) -> f64 {
+        if self.smoothness <= 0.0 {
+            return if shift < self.threshold { 0.0 } else { 1.0 }
+        }
+        let t = ((shift - self.threshold) / self.smoothness + 0.5).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+}
@@ -9,4 +9,8 @@ impl Texture for Same {
         other_side_texture.material(hit, geometry, &Black)
     }
 
+    fn opacity(&self, hit: &Hit, geometry: &dyn Geometry, other_side_texture: &dyn Texture) -> f64 {
+        other_side_texture.opacity(hit, geometry, &Black)
+    }
+
 }
@@ -0,0 +1,19 @@
+use crate::geometries::{Geometry, Hit};
+use crate::textures::{MaterialHolder, Texture};
+use crate::Tagged;
+
+impl<T: Texture> Texture for Tagged<T> {
+
+    fn material<'a>(&'a self, hit: &'a Hit, geometry: &'a dyn Geometry, other_side_texture: &'a dyn Texture) -> MaterialHolder<'a> {
+        self.subject.material(hit, geometry, other_side_texture)
+    }
+
+    fn material_id(&self) -> u32 {
+        self.id
+    }
+
+    fn opacity(&self, hit: &Hit, geometry: &dyn Geometry, other_side_texture: &dyn Texture) -> f64 {
+        self.subject.opacity(hit, geometry, other_side_texture)
+    }
+
+}
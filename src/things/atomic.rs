@@ -1,7 +1,8 @@
 use crate::basic::rays::Ray;
 use crate::geometries::{Geometry, Hit};
+use crate::sampling::random;
 use crate::textures::Texture;
-use crate::things::{MaterialHit, Thing};
+use crate::things::{count_test, MaterialHit, Surface, Thing};
 
 pub struct AtomicThing<G: Geometry, O: Texture, I: Texture> {
     pub geometry: G,
@@ -11,16 +12,45 @@ pub struct AtomicThing<G: Geometry, O: Texture, I: Texture> {
 
 impl<G: Geometry, O: Texture, I: Texture> Thing for AtomicThing<G, O, I> {
 
+    /// Rays pass through hits on (partially) transparent parts of the surface, with probabilities
+    /// equal to the transparency, continuing to the next hits, if any.
     fn shoot(&self, ray: &Ray, min: f64, max: f64) -> Option<MaterialHit<'_>> {
-        match self.geometry.shoot(ray, min, max) {
-            Some(hit) => Some(if hit.outside {
+        count_test();
+        let mut min = min;
+        while let Some(hit) = self.geometry.shoot(ray, min, max) {
+            let material_hit = if hit.outside {
                 self.material_hit(hit, &self.outer_texture, &self.inner_texture)
             } else {
                 self.material_hit(hit, &self.inner_texture, &self.outer_texture)
-            }),
-            None => None
+            };
+            let opacity = material_hit.texture.opacity(&material_hit.hit, &self.geometry, material_hit.other_side_texture);
+            if opacity >= 1.0 || (opacity > 0.0 && random::<f64>() < opacity) {
+                return Some(material_hit)
+            }
+            min = material_hit.hit.distance;
         }
+        None
+    }
+
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        vec![Box::new(self)]
+    }
+
+    fn surface_count(&self) -> usize {
+        1
+    }
+
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        (index == 0).then(|| Box::new(self) as Box<dyn Surface>)
+    }
+
+}
+
+impl<G: Geometry, O: Texture, I: Texture> Surface for AtomicThing<G, O, I> {
 
+    fn sample(&self) -> Option<(MaterialHit<'_>, f64)> {
+        let (hit, pdf) = self.geometry.sample_surface()?;
+        Some((self.material_hit(hit, &self.outer_texture, &self.inner_texture), pdf))
     }
 
 }
@@ -32,7 +62,9 @@ impl<G: Geometry, O: Texture, I: Texture> AtomicThing<G, O, I> {
             hit,
             geometry: &self.geometry,
             texture,
-            other_side_texture
+            other_side_texture,
+            object_id: 0,
+            surface_index: 0,
         }
     }
 
@@ -1,5 +1,5 @@
 use crate::basic::rays::Ray;
-use crate::things::{MaterialHit, Thing};
+use crate::things::{MaterialHit, Surface, Thing};
 
 pub struct Things(pub Vec<Box<dyn Thing>>);
 
@@ -9,15 +9,40 @@ impl Thing for Things {
         let mut hit = None;
         let mut max_distance = max;
         let Things(ref things) = self;
-        for thing in things.iter() {
-            hit = thing.shoot(ray, min, max_distance).or(hit);
+        for (index, thing) in things.iter().enumerate() {
+            hit = thing.shoot(ray, min, max_distance).map(|h| (index, h)).or(hit);
             max_distance = match hit {
-                Some(ref h) => h.hit.distance,
+                Some((_, ref h)) => h.hit.distance,
                 None => max_distance
             }
         }
-        hit
+        hit.map(|(index, hit)| {
+            let offset: usize = things[.. index].iter().map(|thing| thing.surface_count()).sum();
+            MaterialHit { surface_index: offset + hit.surface_index, ..hit }
+        })
     }
 
-}
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        let Things(ref things) = self;
+        things.iter().flat_map(|thing| thing.surfaces()).collect()
+    }
+
+    fn surface_count(&self) -> usize {
+        let Things(ref things) = self;
+        things.iter().map(|thing| thing.surface_count()).sum()
+    }
 
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        let Things(ref things) = self;
+        let mut index = index;
+        for thing in things.iter() {
+            let count = thing.surface_count();
+            if index < count {
+                return thing.surface(index)
+            }
+            index -= count;
+        }
+        None
+    }
+
+}
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 pub use atomic::*;
@@ -10,19 +12,73 @@ use crate::textures::Texture;
 mod atomic;
 mod composite;
 mod transformed;
+mod tagged;
+
+thread_local! {
+    static TESTS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// The number of calls to `counting_tests` in progress, on all threads, so that tests only get
+/// counted while some are.
+static COUNTING: AtomicUsize = AtomicUsize::new(0);
 
 pub trait Thing: Send + Sync {
 
     fn shoot(&self, ray: &Ray, min: f64, max: f64) -> Option<MaterialHit<'_>>;
 
+    /// Returns the surfaces of the thing that could be sampled, e.g. to trace light from the
+    /// emissive ones. Things made of geometries that cannot sample their surfaces have none.
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        Vec::new()
+    }
+
+    /// The number of surfaces of the thing (see `surfaces`).
+    fn surface_count(&self) -> usize {
+        self.surfaces().len()
+    }
+
+    /// Returns the surface at the given index among the surfaces of the thing (see `surfaces`).
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        self.surfaces().into_iter().nth(index)
+    }
+
 }
 
-impl<T: Thing> Thing for Arc<T> {
+impl<T: Thing + ?Sized> Thing for Arc<T> {
 
     fn shoot(&self, ray: &Ray, min: f64, max: f64) -> Option<MaterialHit<'_>> {
         self.as_ref().shoot(ray, min, max)
     }
 
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        self.as_ref().surfaces()
+    }
+
+    fn surface_count(&self) -> usize {
+        self.as_ref().surface_count()
+    }
+
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        self.as_ref().surface(index)
+    }
+
+}
+
+/// The surface of a thing, which could be sampled.
+pub trait Surface: Sync {
+
+    /// Samples a point uniformly over the surface, returning the hit of a ray reaching its outer
+    /// side, against the normal, along with the area density of sampling it.
+    fn sample(&self) -> Option<(MaterialHit<'_>, f64)>;
+
+}
+
+impl<S: Surface + ?Sized> Surface for &S {
+
+    fn sample(&self) -> Option<(MaterialHit<'_>, f64)> {
+        (**self).sample()
+    }
+
 }
 
 pub struct MaterialHit<'a> {
@@ -30,4 +86,25 @@ pub struct MaterialHit<'a> {
     pub geometry: &'a dyn Geometry,
     pub texture: &'a dyn Texture,
     pub other_side_texture: &'a dyn Texture,
+    pub object_id: u32,
+    /// The index of the surface hit among the surfaces of the thing shot (see `Thing::surfaces`).
+    pub surface_index: usize,
+}
+
+/// Runs the given function, returning, along with its result, the number of atomic things that rays
+/// were tested against on the current thread while it ran, which tells how costly tracing is.
+pub fn counting_tests<R, F: FnOnce() -> R>(f: F) -> (R, u32) {
+    COUNTING.fetch_add(1, Ordering::Relaxed);
+    let before = TESTS.with(Cell::get);
+    let result = f();
+    let tests = TESTS.with(Cell::get).wrapping_sub(before);
+    COUNTING.fetch_sub(1, Ordering::Relaxed);
+    (result, tests)
+}
+
+#[inline]
+fn count_test() {
+    if COUNTING.load(Ordering::Relaxed) > 0 {
+        TESTS.with(|tests| tests.set(tests.get().wrapping_add(1)));
+    }
 }
@@ -0,0 +1,39 @@
+use crate::basic::rays::Ray;
+use crate::things::{MaterialHit, Surface, Thing};
+use crate::Tagged;
+
+impl<T: Thing> Thing for Tagged<T> {
+
+    fn shoot(&self, ray: &Ray, min: f64, max: f64) -> Option<MaterialHit<'_>> {
+        self.subject.shoot(ray, min, max).map(|hit| MaterialHit { object_id: self.id, ..hit })
+    }
+
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        self.subject.surfaces().into_iter()
+            .map(|surface| Box::new(TaggedSurface { surface, id: self.id }) as Box<dyn Surface>)
+            .collect()
+    }
+
+    fn surface_count(&self) -> usize {
+        self.subject.surface_count()
+    }
+
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        let surface = self.subject.surface(index)?;
+        Some(Box::new(TaggedSurface { surface, id: self.id }))
+    }
+
+}
+
+struct TaggedSurface<'a> {
+    surface: Box<dyn Surface + 'a>,
+    id: u32,
+}
+
+impl<'a> Surface for TaggedSurface<'a> {
+
+    fn sample(&self) -> Option<(MaterialHit<'_>, f64)> {
+        self.surface.sample().map(|(hit, pdf)| (MaterialHit { object_id: self.id, ..hit }, pdf))
+    }
+
+}
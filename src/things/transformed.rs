@@ -1,5 +1,6 @@
 use crate::basic::rays::Ray;
-use crate::things::{MaterialHit, Thing};
+use crate::basic::vectors::Dot;
+use crate::things::{MaterialHit, Surface, Thing};
 use crate::transforms::{Transformation, Transformed};
 
 impl<T: Thing, F: Transformation> Thing for Transformed<T, F> {
@@ -11,8 +12,42 @@ impl<T: Thing, F: Transformation> Thing for Transformed<T, F> {
             hit: self.transformation.to_global(&h.hit),
             geometry: h.geometry,
             texture: h.texture,
-            other_side_texture: h.other_side_texture
+            other_side_texture: h.other_side_texture,
+            object_id: h.object_id,
+            surface_index: h.surface_index,
         })
     }
 
+    fn surfaces(&self) -> Vec<Box<dyn Surface + '_>> {
+        self.subject.surfaces().into_iter()
+            .map(|surface| Box::new(TransformedSurface { surface, transformation: &self.transformation }) as Box<dyn Surface>)
+            .collect()
+    }
+
+    fn surface_count(&self) -> usize {
+        self.subject.surface_count()
+    }
+
+    fn surface(&self, index: usize) -> Option<Box<dyn Surface + '_>> {
+        let surface = self.subject.surface(index)?;
+        Some(Box::new(TransformedSurface { surface, transformation: &self.transformation }))
+    }
+
+}
+
+struct TransformedSurface<'a, F: Transformation> {
+    surface: Box<dyn Surface + 'a>,
+    transformation: &'a F,
+}
+
+impl<'a, F: Transformation> Surface for TransformedSurface<'a, F> {
+
+    /// Rescales the density the way transformed geometries do (see `Geometry::sample_surface`).
+    fn sample(&self) -> Option<(MaterialHit<'_>, f64)> {
+        let (hit, pdf) = self.surface.sample()?;
+        let global_hit = self.transformation.to_global(&hit.hit);
+        let scale = global_hit.normal.length() / hit.hit.normal.length();
+        Some((MaterialHit { hit: global_hit, ..hit }, pdf / scale))
+    }
+
 }
@@ -42,7 +42,7 @@ impl Transformation for Linear {
         let origin = matrix * &hit.incident_ray.origin;
         let direction = matrix * &hit.incident_ray.direction;
         let ray = hit.incident_ray.with_origin_and_direction(origin, direction);
-        hit.local_hit().transformed_as(ray, anti_matrix * &hit.normal)
+        hit.local_hit().transformed_as(ray, anti_matrix * &hit.normal, matrix * &hit.tangent)
     }
 
 }
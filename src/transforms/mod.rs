@@ -48,7 +48,7 @@ pub trait AffineTransformation: Transformation + Sized {
 
 }
 
-impl<T: Transformation> Transformation for Arc<T> {
+impl<T: Transformation + ?Sized> Transformation for Arc<T> {
 
     fn to_local(&self, ray: &Ray) -> Ray {
         self.as_ref().to_local(ray)
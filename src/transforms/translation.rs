@@ -26,7 +26,7 @@ impl Transformation for Translation {
     fn to_global(&self, hit: &Hit) -> Hit {
         let Translation(ref displacement) = self;
         let ray = hit.incident_ray.with_origin(&hit.incident_ray.origin + displacement);
-        hit.local_hit().transformed_as(ray, hit.normal)
+        hit.local_hit().transformed_as(ray, hit.normal, hit.tangent)
     }
 
 }
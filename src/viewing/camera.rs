@@ -1,25 +1,176 @@
+use rand::{Rng, RngExt};
+use rayon::prelude::*;
+
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::spectra::{terminate_secondary_wavelengths, Wavelengths};
+use crate::basic::vectors::Vec3D;
 use crate::filters::{Bloom, ImageFilter};
-use crate::imaging::Image;
-use crate::viewing::{CameraPixel, Exposure, Lens, Sensor};
-use crate::worlds::World;
+use crate::imaging::{Image, Layers};
+use crate::sampling::{reseed, rng};
+use crate::viewing::{CameraLink, CameraPixel, ChromaticAberration, Exposure, Film, FilmLink, Lens, Perspective, PixelLayers, Projection, ReconstructionFilter, Sensor};
+use crate::worlds::{FirstHit, World};
 
-pub struct Camera {
+pub struct Camera<P: Projection = Perspective> {
+    pub projection: P,
     pub lens: Lens,
     pub sensor: Sensor,
     pub exposure: Exposure,
     pub samples_per_pixel: u16,
 }
 
-impl Camera {
+impl<P: Projection> Camera<P> {
 
     pub fn shoot<W: World>(&self, world: &W, stack_size: u16, bloom_depth: u8) -> Image {
-        let stacked = Image::stack(
+        let stacked = self.shoot_stacked(
+            world,
             stack_size,
-            || self.shoot_linear(world),
             |counter| println!("Rendered {} frames out of {}", counter, stack_size)
         );
-        let bloom = Bloom { half_size: self.bloom_half_size(), depth: bloom_depth };
-        bloom.filter(&stacked).to_non_linear_space()
+        self.develop(&stacked, bloom_depth)
+    }
+
+    /// Renders `stack_size` frames, in parallel, and averages them into an image in linear space,
+    /// reporting the number of frames rendered so far whenever a frame is done.
+    pub fn shoot_stacked<W: World, F: Sync + Send + Fn(u16)>(&self, world: &W, stack_size: u16, progress: F) -> Image {
+        Image::stack(stack_size, |frame| self.shoot_linear(world, frame), progress)
+    }
+
+    /// Same as `shoot`, except that it reconstructs pixels from samples using the filter of the
+    /// given film, instead of averaging the samples falling within each pixel.
+    pub fn shoot_onto<W: World, F: ReconstructionFilter>(&self, world: &W, film: &Film<F>, stack_size: u16, bloom_depth: u8) -> Image {
+        for counter in 1 ..= stack_size {
+            self.expose(world, film);
+            println!("Rendered {} frames out of {}", counter, stack_size)
+        }
+        self.develop(&film.develop(1.0 / stack_size as f64), bloom_depth)
+    }
+
+    /// Adds to the given film `samples_per_pixel` samples for each pixel, in parallel, once the world
+    /// is prepared for the exposure (see `World::prepare`). Samples keep their indices from one
+    /// exposure to the next (see `World::trace_sample`). Worlds could also splat light onto the
+    /// film (see `World::trace_linked`), as much as one exposure's worth of light, which is why
+    /// films exposed multiple times need to be developed with a splats scale of one over the number
+    /// of exposures.
+    pub fn expose<W: World, F: ReconstructionFilter>(&self, world: &W, film: &Film<F>) {
+        let gain = self.sensor.gain;
+        let exposure = film.next_exposure();
+        world.prepare(exposure);
+        let link = FilmLink { camera: self, film, scale: gain / self.samples_per_pixel as f64 };
+        (0 .. film.height()).into_par_iter().for_each(|j| {
+            reseed(exposure, j as u64);
+            let mut rng = rng();
+            for i in 0 .. film.width() {
+                for s in 0 .. self.samples_per_pixel as usize {
+                    let x = (i as f64) + rng.random::<f64>();
+                    let y = (j as f64) + rng.random::<f64>();
+                    let ray = self.ray(&mut rng, &self.sensor.point(x, y));
+                    let sample = (j * film.width() + i) * self.samples_per_pixel as usize + s;
+                    let color = self.trace_sample(world, &ray, sample, &link);
+                    film.add_sample(x, y, &(color * gain));
+                }
+            }
+        });
+    }
+
+    /// Returns a ray landing at the given sensor point. Camera rays carry, in their color, the
+    /// weight with which they contribute to the image. Sensor points falling outside the image area
+    /// of the camera projection, or rays blocked by the lens barrel, produce black rays, which need
+    /// not be traced.
+    pub fn ray<R: Rng + ?Sized>(&self, rng: &mut R, sensor_point: &Vec3D) -> Ray {
+        let time = rng.sample(&self.exposure);
+        let wavelength = self.sensor.spectral.then(|| Wavelengths::sample(rng.random()).hero());
+        let (lens, sensor_point, color) = self.lens.disperse(rng, sensor_point, wavelength);
+        let lens_sample = rng.sample(lens.as_ref());
+
+        if !lens.transmits(&lens_sample, &sensor_point) {
+            return Ray::new(Vec3D::zero(), -Vec3D::Z, Color::BLACK, time)
+        }
+        match self.projection.ray(&sensor_point, &lens_sample, &lens) {
+            Some((origin, direction)) => Ray::new(origin, direction, color, time).with_wavelength(wavelength),
+            None => Ray::new(Vec3D::zero(), -Vec3D::Z, Color::BLACK, time),
+        }
+    }
+
+    /// Traces the given camera ray, returning its weighted contribution to the image. If the sensor
+    /// is spectral, the ray is traced at sampled wavelengths, whose radiance is converted to color.
+    pub fn trace<W: World>(&self, world: &W, ray: &Ray) -> Color {
+        self.trace_with(ray, |ray| world.trace(ray))
+    }
+
+    /// Same as `trace`, for the given sample of an exposure (see `World::trace_sample`), except that
+    /// the world could also splat light onto a film through the given link. Samples and links are
+    /// ignored by spectral sensors, as their statistics and splatted light are not observed at
+    /// sampled wavelengths.
+    fn trace_sample<W: World>(&self, world: &W, ray: &Ray, sample: usize, link: &dyn CameraLink) -> Color {
+        self.trace_with(ray, |ray| if self.sensor.spectral { world.trace(ray) } else { world.trace_sample(ray, sample, link) })
+    }
+
+    /// Same as `trace`, also returning the first hit of the ray (see `World::trace_with_first_hit`).
+    pub(crate) fn trace_with_first_hit<W: World>(&self, world: &W, ray: &Ray) -> (Color, Option<FirstHit>) {
+        let mut first_hit = None;
+        let color = self.trace_with(ray, |ray| {
+            let (color, hit) = world.trace_with_first_hit(ray);
+            first_hit = hit;
+            color
+        });
+        (color, first_hit)
+    }
+
+    /// Traces the given ray using the given function, at sampled wavelengths for spectral sensors,
+    /// along with the hero wavelength of the ray, if it has one. The paths of chromatically
+    /// aberrated rays only hold for their hero wavelength.
+    fn trace_with<F: FnOnce(&Ray) -> Color>(&self, ray: &Ray, trace: F) -> Color {
+        if ray.color == Color::BLACK {
+            Color::BLACK
+        } else if self.sensor.spectral {
+            let wavelengths = ray.wavelength.map_or_else(|| Wavelengths::sample(rng().random()), Wavelengths::of);
+            ray.color * wavelengths.observe(|hero| {
+                if self.lens.chromatic_aberration != ChromaticAberration::NONE {
+                    terminate_secondary_wavelengths();
+                }
+                trace(&ray.with_wavelength(Some(hero)))
+            })
+        } else {
+            ray.color * trace(ray)
+        }
+    }
+
+    pub(crate) fn develop(&self, linear: &Image, bloom_depth: u8) -> Image {
+        self.bloom(bloom_depth).filter(linear).to_non_linear_space()
+    }
+
+    /// Returns a bloom filter of the given depth, whose size suits the sensor resolution.
+    pub fn bloom(&self, depth: u8) -> Bloom {
+        Bloom { half_size: self.bloom_half_size(), depth }
+    }
+
+    /// Renders the world into a multi-layer image, in linear space, that includes auxiliary layers
+    /// besides the beauty layer.
+    pub fn shoot_layers<W: World>(&self, world: &W) -> Layers {
+        let width = self.sensor.width;
+        let height = self.sensor.height;
+        let gain = self.sensor.gain;
+        let rows: Vec<Vec<_>> = (0 .. height).into_par_iter()
+            .map(|j| (0 .. width).map(|i| {
+                reseed(0, (j * width + i) as u64);
+                self.pixel(i, j).estimate_layers(world, gain)
+            }).collect())
+            .collect();
+        let layer = |f: &dyn Fn(&PixelLayers) -> Color| Image::init(width, height, |i, j| f(&rows[j][i]));
+        let hit_layer = |f: &dyn Fn(&FirstHit) -> Color| layer(&|(_, _, hit)| hit.as_ref().map(f).unwrap_or(Color::BLACK));
+        let vector = |v: &Vec3D| Color::new(v.x(), v.y(), v.z());
+        Layers {
+            beauty: layer(&|(color, _, _)| *color),
+            variance: layer(&|(_, variance, _)| *variance),
+            depth: hit_layer(&|hit| Color::grey_shade(hit.depth)),
+            normal: hit_layer(&|hit| vector(&hit.normal)),
+            albedo: hit_layer(&|hit| hit.albedo),
+            surface_coordinates: hit_layer(&|hit| vector(&hit.surface_coordinates)),
+            object_id: hit_layer(&|hit| Color::grey_shade(hit.object_id as f64)),
+            material_id: hit_layer(&|hit| Color::grey_shade(hit.material_id as f64)),
+            motion: hit_layer(&|hit| vector(&hit.motion)),
+        }
     }
 
     fn bloom_half_size(&self) -> u8 {
@@ -28,17 +179,18 @@ impl Camera {
         (((max_res as f64).sqrt().round() as usize - 1) >> 1) as u8
     }
 
-    fn shoot_linear<W: World>(&self, world: &W) -> Image {
+    fn shoot_linear<W: World>(&self, world: &W, frame: u16) -> Image {
         let width = self.sensor.width;
         let height = self.sensor.height;
         let gain = self.sensor.gain / (self.samples_per_pixel as f64);
         Image::init(width, height, |i, j| {
+            reseed(frame as u64, (j * width + i) as u64);
             let pixel = self.pixel(i, j);
             pixel.estimate_color(world, gain)
         })
     }
 
-    fn pixel(&self, x: usize, y: usize) -> CameraPixel<'_> {
+    fn pixel(&self, x: usize, y: usize) -> CameraPixel<'_, P> {
         CameraPixel {
             camera: self,
             pixel: self.sensor.pixel(x, y)
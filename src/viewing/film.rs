@@ -0,0 +1,155 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::basic::colors::Color;
+use crate::imaging::Image;
+use crate::viewing::ReconstructionFilter;
+
+/// A film accumulates weighted color samples, each of which is splatted onto all the pixels within
+/// the radius of the reconstruction filter around it. The film is then developed into an image
+/// where each pixel is the normalized weighted sum of the samples around it.
+///
+/// Film positions are continuous, where the pixel at column `i` and row `j` covers the area
+/// `[i, i + 1) x [j, j + 1)`. Films can be shared by multiple threads.
+pub struct Film<F: ReconstructionFilter> {
+    pub filter: F,
+    width: usize,
+    height: usize,
+    rows: Vec<Mutex<Vec<FilmPixel>>>,
+    exposures: AtomicU64,
+}
+
+#[derive(Copy, Clone)]
+struct FilmPixel {
+    weighted_sum: Color,
+    weights_sum: f64,
+    splats: Color,
+}
+
+impl FilmPixel {
+
+    const EMPTY: Self = Self { weighted_sum: Color::BLACK, weights_sum: 0.0, splats: Color::BLACK };
+
+}
+
+impl<F: ReconstructionFilter> Film<F> {
+
+    pub fn new(width: usize, height: usize, filter: F) -> Self {
+        Self {
+            filter,
+            width,
+            height,
+            rows: (0..height).map(|_| Mutex::new(vec![FilmPixel::EMPTY; width])).collect(),
+            exposures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Adds a sample at the given film position, weighting it for each pixel in its vicinity using
+    /// the reconstruction filter.
+    pub fn add_sample(&self, x: f64, y: f64, color: &Color) {
+        let radius = self.filter.radius();
+        let (columns, rows) = (self.span(x, radius, self.width), self.span(y, radius, self.height));
+        for row in rows {
+            let dy = (row as f64) + 0.5 - y;
+            let mut pixels = self.rows[row].lock().unwrap();
+            for column in columns.clone() {
+                let dx = (column as f64) + 0.5 - x;
+                let weight = self.filter.weight(dx, dy);
+                if weight != 0.0 {
+                    let pixel = &mut pixels[column];
+                    pixel.weighted_sum += color * weight;
+                    pixel.weights_sum += weight;
+                }
+            }
+        }
+    }
+
+    /// Adds a color directly to the pixel containing the given film position, without weighting or
+    /// normalization. Splats are scaled by the factor passed to `develop`, which is useful for
+    /// contributions that are not tied to any particular pixel sample, like light tracing ones.
+    pub fn add_splat(&self, x: f64, y: f64, color: &Color) {
+        if x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64 {
+            let mut pixels = self.rows[y as usize].lock().unwrap();
+            pixels[x as usize].splats += color;
+        }
+    }
+
+    /// Produces an image out of the accumulated samples, adding the splats scaled by the given
+    /// factor.
+    pub fn develop(&self, splats_scale: f64) -> Image {
+        let rows: Vec<Vec<FilmPixel>> = self.rows.iter()
+            .map(|row| row.lock().unwrap().clone())
+            .collect();
+        Image::init(self.width, self.height, |i, j| {
+            let pixel = &rows[j][i];
+            let color = if pixel.weights_sum.abs() > f64::EPSILON {
+                pixel.weighted_sum / pixel.weights_sum
+            } else {
+                Color::BLACK
+            };
+            color + pixel.splats * splats_scale
+        })
+    }
+
+    /// Counts a new exposure of the film, returning its index.
+    pub(crate) fn next_exposure(&self) -> u64 {
+        self.exposures.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn span(&self, center: f64, radius: f64, limit: usize) -> Range<usize> {
+        let first = (center - radius - 0.5).ceil().max(0.0) as usize;
+        let last = ((center + radius - 0.5).floor() + 1.0).clamp(0.0, limit as f64) as usize;
+        first..last.max(first)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::colors::tests::color;
+    use crate::viewing::{BlackmanHarrisFilter, BoxFilter, GaussianFilter, LanczosFilter, MitchellNetravaliFilter};
+
+    use super::*;
+
+    fn assert_develops_into<F: ReconstructionFilter>(filter: F, shade: &Color) {
+        let film = Film::new(8, 8, filter);
+        for j in 0..32 {
+            for i in 0..32 {
+                film.add_sample((i as f64 + 0.5) / 4.0, (j as f64 + 0.5) / 4.0, shade);
+            }
+        }
+
+        let image = film.develop(1.0);
+
+        for ref p in image.pixel_position_iterator() {
+            for c in 0..3 {
+                assert!((image[p][c] - shade[c]).abs() < 1e-9);
+            }
+        }
+    }
+
+    proptest! {
+
+        #[test]
+        fn develops_uniform_samples_into_uniform_images(shade in color(), radius in 0.5..3.0) {
+            assert_develops_into(GaussianFilter { radius, alpha: 2.0 }, &shade);
+            assert_develops_into(MitchellNetravaliFilter::recommended(radius), &shade);
+            assert_develops_into(BlackmanHarrisFilter { radius }, &shade);
+            assert_develops_into(LanczosFilter { radius }, &shade);
+            assert_develops_into(BoxFilter { radius }, &shade);
+        }
+
+    }
+
+}
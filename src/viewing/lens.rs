@@ -1,13 +1,40 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
 use rand::prelude::Distribution;
 use rand::{Rng, RngExt};
 
-use crate::basic::vectors::Vec3D;
-use crate::sampling::UniformSolidUnitCircle;
+use crate::basic::colors::Color;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::sampling::{ImageDistribution, UniformSolidPolygon, UniformSolidUnitCircle};
 
+#[derive(Clone)]
 pub struct Lens {
     pub aperture: f64,
     pub focal_length: f64,
     pub focal_plane_ratio: f64,
+    pub aperture_shape: ApertureShape,
+    pub vignetting: f64,
+    pub chromatic_aberration: ChromaticAberration,
+}
+
+/// The shape of the lens aperture, which determines the shape of the bokeh of out-of-focus
+/// highlights. Circular and polygonal shapes are inscribed in the unit circle, while image masks are
+/// stretched over the square enclosing it. Shapes are then scaled by the lens aperture.
+#[derive(Clone)]
+pub enum ApertureShape {
+    Circular,
+    Polygonal(UniformSolidPolygon),
+    Masked(Arc<ImageDistribution>),
+}
+
+/// Lateral and axial chromatic aberration, expressed as the relative changes in magnification and
+/// in focal plane distance, respectively, of the blue channel. The red channel has the opposite
+/// changes, while the green channel is the reference.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChromaticAberration {
+    pub lateral: f64,
+    pub axial: f64,
 }
 
 impl Lens {
@@ -20,20 +47,109 @@ impl Lens {
         Self {
             aperture,
             focal_length,
-            focal_plane_ratio: focal_plane_distance / focal_length
+            focal_plane_ratio: focal_plane_distance / focal_length,
+            aperture_shape: ApertureShape::Circular,
+            vignetting: 0.0,
+            chromatic_aberration: ChromaticAberration::NONE,
+        }
+    }
+
+    pub fn with_aperture_shape(self, aperture_shape: ApertureShape) -> Self {
+        Self { aperture_shape, ..self }
+    }
+
+    /// Sets the strength of the optical (cat-eye) vignetting, which is the relative displacement of
+    /// the lens barrel opening from the aperture, per unit of distance from the sensor center.
+    pub fn with_vignetting(self, vignetting: f64) -> Self {
+        Self { vignetting, ..self }
+    }
+
+    pub fn with_chromatic_aberration(self, lateral: f64, axial: f64) -> Self {
+        Self { chromatic_aberration: ChromaticAberration { lateral, axial }, ..self }
+    }
+
+    pub fn focal_plane_distance(&self) -> f64 {
+        self.focal_length * self.focal_plane_ratio
+    }
+
+    /// Tells whether a ray passing through the given lens sample, and landing at the given sensor
+    /// point, makes it through the lens barrel. Off-axis rays get clipped by the barrel opening,
+    /// giving out-of-focus highlights near the image corners their cat-eye look.
+    pub fn transmits(&self, lens_sample: &Vec3D, sensor_point: &Vec3D) -> bool {
+        if self.vignetting == 0.0 {
+            return true
         }
+        let field_position = Vec3D::new(sensor_point.x(), sensor_point.y(), 0.0);
+        let barrel_center = field_position * (self.vignetting * self.aperture);
+        (lens_sample - &barrel_center).length() <= self.aperture
+    }
+
+    /// Returns the lens as seen by a ray of the given wavelength, if any, landing at the given sensor
+    /// point, along with the displaced sensor point, and the ray weight. Rays traced in RGB pick one
+    /// of the color channels at random instead.
+    pub fn disperse<R: Rng + ?Sized>(&self, rng: &mut R, sensor_point: &Vec3D, wavelength: Option<f64>) -> (Cow<'_, Lens>, Vec3D, Color) {
+        if self.chromatic_aberration == ChromaticAberration::NONE {
+            return (Cow::Borrowed(self), *sensor_point, Color::WHITE)
+        }
+        if let Some(wavelength) = wavelength {
+            let (lens, point) = self.shifted(aberration_shift(wavelength), sensor_point);
+            return (Cow::Owned(lens), point, Color::WHITE)
+        }
+        let channel = rng.random_range(0..3usize);
+        let shift = channel as f64 - 1.0;
+        let mut color = Color::BLACK;
+        color[channel] = 3.0;
+        let (lens, point) = self.shifted(shift, sensor_point);
+        (Cow::Owned(lens), point, color)
+    }
+
+    /// Returns the lens and sensor point as seen by light whose aberration shift is the given one,
+    /// where `-1`, `0`, and `1` correspond to red, green, and blue, respectively.
+    pub fn shifted(&self, shift: f64, sensor_point: &Vec3D) -> (Lens, Vec3D) {
+        let ChromaticAberration { lateral, axial } = self.chromatic_aberration;
+        let lens = Self {
+            focal_plane_ratio: self.focal_plane_ratio * (1.0 + shift * axial),
+            ..self.clone()
+        };
+        let magnification = 1.0 + shift * lateral;
+        let point = Vec3D::new(sensor_point.x() * magnification, sensor_point.y() * magnification, sensor_point.z());
+        (lens, point)
     }
 
 }
 
+impl ChromaticAberration {
+
+    pub const NONE: Self = Self { lateral: 0.0, axial: 0.0 };
+
+}
+
+/// Returns the aberration shift (see `Lens::shifted`) of light of the given wavelength, in
+/// nanometers, which goes linearly from `-1` at 630 nm (red) to `1` at 470 nm (blue).
+fn aberration_shift(wavelength: f64) -> f64 {
+    (550.0 - wavelength) / 80.0
+}
+
 impl Distribution<Vec3D> for Lens {
 
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3D {
         if self.aperture != 0.0 {
-            self.aperture * rng.sample(UniformSolidUnitCircle)
+            self.aperture * rng.sample(&self.aperture_shape)
         } else {
             Vec3D::zero()
         }
     }
 
 }
+
+impl Distribution<Vec3D> for ApertureShape {
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3D {
+        match self {
+            ApertureShape::Circular => rng.sample(UniformSolidUnitCircle),
+            ApertureShape::Polygonal(polygon) => rng.sample(polygon),
+            ApertureShape::Masked(mask) => rng.sample(mask.as_ref()),
+        }
+    }
+
+}
@@ -0,0 +1,83 @@
+use rand::RngExt;
+
+use crate::basic::colors::Color;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::sampling::rng;
+use crate::viewing::{Camera, Film, Projection, ReconstructionFilter};
+
+/// The camera, as seen from within a world while the camera exposes a film. Worlds could use it to
+/// connect points they reach by tracing light from light sources to the camera, and to splat the
+/// light those points send through the lens onto the film, as light tracing does.
+pub trait CameraLink: Sync {
+
+    /// Samples a point on the lens, and links it to the given point, unless the given point cannot
+    /// be seen from the sampled lens point.
+    fn link(&self, point: &Vec3D) -> Option<LensLink>;
+
+    /// The solid angle density of camera rays leaving the given lens point along the given
+    /// direction.
+    fn pdf(&self, lens_point: &Vec3D, direction: &Vec3D) -> f64;
+
+    /// Adds to the film the given light, arriving at the lens along the given link.
+    fn splat(&self, link: &LensLink, color: &Color);
+
+}
+
+/// A link between a point on the lens, and a point seen from it, which is at the given distance,
+/// along the given unit direction. The importance of the link is the density of camera rays
+/// leaving the lens point along its direction, which is the factor by which the light arriving
+/// along it is scaled (besides the distance falloff) when splatted onto the film.
+#[derive(Clone, Debug)]
+pub struct LensLink {
+    pub lens_point: Vec3D,
+    pub direction: Vec3D,
+    pub distance: f64,
+    pub importance: f64,
+    pub film_position: (f64, f64),
+}
+
+/// The link to a camera exposing a film, splatting light with the given scale.
+pub(crate) struct FilmLink<'a, P: Projection, F: ReconstructionFilter> {
+    pub camera: &'a Camera<P>,
+    pub film: &'a Film<F>,
+    pub scale: f64,
+}
+
+impl<'a, P: Projection, F: ReconstructionFilter> FilmLink<'a, P, F> {
+
+    /// Maps the ray leaving the given lens point along the given direction to the film position it
+    /// comes from, along with its density, unless it comes from outside the film.
+    fn film_position(&self, lens_point: &Vec3D, direction: &Vec3D) -> Option<((f64, f64), f64)> {
+        let camera = self.camera;
+        let (sensor_point, density) = camera.projection.sensor_point(lens_point, direction, &camera.lens)?;
+        let (x, y) = camera.sensor.film_position(&sensor_point);
+        if x >= 0.0 && y >= 0.0 && x < self.film.width() as f64 && y < self.film.height() as f64 {
+            Some(((x, y), density / camera.sensor.area()))
+        } else {
+            None
+        }
+    }
+
+}
+
+impl<'a, P: Projection, F: ReconstructionFilter> CameraLink for FilmLink<'a, P, F> {
+
+    fn link(&self, point: &Vec3D) -> Option<LensLink> {
+        let lens_point = rng().sample(&self.camera.lens);
+        let offset = point - &lens_point;
+        let distance = offset.length();
+        let direction = offset / distance;
+        let (film_position, importance) = self.film_position(&lens_point, &direction)?;
+        Some(LensLink { lens_point, direction, distance, importance, film_position })
+    }
+
+    fn pdf(&self, lens_point: &Vec3D, direction: &Vec3D) -> f64 {
+        self.film_position(lens_point, direction).map_or(0.0, |(_, pdf)| pdf)
+    }
+
+    fn splat(&self, link: &LensLink, color: &Color) {
+        let (x, y) = link.film_position;
+        self.film.add_splat(x, y, &(color * self.scale));
+    }
+
+}
@@ -0,0 +1,214 @@
+use rand::rngs::SmallRng;
+use rand::{RngExt, SeedableRng};
+use rayon::prelude::*;
+
+use crate::basic::colors::Color;
+use crate::sampling::{mix, random, replay, reseed, rng, PrimarySamples};
+use crate::viewing::{Camera, Film, Projection, ReconstructionFilter};
+use crate::worlds::World;
+
+/// Renders images using primary sample space Metropolis light transport, which samples paths in
+/// proportion to their brightness, using chains of paths, each mutated out of the previous one, so
+/// that the paths that are hard to find (e.g. light reaching the camera through narrow gaps, or
+/// caustics seen in mirrors) get explored once found. Paths are traced from the camera into the
+/// world (e.g. a `PathTraced` one), with all the random decisions, including the film positions,
+/// replayed from primary samples (see `replay`), which the chains mutate.
+///
+/// The brightness of the image is estimated out of the given number of independent bootstrap
+/// paths, out of which the given number of chains start, picked in proportion to their brightness.
+/// Mutations are either large steps, taken with the given probability, which redraw all the
+/// samples, or small steps, which perturb them by the given standard deviation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metropolis {
+    pub bootstrap_samples: usize,
+    pub chains: usize,
+    pub sigma: f64,
+    pub large_step_probability: f64,
+}
+
+impl Default for Metropolis {
+
+    fn default() -> Self {
+        Self { bootstrap_samples: 100_000, chains: 1000, sigma: 0.01, large_step_probability: 0.3 }
+    }
+
+}
+
+impl Metropolis {
+
+    /// Starts the chains rendering the given world, as seen by the given camera, once the
+    /// brightness of the image is estimated, out of the bootstrap paths.
+    pub fn start<'a, P: Projection, W: World>(&self, camera: &'a Camera<P>, world: &'a W) -> MetropolisChains<'a, P, W> {
+        let sampler = PathSampler { camera, world };
+        reseed(u64::MAX, 0);
+        let base_seed: u64 = random();
+        let seed = |index: usize| mix(base_seed ^ index as u64);
+        let brightnesses: Vec<f64> = (0 .. self.bootstrap_samples).into_par_iter()
+            .map(|index| sampler.sample(&mut self.samples(seed(index))).brightness)
+            .collect();
+        let mut cumulative_brightnesses = Vec::with_capacity(brightnesses.len());
+        let mut total = 0.0;
+        for brightness in brightnesses {
+            total += brightness;
+            cumulative_brightnesses.push(total);
+        }
+        if total <= 0.0 {
+            return MetropolisChains { sampler, chains: Vec::new(), brightness: 0.0 }
+        }
+        let starts: Vec<usize> = (0 .. self.chains)
+            .map(|_| {
+                let dice = random::<f64>() * total;
+                cumulative_brightnesses.partition_point(|&brightness| brightness <= dice).min(self.bootstrap_samples - 1)
+            })
+            .collect();
+        // the chains replay the bootstrap paths they start from, drawing the same primary samples
+        let chains = starts.into_par_iter()
+            .map(|index| {
+                let mut samples = self.samples(seed(index));
+                let current = sampler.sample(&mut samples);
+                Chain { samples, rng: SmallRng::seed_from_u64(mix(seed(index))), current }
+            })
+            .collect();
+        MetropolisChains { sampler, chains, brightness: total / self.bootstrap_samples as f64 }
+    }
+
+    fn samples(&self, seed: u64) -> PrimarySamples {
+        PrimarySamples::new(seed, self.sigma, self.large_step_probability)
+    }
+
+}
+
+/// The chains of paths rendering a world, as seen by a camera (see `Metropolis`).
+pub struct MetropolisChains<'a, P: Projection, W: World> {
+    sampler: PathSampler<'a, P, W>,
+    chains: Vec<Chain>,
+    brightness: f64,
+}
+
+impl<'a, P: Projection, W: World> MetropolisChains<'a, P, W> {
+
+    /// The average brightness of the image, as estimated out of the bootstrap paths.
+    pub fn brightness(&self) -> f64 {
+        self.brightness
+    }
+
+    /// Mutates the chains, in parallel, making as many mutations in total as the sensor has pixels,
+    /// and splats the paths onto the given film, which gets as much light as one exposure of the
+    /// camera gives it (see `Camera::expose`). The light of every mutation is split between the
+    /// proposed path and the current one, in proportion to the probability of accepting the former.
+    pub fn expose<F: ReconstructionFilter>(&mut self, film: &Film<F>) {
+        let exposure = film.next_exposure();
+        self.sampler.world.prepare(exposure);
+        if self.chains.is_empty() {
+            return
+        }
+        let sensor = &self.sampler.camera.sensor;
+        let pixels = sensor.width * sensor.height;
+        let mutations = pixels.div_ceil(self.chains.len());
+        let scale = self.brightness * pixels as f64 / (mutations * self.chains.len()) as f64;
+        let sampler = &self.sampler;
+        self.chains.par_iter_mut().for_each(|chain| {
+            // the current paths were traced before the world got prepared for this exposure
+            chain.samples.replay_iteration();
+            chain.current = sampler.sample(&mut chain.samples);
+            for _ in 0 .. mutations {
+                chain.samples.start_iteration();
+                let proposed = sampler.sample(&mut chain.samples);
+                let current = &chain.current;
+                let acceptance = if current.brightness > 0.0 { (proposed.brightness / current.brightness).min(1.0) } else { 1.0 };
+                if proposed.brightness > 0.0 {
+                    film.add_splat(proposed.x, proposed.y, &(proposed.color * (acceptance * scale / proposed.brightness)));
+                }
+                if current.brightness > 0.0 {
+                    film.add_splat(current.x, current.y, &(current.color * ((1.0 - acceptance) * scale / current.brightness)));
+                }
+                if chain.rng.random::<f64>() < acceptance {
+                    chain.current = proposed;
+                    chain.samples.accept();
+                } else {
+                    chain.samples.reject();
+                }
+            }
+        });
+    }
+
+}
+
+struct Chain {
+    samples: PrimarySamples,
+    /// The generator deciding whether to accept the proposed paths, which are not part of them.
+    rng: SmallRng,
+    current: PathSample,
+}
+
+/// The light a path brings to the given film position, along with its brightness.
+struct PathSample {
+    x: f64,
+    y: f64,
+    color: Color,
+    brightness: f64,
+}
+
+struct PathSampler<'a, P: Projection, W: World> {
+    camera: &'a Camera<P>,
+    world: &'a W,
+}
+
+impl<'a, P: Projection, W: World> PathSampler<'a, P, W> {
+
+    /// Traces the path that the given primary samples make, from a film position that the first
+    /// two samples pick.
+    fn sample(&self, samples: &mut PrimarySamples) -> PathSample {
+        let sensor = &self.camera.sensor;
+        replay(samples, || {
+            let mut rng = rng();
+            let x = rng.random::<f64>() * sensor.width as f64;
+            let y = rng.random::<f64>() * sensor.height as f64;
+            let ray = self.camera.ray(&mut rng, &sensor.point(x, y));
+            let color = self.camera.trace(self.world, &ray) * sensor.gain;
+            PathSample { x, y, color, brightness: color.luminance().max(0.0) }
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::rays::Ray;
+    use crate::viewing::{BoxFilter, Exposure, Lens, Perspective, Sensor};
+    use crate::worlds::WorldFunction;
+
+    use super::*;
+
+    #[test]
+    fn converges_to_the_estimated_brightness() {
+        let camera = Camera {
+            projection: Perspective,
+            lens: Lens::ideal(1.0),
+            sensor: Sensor::new(16, 16, 1.0),
+            exposure: Exposure(0.0),
+            samples_per_pixel: 1,
+        };
+        // half of the image is white, the other half is black
+        let world: WorldFunction = |ray: &Ray| if ray.direction.x() > 0.0 { Color::WHITE } else { Color::BLACK };
+        let metropolis = Metropolis { bootstrap_samples: 10_000, chains: 64, ..Metropolis::default() };
+        let mut chains = metropolis.start(&camera, &world);
+        let film = Film::new(16, 16, BoxFilter { radius: 0.5 });
+        for _ in 0 .. 64 {
+            chains.expose(&film);
+        }
+
+        let image = film.develop(1.0 / 64.0);
+        let mean = image.pixel_position_iterator()
+            .map(|ref p| image[p].luminance())
+            .sum::<f64>() / 256.0;
+        assert!((chains.brightness() - 0.5).abs() < 0.02, "{}", chains.brightness());
+        assert!((mean - chains.brightness()).abs() < 1e-6, "{} {}", mean, chains.brightness());
+        let bright = image.pixel_position_iterator()
+            .filter(|p| p.column >= 8)
+            .map(|ref p| image[p].luminance())
+            .sum::<f64>() / 128.0;
+        assert!((bright - 1.0).abs() < 0.1, "{}", bright);
+    }
+
+}
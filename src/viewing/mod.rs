@@ -1,7 +1,12 @@
 pub use camera::*;
 pub use exposure::*;
+pub use film::*;
 pub use lens::*;
+pub use link::*;
+pub use metropolis::*;
 pub use pixel::*;
+pub use projections::*;
+pub use reconstruction::*;
 pub use sensor::*;
 
 mod camera;
@@ -9,4 +14,9 @@ mod lens;
 mod sensor;
 mod exposure;
 mod pixel;
+mod projections;
+mod film;
+mod link;
+mod metropolis;
+mod reconstruction;
 
@@ -1,42 +1,90 @@
 use rand::prelude::Distribution;
-use rand::{rng, Rng, RngExt};
+use rand::{Rng, RngExt};
 
 use crate::basic::colors::Color;
 use crate::basic::rays::Ray;
 use crate::basic::vectors::Vec3D;
-use crate::sampling::UniformSolidUnitSquare;
-use crate::viewing::Camera;
-use crate::worlds::World;
+use crate::sampling::{rng, UniformSolidUnitSquare};
+use crate::viewing::{Camera, Projection};
+use crate::worlds::{FirstHit, World};
 
-pub struct CameraPixel<'a> {
-    pub camera: &'a Camera,
+/// The estimated color of a pixel, its variance, and the average first hit of its samples.
+pub type PixelLayers = (Color, Color, Option<FirstHit>);
+
+pub struct CameraPixel<'a, P: Projection> {
+    pub camera: &'a Camera<P>,
     pub pixel: Pixel
 }
 
-impl<'a> CameraPixel<'a> {
+impl<'a, P: Projection> CameraPixel<'a, P> {
 
     pub fn estimate_color<W: World>(&self, world: &W, gain: f64) -> Color {
         let mut color = Color::BLACK;
         for _ in 0u16 .. self.camera.samples_per_pixel {
             let ray = rng().sample(self);
-            color += world.trace(&ray);
+            color += self.camera.trace(world, &ray);
         }
         color * gain
     }
 
+    /// Estimates the pixel color, along with its variance, and the average of the auxiliary
+    /// information about the first surfaces hit by the pixel samples. Identifiers are taken from
+    /// the first sample that hits a surface.
+    pub fn estimate_layers<W: World>(&self, world: &W, gain: f64) -> PixelLayers {
+        let mut sum = Color::BLACK;
+        let mut squares_sum = Color::BLACK;
+        let mut first_hit: Option<FirstHit> = None;
+        let mut hits = 0;
+        for _ in 0u16 .. self.camera.samples_per_pixel {
+            let ray = rng().sample(self);
+            if ray.color == Color::BLACK {
+                continue
+            }
+            let (color, hit) = self.camera.trace_with_first_hit(world, &ray);
+            let color = color * gain;
+            sum += color;
+            squares_sum += color * color;
+            if let Some(hit) = hit {
+                hits += 1;
+                first_hit = Some(match first_hit {
+                    None => hit,
+                    Some(h) => FirstHit {
+                        depth: h.depth + hit.depth,
+                        position: h.position + hit.position,
+                        normal: h.normal + hit.normal,
+                        albedo: h.albedo + hit.albedo,
+                        surface_coordinates: h.surface_coordinates + hit.surface_coordinates,
+                        motion: h.motion + hit.motion,
+                        ..h
+                    }
+                });
+            }
+        }
+        let n = self.camera.samples_per_pixel as f64;
+        let mean = sum / n;
+        let variance = (squares_sum / n - mean * mean) / n;
+        let average_hit = first_hit.map(|h| {
+            let ratio = 1.0 / (hits as f64);
+            FirstHit {
+                depth: h.depth * ratio,
+                position: h.position * ratio,
+                normal: (h.normal * ratio).unit(),
+                albedo: h.albedo * ratio,
+                surface_coordinates: h.surface_coordinates * ratio,
+                motion: h.motion * ratio,
+                ..h
+            }
+        });
+        (mean, variance, average_hit)
+    }
+
 }
 
-impl<'a> Distribution<Ray> for CameraPixel<'a> {
+impl<'a, P: Projection> Distribution<Ray> for CameraPixel<'a, P> {
 
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Ray {
-        let lens_sample = rng.sample(&self.camera.lens);
         let pixel_sample = rng.sample(&self.pixel);
-        let time = rng.sample(&self.camera.exposure);
-
-        let teleported_pixel_sample = Vec3D::new(pixel_sample.x(), pixel_sample.y(), -self.camera.lens.focal_length);
-        let focal_plane_sample = teleported_pixel_sample * self.camera.lens.focal_plane_ratio;
-        let direction = focal_plane_sample - lens_sample;
-        Ray::new(lens_sample, direction, Color::WHITE, time)
+        self.camera.ray(rng, &pixel_sample)
     }
 
 }
@@ -0,0 +1,74 @@
+use std::f64::consts::FRAC_PI_2;
+
+use crate::basic::vectors::Vec3D;
+use crate::viewing::{Lens, Projection};
+
+/// A full 360 degrees panoramic projection, where the horizontal sensor axis maps linearly to the
+/// longitude, and the vertical sensor axis maps linearly to the latitude. Both axes have the same
+/// angular resolution, so a sensor with a 2:1 aspect ratio captures the entire sphere of
+/// directions, with the center of the image looking towards the negative z-axis.
+pub struct Equirectangular;
+
+impl Equirectangular {
+
+    pub(crate) fn direction_at(longitude: f64, latitude: f64) -> Vec3D {
+        let (sin_lon, cos_lon) = longitude.sin_cos();
+        let (sin_lat, cos_lat) = latitude.sin_cos();
+        Vec3D::new(cos_lat * sin_lon, sin_lat, -cos_lat * cos_lon)
+    }
+
+}
+
+impl Projection for Equirectangular {
+
+    fn direction(&self, sensor_point: &Vec3D, _: &Lens) -> Option<Vec3D> {
+        let longitude = sensor_point.x() * FRAC_PI_2;
+        let latitude = sensor_point.y() * FRAC_PI_2;
+        Some(Self::direction_at(longitude, latitude))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::Dot;
+
+    use super::*;
+
+    /// Returns the sensor point seen along the given unit direction.
+    fn sensor_point_of(direction: &Vec3D) -> Vec3D {
+        let longitude = direction.x().atan2(-direction.z());
+        let latitude = direction.y().clamp(-1.0, 1.0).asin();
+        Vec3D::new(longitude / FRAC_PI_2, latitude / FRAC_PI_2, 0.0)
+    }
+
+    proptest! {
+
+        #[test]
+        fn covers_the_whole_sphere_of_directions(x in -1.0..1.0, y in -1.0..1.0, z in -1.0..1.0) {
+            let direction = Vec3D::new(x, y, z);
+            prop_assume!(direction.length() > 0.1);
+            let direction = direction.unit();
+
+            let sensor_point = sensor_point_of(&direction);
+
+            assert!(sensor_point.x().abs() <= 2.0 && sensor_point.y().abs() <= 1.0, "{:?}", sensor_point);
+            let seen = Equirectangular.direction(&sensor_point, &Lens::ideal(1.0)).unwrap();
+            assert!(seen.dot(direction) > 1.0 - 1e-9, "{:?} vs {:?}", seen, direction);
+        }
+
+        #[test]
+        fn maps_sensor_points_back_from_their_directions(x in -1.99..1.99, y in -0.99..0.99) {
+            let direction = Equirectangular.direction(&Vec3D::new(x, y, 0.0), &Lens::ideal(1.0)).unwrap();
+
+            let sensor_point = sensor_point_of(&direction);
+
+            assert!((direction.length() - 1.0).abs() < 1e-9);
+            assert!((sensor_point - Vec3D::new(x, y, 0.0)).length() < 1e-9, "{:?}", sensor_point);
+        }
+
+    }
+
+}
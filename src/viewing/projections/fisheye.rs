@@ -0,0 +1,88 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::viewing::{Lens, Projection};
+
+/// A fisheye projection, which maps a circular image area, inscribed in the sensor, to a cone of
+/// directions whose apex angle is the specified field of view (in radians). The field of view
+/// could exceed `PI` to look behind the camera.
+pub struct Fisheye {
+    pub field_of_view: f64,
+    pub mapping: FisheyeMapping,
+}
+
+/// The mapping between the distance `r` of a sensor point from the image center, and the angle
+/// `theta` between the corresponding ray and the viewing direction.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FisheyeMapping {
+    /// `r` is proportional to `theta`.
+    Equidistant,
+    /// `r` is proportional to `sin(theta / 2)`, which preserves solid angles.
+    Equisolid,
+}
+
+impl Projection for Fisheye {
+
+    fn direction(&self, sensor_point: &Vec3D, _: &Lens) -> Option<Vec3D> {
+        let radial = Vec3D::new(sensor_point.x(), sensor_point.y(), 0.0);
+        let r = radial.length();
+        if r > 1.0 {
+            return None
+        }
+        let half_fov = self.field_of_view / 2.0;
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => r * half_fov,
+            FisheyeMapping::Equisolid => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+        };
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let sideways = if r > 0.0 { radial * (sin_theta / r) } else { Vec3D::zero() };
+        Some(sideways - Vec3D::along_z(cos_theta))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use proptest::*;
+
+    use crate::rough_equality;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn equidistant_angles_are_proportional_to_distance_from_center(x in -0.7..0.7, y in -0.7..0.7, fov in 0.5..6.0) {
+            let fisheye = Fisheye { field_of_view: fov, mapping: FisheyeMapping::Equidistant };
+
+            let direction = fisheye.direction(&Vec3D::new(x, y, 0.0), &Lens::ideal(1.0)).unwrap();
+
+            let theta = direction.dot(-Vec3D::Z).acos();
+            let r = (x * x + y * y).sqrt();
+            assert!(rough_equality(direction.length(), 1.0));
+            assert!((theta - r * fov / 2.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn equisolid_angles_preserve_solid_angles(x in -0.7..0.7, y in -0.7..0.7, fov in 0.5..6.0) {
+            let fisheye = Fisheye { field_of_view: fov, mapping: FisheyeMapping::Equisolid };
+
+            let direction = fisheye.direction(&Vec3D::new(x, y, 0.0), &Lens::ideal(1.0)).unwrap();
+
+            let theta = direction.dot(-Vec3D::Z).acos();
+            let r = (x * x + y * y).sqrt();
+            assert!(((theta / 2.0).sin() - r * (fov / 4.0).sin()).abs() < 1e-9);
+        }
+
+        #[test]
+        fn points_outside_the_image_circle_have_no_direction(angle in 0.0..TAU, r in 1.01..2.0f64) {
+            let fisheye = Fisheye { field_of_view: 3.0, mapping: FisheyeMapping::Equidistant };
+
+            let direction = fisheye.direction(&Vec3D::new(r * angle.cos(), r * angle.sin(), 0.0), &Lens::ideal(1.0));
+
+            assert!(direction.is_none());
+        }
+
+    }
+
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+pub use equirectangular::*;
+pub use fisheye::*;
+pub use orthographic::*;
+pub use perspective::*;
+pub use stereo::*;
+
+use crate::basic::matrices::Matrix;
+use crate::basic::vectors::Vec3D;
+use crate::viewing::Lens;
+
+mod perspective;
+mod orthographic;
+mod fisheye;
+mod equirectangular;
+mod stereo;
+
+/// A projection maps points on the camera sensor to rays in the camera space, where the camera sits
+/// at the origin looking towards the negative z-axis, with the y-axis pointing upwards.
+///
+/// Sensor points have their `x` component in the range `[-aspect, aspect]`, and their `y` component
+/// in the range `[-1, 1]`, where `aspect` is the width-to-height ratio of the sensor.
+pub trait Projection: Send + Sync {
+
+    /// Returns the direction of the ray passing through the center of the lens and the given sensor
+    /// point, or `None` if the sensor point falls outside the image area of the projection.
+    fn direction(&self, sensor_point: &Vec3D, lens: &Lens) -> Option<Vec3D>;
+
+    /// Returns the origin and direction of the ray passing through the given lens sample and the
+    /// given sensor point. By default, the lens is placed perpendicular to the ray passing through
+    /// its center, so that all rays through the same sensor point meet at the focal plane.
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        self.direction(sensor_point, lens)
+            .map(|direction| thin_lens_ray(&Vec3D::zero(), &direction, lens_sample, lens))
+    }
+
+    /// Maps the ray leaving the given lens sample along the given direction back to the sensor
+    /// point it comes from, along with the density of sensor points per unit solid angle around
+    /// that direction. Projections that cannot tell, which is the default, return `None`, which
+    /// prevents worlds from connecting arbitrary points to the camera (e.g. for light tracing).
+    fn sensor_point(&self, _lens_sample: &Vec3D, _direction: &Vec3D, _lens: &Lens) -> Option<(Vec3D, f64)> {
+        None
+    }
+
+}
+
+impl<P: Projection + ?Sized> Projection for Box<P> {
+
+    fn direction(&self, sensor_point: &Vec3D, lens: &Lens) -> Option<Vec3D> {
+        self.as_ref().direction(sensor_point, lens)
+    }
+
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        self.as_ref().ray(sensor_point, lens_sample, lens)
+    }
+
+    fn sensor_point(&self, lens_sample: &Vec3D, direction: &Vec3D, lens: &Lens) -> Option<(Vec3D, f64)> {
+        self.as_ref().sensor_point(lens_sample, direction, lens)
+    }
+
+}
+
+impl<P: Projection + ?Sized> Projection for Arc<P> {
+
+    fn direction(&self, sensor_point: &Vec3D, lens: &Lens) -> Option<Vec3D> {
+        self.as_ref().direction(sensor_point, lens)
+    }
+
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        self.as_ref().ray(sensor_point, lens_sample, lens)
+    }
+
+    fn sensor_point(&self, lens_sample: &Vec3D, direction: &Vec3D, lens: &Lens) -> Option<(Vec3D, f64)> {
+        self.as_ref().sensor_point(lens_sample, direction, lens)
+    }
+
+}
+
+/// Returns the origin and direction of a ray passing through a thin lens, whose center is at the
+/// given position, and which is perpendicular to the given direction.
+pub fn thin_lens_ray(center: &Vec3D, direction: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> (Vec3D, Vec3D) {
+    let unit_direction = direction.unit();
+    let focal_point = center + &(unit_direction * lens.focal_plane_distance());
+    let origin = center + &(&Matrix::with_z_alignment(&unit_direction) * lens_sample);
+    (origin, focal_point - origin)
+}
@@ -0,0 +1,51 @@
+use crate::basic::vectors::Vec3D;
+use crate::viewing::{Lens, Projection};
+
+/// A parallel projection, where all rays passing through the center of the lens share the same
+/// direction (the negative z-axis). The `half_height` is half the height of the viewed area, in
+/// world units.
+pub struct Orthographic {
+    pub half_height: f64,
+}
+
+impl Projection for Orthographic {
+
+    fn direction(&self, _: &Vec3D, _: &Lens) -> Option<Vec3D> {
+        Some(-Vec3D::Z)
+    }
+
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        let center = Vec3D::new(sensor_point.x(), sensor_point.y(), 0.0) * self.half_height;
+        let focal_point = center - Vec3D::along_z(lens.focal_plane_distance());
+        let origin = center + *lens_sample;
+        Some((origin, focal_point - origin))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::Dot;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn rays_are_parallel_and_offset_by_their_sensor_points(x in -1.5..1.5, y in -1.0..1.0, other_x in -1.5..1.5, other_y in -1.0..1.0, half_height in 0.5..5.0) {
+            let orthographic = Orthographic { half_height };
+            let lens = Lens::ideal(2.0);
+
+            let (origin, direction) = orthographic.ray(&Vec3D::new(x, y, 0.0), &Vec3D::zero(), &lens).unwrap();
+            let (other_origin, other_direction) = orthographic.ray(&Vec3D::new(other_x, other_y, 0.0), &Vec3D::zero(), &lens).unwrap();
+
+            assert!((direction.unit() + Vec3D::Z).length() < 1e-9);
+            assert!((other_direction.unit() + Vec3D::Z).length() < 1e-9);
+            assert!((origin - other_origin - Vec3D::new(x - other_x, y - other_y, 0.0) * half_height).length() < 1e-9);
+        }
+
+    }
+
+}
@@ -0,0 +1,34 @@
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::viewing::{Lens, Projection};
+
+/// The classic pinhole/thin-lens projection, where the sensor sits behind the lens at a distance
+/// equal to the focal length.
+pub struct Perspective;
+
+impl Projection for Perspective {
+
+    fn direction(&self, sensor_point: &Vec3D, lens: &Lens) -> Option<Vec3D> {
+        Some(Vec3D::new(sensor_point.x(), sensor_point.y(), -lens.focal_length))
+    }
+
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        self.direction(sensor_point, lens).map(|direction| {
+            let focal_plane_sample = direction * lens.focal_plane_ratio;
+            (*lens_sample, focal_plane_sample - *lens_sample)
+        })
+    }
+
+    fn sensor_point(&self, lens_sample: &Vec3D, direction: &Vec3D, lens: &Lens) -> Option<(Vec3D, f64)> {
+        let cos_theta = -direction.z() / direction.length();
+        if cos_theta <= 0.0 {
+            return None
+        }
+        // Rays through the same sensor point meet on the focal plane, where the sensor image is
+        // scaled by the focal plane ratio.
+        let focal_plane_distance = lens.focal_plane_distance();
+        let focal_plane_point = lens_sample + &(direction * ((focal_plane_distance + lens_sample.z()) / -direction.z()));
+        let sensor_point = Vec3D::new(focal_plane_point.x(), focal_plane_point.y(), 0.0) / lens.focal_plane_ratio;
+        Some((sensor_point, lens.focal_length * lens.focal_length / (cos_theta * cos_theta * cos_theta)))
+    }
+
+}
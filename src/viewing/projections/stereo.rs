@@ -0,0 +1,72 @@
+use std::f64::consts::PI;
+
+use crate::basic::vectors::Vec3D;
+use crate::viewing::{thin_lens_ray, Equirectangular, Lens, Projection};
+
+/// An omni-directional stereo (ODS) projection, producing a pair of equirectangular panoramas in
+/// an over-under layout: the upper half of the sensor is the left eye view, and the lower half is
+/// the right eye view. A square sensor captures the entire sphere of directions for both eyes.
+///
+/// Rays for each eye originate from a circle whose diameter is the interpupillary distance, and
+/// are tangent to it, which is what head-mounted displays expect for stereo panoramas.
+pub struct OmniDirectionalStereo {
+    pub interpupillary_distance: f64,
+}
+
+impl OmniDirectionalStereo {
+
+    fn eye_and_angles(&self, sensor_point: &Vec3D) -> (f64, f64, f64) {
+        let y = sensor_point.y();
+        let (eye, eye_y) = if y >= 0.0 { (-1.0, 2.0 * y - 1.0) } else { (1.0, 2.0 * y + 1.0) };
+        let longitude = sensor_point.x() * PI;
+        let latitude = eye_y * PI / 2.0;
+        (eye, longitude, latitude)
+    }
+
+}
+
+impl Projection for OmniDirectionalStereo {
+
+    fn direction(&self, sensor_point: &Vec3D, _: &Lens) -> Option<Vec3D> {
+        let (_, longitude, latitude) = self.eye_and_angles(sensor_point);
+        Some(Equirectangular::direction_at(longitude, latitude))
+    }
+
+    fn ray(&self, sensor_point: &Vec3D, lens_sample: &Vec3D, lens: &Lens) -> Option<(Vec3D, Vec3D)> {
+        let (eye, longitude, latitude) = self.eye_and_angles(sensor_point);
+        let (sin_lon, cos_lon) = longitude.sin_cos();
+        let eye_position = Vec3D::new(cos_lon, 0.0, sin_lon) * (eye * self.interpupillary_distance / 2.0);
+        let direction = Equirectangular::direction_at(longitude, latitude);
+        Some(thin_lens_ray(&eye_position, &direction, lens_sample, lens))
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::basic::vectors::Dot;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn eyes_are_offset_oppositely_across_the_rays(x in -1.0..1.0, y in 0.0..1.0, distance in 0.01..0.1) {
+            let stereo = OmniDirectionalStereo { interpupillary_distance: distance };
+            let lens = Lens::ideal(1.0);
+
+            let (left_origin, left_direction) = stereo.ray(&Vec3D::new(x, y, 0.0), &Vec3D::zero(), &lens).unwrap();
+            let (right_origin, right_direction) = stereo.ray(&Vec3D::new(x, y - 1.0, 0.0), &Vec3D::zero(), &lens).unwrap();
+
+            assert!((left_direction.unit() - right_direction.unit()).length() < 1e-9);
+            assert!((left_origin + right_origin).length() < 1e-9);
+            assert!((left_origin.length() - distance / 2.0).abs() < 1e-9);
+            assert!(left_origin.dot(left_direction.unit()).abs() < 1e-9);
+            assert_eq!(left_origin.y(), 0.0);
+        }
+
+    }
+
+}
@@ -0,0 +1,46 @@
+use std::f64::consts::PI;
+
+use crate::viewing::ReconstructionFilter;
+
+/// The four-term Blackman-Harris window, which is a smooth bell with very low side lobes.
+pub struct BlackmanHarrisFilter {
+    pub radius: f64,
+}
+
+impl ReconstructionFilter for BlackmanHarrisFilter {
+
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        if offset.abs() >= self.radius {
+            return 0.0
+        }
+        let t = 2.0 * PI * (offset + self.radius) / (2.0 * self.radius);
+        0.35875 - 0.48829 * t.cos() + 0.14128 * (2.0 * t).cos() - 0.01168 * (3.0 * t).cos()
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::viewing::reconstruction::tests::assert_supported_within;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn peaks_at_one_and_fades_out_at_its_radius(radius in 0.5..3.0f64) {
+            let filter = BlackmanHarrisFilter { radius };
+            assert_supported_within(&filter);
+            assert!((filter.evaluate(0.0) - 1.0).abs() < 1e-12);
+            assert!(filter.evaluate(radius * 0.999) < 1e-4);
+        }
+
+    }
+
+}
@@ -0,0 +1,40 @@
+use crate::viewing::ReconstructionFilter;
+
+/// Gives equal weights to all samples within the radius. A radius of `0.5` makes each sample
+/// contribute only to the pixel it falls in.
+pub struct BoxFilter {
+    pub radius: f64,
+}
+
+impl ReconstructionFilter for BoxFilter {
+
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        if offset.abs() <= self.radius { 1.0 } else { 0.0 }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::viewing::reconstruction::tests::assert_supported_within;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn weighs_samples_equally_within_its_radius(radius in 0.5..3.0, offset in -3.0..3.0f64) {
+            let filter = BoxFilter { radius };
+            assert_supported_within(&filter);
+            assert_eq!(filter.evaluate(offset), if offset.abs() <= radius { 1.0 } else { 0.0 });
+        }
+
+    }
+
+}
@@ -0,0 +1,43 @@
+use crate::viewing::ReconstructionFilter;
+
+/// A Gaussian bell, shifted down so that it reaches zero at the radius. The `alpha` parameter is
+/// the falloff rate, where the bell is `exp(-alpha * x^2)`.
+pub struct GaussianFilter {
+    pub radius: f64,
+    pub alpha: f64,
+}
+
+impl ReconstructionFilter for GaussianFilter {
+
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        let bell = |x: f64| (-self.alpha * x * x).exp();
+        (bell(offset) - bell(self.radius)).max(0.0)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::viewing::reconstruction::tests::assert_supported_within;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn peaks_at_the_bell_shifted_down_to_zero_at_its_radius(radius in 0.5..3.0, alpha in 0.5..4.0f64) {
+            let filter = GaussianFilter { radius, alpha };
+            assert_supported_within(&filter);
+            assert!((filter.evaluate(0.0) - (1.0 - (-alpha * radius * radius).exp())).abs() < 1e-12);
+            assert!(filter.evaluate(radius * 0.99) > 0.0);
+        }
+
+    }
+
+}
@@ -0,0 +1,57 @@
+use std::f64::consts::PI;
+
+use crate::viewing::ReconstructionFilter;
+
+/// A sinc function windowed by a wider sinc function. The radius is also the number of lobes of
+/// the filter on each side.
+pub struct LanczosFilter {
+    pub radius: f64,
+}
+
+impl ReconstructionFilter for LanczosFilter {
+
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        let x = offset.abs();
+        if x < self.radius { sinc(x) * sinc(x / self.radius) } else { 0.0 }
+    }
+
+}
+
+fn sinc(x: f64) -> f64 {
+    if x < 1e-5 {
+        1.0
+    } else {
+        let pi_x = PI * x;
+        pi_x.sin() / pi_x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::viewing::reconstruction::tests::assert_supported_within;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn peaks_at_one_and_crosses_zero_at_whole_offsets(lobes in 1..4, offset in 0.01..0.99) {
+            let filter = LanczosFilter { radius: lobes as f64 };
+            assert_supported_within(&filter);
+            assert_eq!(filter.evaluate(0.0), 1.0);
+            for i in 1 .. lobes {
+                assert!(filter.evaluate(i as f64).abs() < 1e-12);
+                // the lobes alternate in sign
+                assert!(filter.evaluate(i as f64 - 1.0 + offset) * filter.evaluate(i as f64 + offset) < 0.0);
+            }
+        }
+
+    }
+
+}
@@ -0,0 +1,63 @@
+use crate::viewing::ReconstructionFilter;
+
+/// The Mitchell-Netravali family of cubic filters, parameterized by `b` and `c`. The values
+/// recommended by Mitchell and Netravali are `b = c = 1/3`.
+pub struct MitchellNetravaliFilter {
+    pub radius: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+impl MitchellNetravaliFilter {
+
+    pub fn recommended(radius: f64) -> Self {
+        Self { radius, b: 1.0 / 3.0, c: 1.0 / 3.0 }
+    }
+
+}
+
+impl ReconstructionFilter for MitchellNetravaliFilter {
+
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        let &Self { radius, b, c } = self;
+        let x = 2.0 * offset.abs() / radius;
+        let x2 = x * x;
+        let x3 = x2 * x;
+        let value = if x < 1.0 {
+            (12.0 - 9.0 * b - 6.0 * c) * x3 + (-18.0 + 12.0 * b + 6.0 * c) * x2 + (6.0 - 2.0 * b)
+        } else if x < 2.0 {
+            (-b - 6.0 * c) * x3 + (6.0 * b + 30.0 * c) * x2 + (-12.0 * b - 48.0 * c) * x + (8.0 * b + 24.0 * c)
+        } else {
+            0.0
+        };
+        value / 6.0
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::*;
+
+    use crate::viewing::reconstruction::tests::assert_supported_within;
+
+    use super::*;
+
+    proptest! {
+
+        #[test]
+        fn sums_to_one_over_offsets_a_half_radius_apart(radius in 0.5..3.0, offset in 0.0..1.0) {
+            // which holds for the filters where b + 2c = 1, like the recommended one
+            let filter = MitchellNetravaliFilter::recommended(radius);
+            assert_supported_within(&filter);
+            let sum: f64 = (-4 ..= 4).map(|i| filter.evaluate((offset + i as f64) * radius / 2.0)).sum();
+            assert!((sum - 1.0).abs() < 1e-9, "{}", sum);
+        }
+
+    }
+
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+pub use blackman_harris::*;
+pub use boxed::*;
+pub use gaussian::*;
+pub use lanczos::*;
+pub use mitchell_netravali::*;
+
+mod boxed;
+mod gaussian;
+mod mitchell_netravali;
+mod lanczos;
+mod blackman_harris;
+
+/// A pixel reconstruction filter, which determines how much a sample contributes to the pixels
+/// around it, based on its offset from their centers (in pixel units). Filters are separable, and
+/// could extend beyond a single pixel, and could also have negative lobes.
+pub trait ReconstructionFilter: Send + Sync {
+
+    /// The offset beyond which the filter has no effect.
+    fn radius(&self) -> f64;
+
+    /// The one-dimensional profile of the filter.
+    fn evaluate(&self, offset: f64) -> f64;
+
+    fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.evaluate(dx) * self.evaluate(dy)
+    }
+
+}
+
+impl<F: ReconstructionFilter + ?Sized> ReconstructionFilter for Box<F> {
+
+    fn radius(&self) -> f64 {
+        self.as_ref().radius()
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        self.as_ref().evaluate(offset)
+    }
+
+}
+
+impl<F: ReconstructionFilter + ?Sized> ReconstructionFilter for Arc<F> {
+
+    fn radius(&self) -> f64 {
+        self.as_ref().radius()
+    }
+
+    fn evaluate(&self, offset: f64) -> f64 {
+        self.as_ref().evaluate(offset)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the given filter is symmetric, and has no effect beyond its radius.
+    pub fn assert_supported_within<F: ReconstructionFilter>(filter: &F) {
+        let radius = filter.radius();
+        for i in 0 ..= 100 {
+            let offset = 2.0 * radius * i as f64 / 100.0;
+            assert!((filter.evaluate(offset) - filter.evaluate(-offset)).abs() < 1e-12, "{}", offset);
+            if offset > radius {
+                assert_eq!(filter.evaluate(offset), 0.0, "{}", offset);
+            }
+        }
+    }
+
+}
@@ -1,9 +1,13 @@
+use crate::basic::vectors::Vec3D;
 use crate::viewing::Pixel;
 
 pub struct Sensor {
     pub width: usize,
     pub height: usize,
     pub gain: f64,
+    /// Whether the sensor responds to light spectrally, i.e. whether rays are traced at sampled
+    /// wavelengths, which get converted into colors, instead of being traced in RGB.
+    pub spectral: bool,
 
     aspect: f64,
     pixel_size: f64,
@@ -16,11 +20,33 @@ impl Sensor {
             width,
             height,
             gain,
+            spectral: false,
             aspect: (width as f64) / (height as f64),
             pixel_size: 2.0 / (height as f64)
         }
     }
 
+    pub fn with_spectral_response(self, spectral: bool) -> Self {
+        Self { spectral, ..self }
+    }
+
+    /// Returns the sensor point at the given continuous film position, where the pixel at column `i`
+    /// and row `j` covers the area `[i, i + 1) x [j, j + 1)`.
+    pub fn point(&self, x: f64, y: f64) -> Vec3D {
+        Vec3D::new(x * self.pixel_size - self.aspect, 1.0 - y * self.pixel_size, 0.0)
+    }
+
+    /// Returns the continuous film position of the given sensor point, which is the inverse of
+    /// `point`.
+    pub fn film_position(&self, point: &Vec3D) -> (f64, f64) {
+        ((point.x() + self.aspect) / self.pixel_size, (1.0 - point.y()) / self.pixel_size)
+    }
+
+    /// The area the sensor covers, in the units of sensor points.
+    pub fn area(&self) -> f64 {
+        4.0 * self.aspect
+    }
+
     pub fn pixel(&self, x: usize, y: usize) -> Pixel {
         let size = self.pixel_size;
         let aspect = self.aspect;
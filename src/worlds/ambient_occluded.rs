@@ -0,0 +1,74 @@
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::brdfs::Lambertian;
+use crate::sampling::Space;
+use crate::things::Thing;
+use crate::worlds::{first_hit_on, FirstHit, World};
+
+/// A world made of the given subject, shaded by ambient occlusion, i.e. by how much of the sky,
+/// weighted by the cosine of the angle with the normal, the surfaces seen by the camera could see,
+/// as the subject occludes the rest, within the given radius. It ignores materials and lights,
+/// which makes it a quick way to check the geometry of a scene. Rays missing the subject are black.
+pub struct AmbientOccluded<T: Thing> {
+    pub subject: T,
+    pub radius: f64,
+}
+
+impl<T: Thing> World for AmbientOccluded<T> {
+
+    fn trace(&self, ray: &Ray) -> Color {
+        let Some(hit) = self.subject.shoot(ray, 0.0001, f64::INFINITY) else {
+            return Color::BLACK
+        };
+        let (direction, _) = Lambertian::new(&hit.hit.normal).arbitrary_sample_and_pdf();
+        match self.subject.shoot(&hit.hit.incident_ray.with_direction(direction), 0.0001, self.radius) {
+            Some(_) => Color::BLACK,
+            None => Color::WHITE,
+        }
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        first_hit_on(&self.subject, ray)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::vectors::Vec3D;
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::materials::Diffusive;
+    use crate::textures::Constant;
+    use crate::things::Things;
+    use crate::transforms::Linear;
+
+    use super::*;
+
+    fn world(radius: f64) -> AmbientOccluded<Things> {
+        // The top of a sphere of radius 0.5 sees the inside of a sphere of radius 2, at its center,
+        // at distances between 1.5 and 2.5.
+        let things = Things(vec![
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(2.0))
+                .with_inner_texture(Constant(Diffusive(Color::WHITE)))
+                .boxed(),
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(0.5))
+                .with_outer_texture(Constant(Diffusive(Color::WHITE)))
+                .boxed(),
+        ]);
+        Building(things).ambient_occluded().with_radius(radius).done()
+    }
+
+    #[test]
+    fn occludes_within_the_radius_only() {
+        let ray = Ray::new(Vec3D::new(0.0, 1.0, 0.0), -Vec3D::Y, Color::WHITE, 0.0);
+        let (near, far) = (world(1.4), world(2.6));
+        for _ in 0 .. 100 {
+            assert_eq!(near.trace(&ray), Color::WHITE);
+            assert_eq!(far.trace(&ray), Color::BLACK);
+        }
+    }
+
+}
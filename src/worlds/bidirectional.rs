@@ -0,0 +1,645 @@
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::spectra::seen_at;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::BRDF;
+use crate::lights::{Light, Lights};
+use crate::materials::{Effect, Material};
+use crate::things::{MaterialHit, Thing};
+use crate::viewing::CameraLink;
+use crate::worlds::{environment_light, first_hit_on, follow_relocations, FirstHit, Relocated, World};
+
+/// A world made of the given subject, in the given environment, where light is traced along paths
+/// of up to the given depth, built by connecting every vertex of a subpath starting at the camera,
+/// with every vertex of a subpath starting at one of the lights. The light of all these
+/// connections is combined using multiple importance sampling. Connections to the camera itself
+/// (i.e. light tracing) are only made when the camera links the world to the film it exposes.
+///
+/// Only the lights can start light subpaths, and their light is only gathered at the end of camera
+/// subpaths, while the light of emissive things and of the environment is only gathered by camera
+/// subpaths, as in plain path tracing. Relocations (e.g. random walks within media) are followed,
+/// but never connected through. Materials choosing their effects at random are assumed to make the
+/// same choice when evaluated in reverse, which slightly biases the weights of their connections.
+pub struct BidirectionalPathTraced<W: World, T: Thing> {
+
+    pub environment: W,
+    pub subject: T,
+    pub depth: u8,
+    pub lights: Lights
+
+}
+
+impl<W: World, T: Thing> World for BidirectionalPathTraced<W, T> {
+
+    fn trace(&self, ray: &Ray) -> Color {
+        self.trace_paths(ray, None)
+    }
+
+    fn trace_linked(&self, ray: &Ray, link: &dyn CameraLink) -> Color {
+        self.trace_paths(ray, Some(link))
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        first_hit_on(&self.subject, ray)
+    }
+
+}
+
+/// A vertex of a subpath, starting at either the camera or a light.
+struct Vertex<'a> {
+    kind: Kind<'a>,
+    /// The position of the vertex, or, for vertices at infinity, the unit direction towards them.
+    position: Vec3D,
+    /// The unit normal of the surface the vertex is on, or zero if it is not on a surface.
+    normal: Vec3D,
+    /// The light (or the importance) carried by the subpath up to the vertex, divided by the
+    /// density of sampling it.
+    beta: Color,
+    /// Whether the vertex redirects light along a single direction, like mirrors do.
+    delta: bool,
+    /// The densities, in the area measure around the vertex, of sampling it from its predecessor
+    /// in its subpath, and from its successor, as if the subpath had been traced the other way.
+    /// Vertices at infinity have solid angle densities instead.
+    pdf_fwd: f64,
+    pdf_rev: f64,
+}
+
+enum Kind<'a> {
+    Camera,
+    Light { index: usize, radiance: Color, infinite: bool },
+    /// A surface, scattering light with the given color and BRDF, unless it redirects it.
+    Surface { hit: Box<MaterialHit<'a>>, color: Color, brdf: Option<Box<dyn BRDF>> },
+}
+
+impl<'a> Vertex<'a> {
+
+    fn is_infinite(&self) -> bool {
+        matches!(self.kind, Kind::Light { infinite: true, .. })
+    }
+
+    fn is_connectible(&self) -> bool {
+        matches!(self.kind, Kind::Surface { brdf: Some(_), .. })
+    }
+
+    /// Returns the unit direction from this vertex to the given one, and the distance between
+    /// them.
+    fn direction_to(&self, other: &Vertex) -> (Vec3D, f64) {
+        if other.is_infinite() {
+            (other.position, f64::INFINITY)
+        } else if self.is_infinite() {
+            (-self.position, f64::INFINITY)
+        } else {
+            let offset = other.position - self.position;
+            let distance = offset.length();
+            (offset / distance, distance)
+        }
+    }
+
+    /// Returns the light scattered by this vertex along the given direction, relative to the light
+    /// arriving along the direction it was reached from, including the cosine factor.
+    fn scattering(&self, direction: &Vec3D) -> Color {
+        match self.kind {
+            Kind::Surface { color, brdf: Some(ref brdf), .. } => color * brdf.eval(direction),
+            _ => Color::BLACK,
+        }
+    }
+
+    /// Converts the given solid angle density, of sampling the direction from this vertex to the
+    /// given one, into the area density of sampling the latter.
+    fn area_density(&self, pdf: f64, to: &Vertex) -> f64 {
+        if to.is_infinite() {
+            return pdf
+        }
+        let offset = to.position - self.position;
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return 0.0
+        }
+        let cos = if to.normal == Vec3D::zero() { 1.0 } else { to.normal.dot(offset).abs() / distance_squared.sqrt() };
+        pdf * cos / distance_squared
+    }
+
+}
+
+/// The densities of sampling a vertex, along with whether it is a delta vertex, as needed to weight
+/// the connections made through it.
+#[derive(Clone, Copy)]
+struct Densities {
+    fwd: f64,
+    rev: f64,
+    delta: bool,
+}
+
+impl Densities {
+
+    fn of(vertex: &Vertex) -> Self {
+        Self { fwd: vertex.pdf_fwd, rev: vertex.pdf_rev, delta: vertex.delta }
+    }
+
+}
+
+impl<W: World, T: Thing> BidirectionalPathTraced<W, T> {
+
+    fn light(&self, index: usize) -> &dyn Light {
+        self.lights.lights()[index].as_ref()
+    }
+
+    fn trace_paths(&self, ray: &Ray, link: Option<&dyn CameraLink>) -> Color {
+        let depth = self.depth as usize;
+        let mut camera_path = vec![Vertex {
+            kind: Kind::Camera,
+            position: ray.origin,
+            normal: Vec3D::zero(),
+            beta: Color::WHITE,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }];
+        let pdf = link.map_or(0.0, |link| link.pdf(&ray.origin, &ray.direction.unit()));
+        let mut color = self.walk(&mut camera_path, ray.clone(), Color::WHITE, pdf, depth + 2, true);
+        let mut light_path = Vec::new();
+        self.start_light_path(&mut light_path, ray, depth + 1);
+        // lights that could not start light subpaths could still be sampled directly
+        for t in 1 ..= camera_path.len() {
+            for s in 0 ..= light_path.len().max(1) {
+                if s + t < 2 || s + t - 2 > depth || (s == 1 && t == 1) {
+                    continue
+                }
+                if t == 1 {
+                    if let Some(link) = link {
+                        self.splat(&light_path, s, ray, link);
+                    }
+                } else {
+                    color += self.connect(&light_path, &camera_path, s, t, ray, link);
+                }
+            }
+        }
+        color
+    }
+
+    /// Starts a light subpath, of up to the given number of vertices, at one of the lights, picked
+    /// according to their powers.
+    fn start_light_path<'a>(&'a self, path: &mut Vec<Vertex<'a>>, camera_ray: &Ray, max_vertices: usize) {
+        let Some((index, probability)) = self.lights.pick_by_power() else {
+            return
+        };
+        let Some(emission) = self.light(index).emit() else {
+            return
+        };
+        let radiance = seen_at(&emission.radiance, camera_ray.wavelength);
+        let pdf_position = probability * emission.pdf_position;
+        if pdf_position <= 0.0 || emission.pdf_direction <= 0.0 {
+            return
+        }
+        let cos = emission.normal.map_or(1.0, |normal| normal.dot(emission.direction).abs());
+        let beta = radiance * (cos / (pdf_position * emission.pdf_direction));
+        path.push(Vertex {
+            kind: Kind::Light { index, radiance, infinite: false },
+            position: emission.origin,
+            normal: emission.normal.unwrap_or(Vec3D::zero()),
+            beta: radiance / pdf_position,
+            delta: false,
+            pdf_fwd: pdf_position,
+            pdf_rev: 0.0,
+        });
+        let ray = camera_ray.with_origin_and_direction(emission.origin, emission.direction).with_color(Color::WHITE);
+        self.walk(path, ray, beta, emission.pdf_direction, max_vertices, false);
+    }
+
+    /// Extends the given subpath along the given ray, which carries the given light (or
+    /// importance), and whose direction was sampled with the given solid angle density, until the
+    /// subpath has the given number of vertices. Camera subpaths end at the lights they hit, and
+    /// return the light of the emissive things and of the environment they reach, which no other
+    /// connection can gather.
+    fn walk<'a>(&'a self, path: &mut Vec<Vertex<'a>>, ray: Ray, beta: Color, pdf: f64, max_vertices: usize, camera: bool) -> Color {
+        let (mut ray, mut beta, mut pdf) = (ray, beta, pdf);
+        let mut gathered = Color::BLACK;
+        while path.len() < max_vertices {
+            let hit = self.subject.shoot(&ray, 0.0001, f64::INFINITY);
+            if camera {
+                let max = hit.as_ref().map_or(f64::INFINITY, |hit| hit.hit.distance);
+                if let Some((index, distance, radiance)) = self.lights.hit(&ray, max) {
+                    let radiance = seen_at(&radiance, ray.wavelength);
+                    let infinite = distance.is_infinite();
+                    let position = if infinite { ray.direction.unit() } else { ray.at(distance) };
+                    let normal = if infinite { None } else { self.light(index).normal_at(&position) };
+                    let mut vertex = Vertex {
+                        kind: Kind::Light { index, radiance, infinite },
+                        position,
+                        normal: normal.unwrap_or(Vec3D::zero()),
+                        beta,
+                        delta: false,
+                        pdf_fwd: 0.0,
+                        pdf_rev: 0.0,
+                    };
+                    vertex.pdf_fwd = path[path.len() - 1].area_density(pdf, &vertex);
+                    path.push(vertex);
+                    break
+                }
+            }
+            let Some(hit) = hit else {
+                if camera {
+                    gathered += beta * environment_light(&self.environment, &ray);
+                }
+                break
+            };
+            let (hit, effect, relocated) = match follow_relocations(&self.subject, hit) {
+                Relocated::Hit(hit, effect, relocation) => {
+                    if let Some(c) = relocation {
+                        beta *= c;
+                    }
+                    (hit, effect, relocation.is_some())
+                },
+                Relocated::Escaped(ray, relocation) => {
+                    if camera {
+                        gathered += beta * relocation * environment_light(&self.environment, &ray);
+                    }
+                    return gathered
+                },
+                Relocated::Lost => return gathered,
+            };
+            let position = hit.hit.incident_ray.origin;
+            let normal = hit.hit.normal.unit();
+            let incident = hit.hit.incident_ray.direction.unit();
+            let (color, brdf, direction) = match effect {
+                Effect::Scattering(color, brdf) => {
+                    let (direction, _) = brdf.arbitrary_sample_and_pdf();
+                    (color, Some(brdf), direction)
+                },
+                Effect::Redirection(color, direction) => (color, None, direction),
+                Effect::Emission(c) => {
+                    if camera {
+                        gathered += beta * c;
+                    }
+                    break
+                },
+                _ => break,
+            };
+            let (next_pdf, weight, reverse_pdf) = match brdf {
+                Some(ref brdf) => {
+                    let next_pdf = brdf.pdf(&direction);
+                    let weight = if next_pdf > 0.0 { color * (brdf.eval(&direction) / next_pdf) } else { Color::BLACK };
+                    (next_pdf, weight, reverse_pdf(&hit, &-direction, &-incident))
+                },
+                None => (0.0, color, 0.0),
+            };
+            let next_ray = hit.hit.incident_ray.with_direction(direction);
+            let delta = brdf.is_none();
+            let mut vertex = Vertex {
+                kind: Kind::Surface { hit: Box::new(hit), color, brdf },
+                position,
+                normal,
+                beta,
+                delta,
+                pdf_fwd: 0.0,
+                pdf_rev: 0.0,
+            };
+            let previous = path.len() - 1;
+            if !relocated {
+                vertex.pdf_fwd = path[previous].area_density(pdf, &vertex);
+                path[previous].pdf_rev = vertex.area_density(reverse_pdf, &path[previous]);
+            }
+            path.push(vertex);
+            if weight == Color::BLACK {
+                break
+            }
+            beta *= weight;
+            pdf = next_pdf;
+            ray = next_ray;
+        }
+        gathered
+    }
+
+    /// Returns the light of the path made of the first `s` vertices of the given light subpath,
+    /// and the first `t` vertices of the given camera subpath, weighted against the other ways of
+    /// sampling the same path. Paths ending with a single light vertex connect to a light sampled
+    /// anew, rather than to the start of the light subpath.
+    fn connect(&self, light_path: &[Vertex], camera_path: &[Vertex], s: usize, t: usize, camera_ray: &Ray, link: Option<&dyn CameraLink>) -> Color {
+        let pt = &camera_path[t - 1];
+        match s {
+            0 => match pt.kind {
+                Kind::Light { radiance, .. } => pt.beta * radiance * self.weight(light_path, camera_path, s, t, None, link),
+                _ => Color::BLACK,
+            },
+            1 => {
+                if !pt.is_connectible() {
+                    return Color::BLACK
+                }
+                let Some((index, probability)) = self.lights.pick_index(&pt.position) else {
+                    return Color::BLACK
+                };
+                let light = self.light(index);
+                let Some(sample) = light.sample(&pt.position) else {
+                    return Color::BLACK
+                };
+                let radiance = seen_at(&sample.radiance, camera_ray.wavelength);
+                let scattering = pt.scattering(&sample.direction);
+                if sample.pdf <= 0.0 || scattering == Color::BLACK || !self.visible(camera_ray, &pt.position, &sample.direction, sample.distance) {
+                    return Color::BLACK
+                }
+                let infinite = sample.distance.is_infinite();
+                let position = if infinite { sample.direction } else { pt.position + sample.direction * sample.distance };
+                let normal = if infinite { None } else { light.normal_at(&position) };
+                let mut sampled = Vertex {
+                    kind: Kind::Light { index, radiance, infinite },
+                    position,
+                    normal: normal.unwrap_or(Vec3D::zero()),
+                    beta: radiance / (probability * sample.pdf),
+                    delta: false,
+                    pdf_fwd: 0.0,
+                    pdf_rev: 0.0,
+                };
+                sampled.pdf_fwd = self.sampled_light_pdf(&sampled, pt);
+                let weight = self.weight(light_path, camera_path, s, t, Some(&sampled), link);
+                pt.beta * scattering * sampled.beta * weight
+            },
+            _ => {
+                let qs = &light_path[s - 1];
+                if !qs.is_connectible() || !pt.is_connectible() {
+                    return Color::BLACK
+                }
+                let (direction, distance) = pt.direction_to(qs);
+                let light = qs.beta * qs.scattering(&-direction) * pt.scattering(&direction) * pt.beta / (distance * distance);
+                if light == Color::BLACK || !self.visible(camera_ray, &pt.position, &direction, distance) {
+                    return Color::BLACK
+                }
+                light * self.weight(light_path, camera_path, s, t, None, link)
+            },
+        }
+    }
+
+    /// Connects the last of the first `s` vertices of the given light subpath to the camera, and
+    /// splats its light onto the film.
+    fn splat(&self, light_path: &[Vertex], s: usize, camera_ray: &Ray, link: &dyn CameraLink) {
+        let qs = &light_path[s - 1];
+        if !qs.is_connectible() {
+            return
+        }
+        let Some(lens_link) = link.link(&qs.position) else {
+            return
+        };
+        let light = qs.beta * qs.scattering(&-lens_link.direction) * (lens_link.importance / (lens_link.distance * lens_link.distance));
+        if light == Color::BLACK || !self.visible(camera_ray, &qs.position, &-lens_link.direction, lens_link.distance) {
+            return
+        }
+        let camera = Vertex {
+            kind: Kind::Camera,
+            position: lens_link.lens_point,
+            normal: Vec3D::zero(),
+            beta: Color::WHITE,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        };
+        let weight = self.weight(light_path, &[], s, 1, Some(&camera), Some(link));
+        link.splat(&lens_link, &(light * weight));
+    }
+
+    fn visible(&self, camera_ray: &Ray, position: &Vec3D, direction: &Vec3D, distance: f64) -> bool {
+        let shadow_ray = camera_ray.with_origin_and_direction(*position, *direction);
+        self.subject.shoot(&shadow_ray, 0.0001, distance * (1.0 - 1e-6)).is_none()
+    }
+
+    /// Returns the weight, according to the balance heuristic, of the path made of the first `s`
+    /// vertices of the given light subpath, and the first `t` vertices of the given camera subpath,
+    /// where the given vertex, if any, replaces the single vertex of the light subpath or of the
+    /// camera subpath.
+    fn weight(&self, light_path: &[Vertex], camera_path: &[Vertex], s: usize, t: usize, sampled: Option<&Vertex>, link: Option<&dyn CameraLink>) -> f64 {
+        if s + t == 2 {
+            return 1.0
+        }
+        let qs = match s {
+            0 => None,
+            1 => sampled,
+            _ => Some(&light_path[s - 1]),
+        };
+        let Some(pt) = (if t == 1 { sampled } else { camera_path.get(t - 1) }) else {
+            return 0.0
+        };
+        let qs_minus = if s >= 2 { Some(&light_path[s - 2]) } else { None };
+        let pt_minus = if t >= 2 { Some(&camera_path[t - 2]) } else { None };
+        let mut light: Vec<Densities> = match qs {
+            Some(qs) if s == 1 => vec![Densities::of(qs)],
+            _ => light_path[..s].iter().map(Densities::of).collect(),
+        };
+        let mut camera: Vec<Densities> = match t {
+            1 => vec![Densities::of(pt)],
+            _ => camera_path[..t].iter().map(Densities::of).collect(),
+        };
+        camera[t - 1].delta = false;
+        camera[t - 1].rev = match (qs, pt_minus) {
+            (Some(qs), _) => self.pdf(qs, None, pt, link),
+            (None, Some(pt_minus)) => self.sampled_light_pdf(pt, pt_minus),
+            (None, None) => 0.0,
+        };
+        if let Some(pt_minus) = pt_minus {
+            camera[t - 2].rev = match qs {
+                Some(qs) => self.pdf(pt, Some(qs), pt_minus, link),
+                None => self.light_pdf(pt, pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light[s - 1].delta = false;
+            light[s - 1].rev = self.pdf(pt, pt_minus, qs, link);
+            if let Some(qs_minus) = qs_minus {
+                light[s - 2].rev = self.pdf(qs, Some(pt), qs_minus, link);
+            }
+        }
+        let (emitter, neighbor) = match s {
+            0 => (Some(pt), pt_minus),
+            1 => (qs, Some(pt)),
+            _ => (light_path.first(), light_path.get(1)),
+        };
+        let (mut emitted, mut sampled, mut delta_light) = (None, 0.0, false);
+        if let (Some(emitter), Some(neighbor)) = (emitter, neighbor) {
+            if let Kind::Light { index, .. } = emitter.kind {
+                emitted = self.emitted_light_pdf(emitter, neighbor);
+                sampled = self.sampled_light_pdf(emitter, neighbor);
+                delta_light = self.light(index).is_delta();
+            }
+        }
+        // strategies connecting to the camera need it to be linked, with a projection that could
+        // have traced the camera ray, and those starting at the light need the light to emit rays
+        let linked = camera.get(1).is_some_and(|densities| densities.fwd > 0.0);
+        let possible = |s: usize, t: usize| (t != 1 || linked) && (s < 2 || emitted.is_some());
+        let remap = |pdf: f64| if pdf != 0.0 { pdf } else { 1.0 };
+        // strategies starting at the light pick its first vertex differently than those sampling it
+        // directly
+        let origin_ratio = remap(emitted.unwrap_or(0.0)) / remap(sampled);
+        let mut sum = 0.0;
+        let mut ratio = 1.0;
+        for i in (1..t).rev() {
+            ratio *= remap(camera[i].rev) / remap(camera[i].fwd);
+            if !camera[i].delta && !camera[i - 1].delta && possible(s + t - i, i) {
+                sum += if s <= 1 && s + t - i >= 2 { ratio * origin_ratio } else { ratio };
+            }
+        }
+        ratio = 1.0;
+        for i in (0..s).rev() {
+            ratio *= remap(light[i].rev) / remap(if i == 0 { sampled } else { light[i].fwd });
+            if i == 1 {
+                ratio /= origin_ratio;
+            }
+            let delta_before = if i > 0 { light[i - 1].delta } else { delta_light };
+            if !light[i].delta && !delta_before && possible(i, s + t - i) {
+                sum += ratio;
+            }
+        }
+        1.0 / (1.0 + sum)
+    }
+
+    /// Returns the area density of sampling the given next vertex from the given vertex, which was
+    /// reached from the given previous vertex, or from the one it was reached from in its subpath.
+    fn pdf(&self, vertex: &Vertex, previous: Option<&Vertex>, next: &Vertex, link: Option<&dyn CameraLink>) -> f64 {
+        let (direction, _) = vertex.direction_to(next);
+        let pdf = match vertex.kind {
+            Kind::Camera => link.map_or(0.0, |link| link.pdf(&vertex.position, &direction)),
+            Kind::Light { .. } => return self.light_pdf(vertex, next),
+            Kind::Surface { ref hit, brdf: Some(ref brdf), .. } => match previous {
+                Some(previous) => reverse_pdf(hit, &previous.direction_to(vertex).0, &direction),
+                None => brdf.pdf(&direction),
+            },
+            Kind::Surface { brdf: None, .. } => 0.0,
+        };
+        vertex.area_density(pdf, next)
+    }
+
+    /// Returns the area density of the given light vertex emitting rays reaching the given vertex.
+    fn light_pdf(&self, vertex: &Vertex, next: &Vertex) -> f64 {
+        let Kind::Light { index, infinite: false, .. } = vertex.kind else {
+            return 0.0
+        };
+        let (direction, _) = vertex.direction_to(next);
+        self.light(index).emission_pdf(&vertex.position, &direction).map_or(0.0, |(_, pdf)| vertex.area_density(pdf, next))
+    }
+
+    /// Returns the density of sampling the given light vertex directly from the given vertex,
+    /// which is a solid angle density for vertices at infinity.
+    fn sampled_light_pdf(&self, vertex: &Vertex, next: &Vertex) -> f64 {
+        let Kind::Light { index, infinite, .. } = vertex.kind else {
+            return 0.0
+        };
+        let probability = self.lights.probability(index, &next.position);
+        let (direction, _) = vertex.direction_to(next);
+        if infinite {
+            probability * self.light(index).pdf(&next.position, &-direction)
+        } else {
+            probability * self.light(index).emission_pdf(&vertex.position, &direction).map_or(0.0, |(pdf, _)| pdf)
+        }
+    }
+
+    /// Returns the density of starting a light subpath at the given light vertex, towards the given
+    /// vertex, unless the light could not start light subpaths.
+    fn emitted_light_pdf(&self, vertex: &Vertex, next: &Vertex) -> Option<f64> {
+        let Kind::Light { index, infinite: false, .. } = vertex.kind else {
+            return None
+        };
+        let (direction, _) = vertex.direction_to(next);
+        let (pdf, _) = self.light(index).emission_pdf(&vertex.position, &direction)?;
+        Some(self.lights.power_probability(index) * pdf)
+    }
+
+}
+
+/// Returns the solid angle density of the given hit scattering light along the given direction,
+/// when its incident ray comes along the given incident direction.
+fn reverse_pdf(hit: &MaterialHit, incident: &Vec3D, direction: &Vec3D) -> f64 {
+    let reversed = hit.hit.with_incident_direction(incident);
+    let material_holder = hit.texture.material(&reversed, hit.geometry, hit.other_side_texture);
+    match material_holder.effect_of(&reversed) {
+        Effect::Scattering(_, brdf) => brdf.pdf(direction),
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use std::sync::{Arc, Mutex};
+
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::lights::{PointLight, SphereLight};
+    use crate::materials::Diffusive;
+    use crate::textures::Constant;
+    use crate::viewing::LensLink;
+
+    use super::*;
+
+    /// A pinhole camera at the given position, which sees everything, and records the light it
+    /// gets splatted.
+    struct RecordingLink {
+        position: Vec3D,
+        splats: Mutex<Vec<Color>>,
+    }
+
+    impl CameraLink for RecordingLink {
+
+        fn link(&self, point: &Vec3D) -> Option<LensLink> {
+            let offset = *point - self.position;
+            let distance = offset.length();
+            Some(LensLink { lens_point: self.position, direction: offset / distance, distance, importance: 1.0, film_position: (0.0, 0.0) })
+        }
+
+        fn pdf(&self, _: &Vec3D, _: &Vec3D) -> f64 {
+            1.0
+        }
+
+        fn splat(&self, _: &LensLink, color: &Color) {
+            self.splats.lock().unwrap().push(*color);
+        }
+
+    }
+
+    #[test]
+    fn weights_connections_into_the_light_they_share() {
+        // The top of the unit sphere sees a sphere light, of radius 0.5, 2 units above it, which
+        // makes it reflect a sixteenth of the radiance of the light, times its albedo.
+        let world = Building(Sphere)
+            .with_outer_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+            .bidirectional_path_traced()
+            .with_depth(3)
+            .with_lights(Lights::new(vec![Arc::new(SphereLight::new(Vec3D::new(0.0, 3.0, 0.0), 0.5, Color::new(16.0, 16.0, 16.0)))]))
+            .done();
+        let ray = Ray::new(Vec3D::new(0.0, 2.0, 0.0), -Vec3D::Y, Color::WHITE, 0.0);
+        let count = 20000;
+        let average = (0..count).fold(Color::BLACK, |sum, _| sum + world.trace(&ray)) / count as f64;
+        assert!((average.luminance() - 0.5).abs() < 0.015, "{:?}", average);
+    }
+
+    #[test]
+    fn connects_to_point_lights() {
+        // The top of the unit sphere gets an irradiance of π from a point light 2 units above it,
+        // which makes it reflect its albedo.
+        let world = Building(Sphere)
+            .with_outer_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+            .bidirectional_path_traced()
+            .with_depth(3)
+            .with_lights(Lights::new(vec![Arc::new(PointLight::new(Vec3D::new(0.0, 3.0, 0.0), Color::grey_shade(4.0 * PI)))]))
+            .done();
+        let ray = Ray::new(Vec3D::new(0.0, 2.0, 0.0), -Vec3D::Y, Color::WHITE, 0.0);
+        let count = 1000;
+        let average = (0..count).fold(Color::BLACK, |sum, _| sum + world.trace(&ray)) / count as f64;
+        assert!((average.luminance() - 0.5).abs() < 1e-6, "{:?}", average);
+    }
+
+    #[test]
+    fn splats_light_traced_onto_the_linked_camera() {
+        let world = Building(Sphere)
+            .with_outer_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+            .bidirectional_path_traced()
+            .with_depth(3)
+            .with_lights(Lights::new(vec![Arc::new(SphereLight::new(Vec3D::new(0.0, 3.0, 0.0), 0.5, Color::new(16.0, 16.0, 16.0)))]))
+            .done();
+        let link = RecordingLink { position: Vec3D::new(0.0, 2.0, 0.0), splats: Mutex::new(Vec::new()) };
+        let ray = Ray::new(link.position, -Vec3D::Y, Color::WHITE, 0.0);
+        for _ in 0 .. 2000 {
+            world.trace_linked(&ray, &link);
+        }
+        let splats = link.splats.into_inner().unwrap();
+        assert!(!splats.is_empty());
+        assert!(splats.iter().all(|splat| splat.luminance().is_finite() && splat.luminance() >= 0.0), "{:?}", splats);
+        assert!(splats.iter().any(|splat| splat.luminance() > 0.0));
+    }
+
+}
@@ -0,0 +1,96 @@
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::vectors::Dot;
+use crate::things::{counting_tests, Thing};
+use crate::worlds::{first_hit_on, FirstHit, World};
+
+/// A world made of the given subject, where the first hits of the camera rays are shaded by the
+/// given aspect of them, rather than by the light they get, which helps debugging geometries,
+/// transforms, textures, and scene layouts. Rays missing the subject are black.
+pub struct Inspected<T: Thing> {
+    pub subject: T,
+    pub aspect: Aspect,
+}
+
+/// What the first hits of the camera rays are shaded by, in an `Inspected` world.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Aspect {
+    /// The unit normals, facing the camera, with their x, y, and z coordinates mapped, from -1 to
+    /// 1, to the red, green, and blue components, from 0 to 1.
+    Normals,
+    /// The fractional parts of the surface coordinates, mapped to the red and green components.
+    SurfaceCoordinates,
+    /// The distances from the camera, as a heat map, which saturates at the given distance.
+    Distances(f64),
+    /// The numbers of atomic things the camera rays were tested against, as a heat map, which
+    /// saturates at the given number, which tells where tracing is costly. Unlike the others, it
+    /// shades the rays missing the subject as well.
+    ThingsTested(u32),
+    /// The material identifiers, as false colors (see `Color::false_color`).
+    MaterialIds,
+}
+
+impl<T: Thing> World for Inspected<T> {
+
+    fn trace(&self, ray: &Ray) -> Color {
+        let (hit, tests) = counting_tests(|| self.subject.shoot(ray, 0.0001, f64::INFINITY));
+        match (self.aspect, hit) {
+            (Aspect::ThingsTested(max), _) => Color::heat(tests as f64 / max as f64),
+            (_, None) => Color::BLACK,
+            (Aspect::Normals, Some(hit)) => {
+                let normal = hit.hit.normal.unit();
+                Color::new(normal.x() + 1.0, normal.y() + 1.0, normal.z() + 1.0) * 0.5
+            },
+            (Aspect::SurfaceCoordinates, Some(hit)) => {
+                let coordinates = hit.geometry.surface_coordinates(&hit.hit.local_hit().incident_ray.origin);
+                Color::new(coordinates.x() - coordinates.x().floor(), coordinates.y() - coordinates.y().floor(), 0.0)
+            },
+            (Aspect::Distances(max), Some(hit)) => Color::heat(hit.hit.distance * ray.direction.length() / max),
+            (Aspect::MaterialIds, Some(hit)) => Color::false_color(hit.texture.material_id()),
+        }
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        first_hit_on(&self.subject, ray)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basic::vectors::Vec3D;
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::materials::Diffusive;
+    use crate::textures::Constant;
+    use crate::things::Things;
+    use crate::transforms::Translation;
+
+    use super::*;
+
+    fn world(aspect: Aspect) -> Inspected<Things> {
+        let things = Things(vec![
+            Building(Sphere)
+                .with_texture(Constant(Diffusive(Color::WHITE)))
+                .boxed(),
+            Building(Sphere)
+                .transformed(Translation::new(0.0, 0.0, 3.0))
+                .with_texture(Constant(Diffusive(Color::WHITE)))
+                .boxed(),
+        ]);
+        Building(things).inspected().with_aspect(aspect).done()
+    }
+
+    #[test]
+    fn shades_hits_by_their_aspects() {
+        // The ray hits the top of the first sphere, at a distance of 2, along a direction that is
+        // not a unit vector.
+        let ray = Ray::new(Vec3D::new(0.0, 3.0, 0.0), Vec3D::along_y(-2.0), Color::WHITE, 0.0);
+
+        assert_eq!(world(Aspect::Normals).trace(&ray), Color::new(0.5, 1.0, 0.5));
+        assert_eq!(world(Aspect::Distances(4.0)).trace(&ray), Color::new(1.0, 0.5, 0.0));
+        assert_eq!(world(Aspect::ThingsTested(2)).trace(&ray), Color::WHITE);
+        assert_eq!(world(Aspect::ThingsTested(4)).trace(&ray), Color::new(1.0, 0.5, 0.0));
+    }
+
+}
@@ -1,26 +1,323 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
+pub use ambient_occluded::*;
+pub use bidirectional::*;
+pub use inspected::*;
 pub use path_traced::*;
+pub use photon_mapped::*;
 
 use crate::basic::colors::Color;
 use crate::basic::rays::Ray;
+use crate::basic::spectra::seen_at;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::BRDF;
+use crate::lights::{EmissiveSurface, LightBounds, Lights};
+use crate::materials::{Effect, Material};
+use crate::things::{MaterialHit, Surface, Thing};
+use crate::viewing::CameraLink;
 
+mod ambient_occluded;
+mod bidirectional;
+mod inspected;
 mod path_traced;
+mod photon_mapped;
 
 pub trait World: Send + Sync {
 
     fn trace(&self, ray: &Ray) -> Color;
 
+    /// Traces the given camera ray, like `trace`, with the possibility of splatting light onto the
+    /// film the camera is exposing, through the given link. Only worlds tracing light from light
+    /// sources towards the camera need it.
+    fn trace_linked(&self, ray: &Ray, _: &dyn CameraLink) -> Color {
+        self.trace(ray)
+    }
+
+    /// Same as `trace_linked`, for the given sample of the exposure, which keeps its index from one
+    /// exposure of a film to the next (see `Camera::expose`). Only worlds keeping statistics about
+    /// the samples across exposures need it.
+    fn trace_sample(&self, ray: &Ray, _: usize, link: &dyn CameraLink) -> Color {
+        self.trace_linked(ray, link)
+    }
+
+    /// Returns auxiliary information about the first surface hit by the given ray, if any. Worlds
+    /// that have no surfaces return `None`.
+    fn first_hit(&self, _: &Ray) -> Option<FirstHit> {
+        None
+    }
+
+    /// Same as `trace`, also returning the first hit (see `first_hit`), which worlds shooting the
+    /// ray at their surfaces anyway could reuse, rather than shooting it twice.
+    fn trace_with_first_hit(&self, ray: &Ray) -> (Color, Option<FirstHit>) {
+        (self.trace(ray), self.first_hit(ray))
+    }
+
+    /// Prepares the world for tracing the camera rays of the given exposure of a film (see
+    /// `Camera::expose`), counting from zero. Only worlds tracing light from the light sources ahead
+    /// of the camera rays need it.
+    fn prepare(&self, _: u64) {
+    }
+
+}
+
+/// Auxiliary information about the first surface a camera ray hits, which is typically needed for
+/// compositing and denoising rendered images.
+#[derive(Clone, Debug)]
+pub struct FirstHit {
+    /// The distance of the hit point from the origin of the ray.
+    pub depth: f64,
+    pub position: Vec3D,
+    pub normal: Vec3D,
+    /// The color of the effect of the material at the hit, which is random for materials picking
+    /// one of several effects (e.g. coated ones), like the light traced along the ray, and which
+    /// averages out over the samples of a pixel likewise.
+    pub albedo: Color,
+    pub surface_coordinates: Vec3D,
+    pub object_id: u32,
+    pub material_id: u32,
+    /// The displacement of the hit point over the exposure time. It is zero as long as scenes are
+    /// static.
+    pub motion: Vec3D,
+}
+
+/// Returns auxiliary information about the first surface of the given thing hit by the given ray,
+/// if any.
+fn first_hit_on<T: Thing + ?Sized>(subject: &T, ray: &Ray) -> Option<FirstHit> {
+    subject.shoot(ray, 0.0001, f64::INFINITY).map(|ref hit| first_hit_of(ray, hit))
+}
+
+/// Returns auxiliary information about the given hit of the given ray.
+fn first_hit_of(ray: &Ray, hit: &MaterialHit) -> FirstHit {
+    let material_holder = hit.texture.material(&hit.hit, hit.geometry, hit.other_side_texture);
+    let albedo = match material_holder.effect_of(&hit.hit) {
+        Effect::Absorption => Color::BLACK,
+        Effect::Emission(c) => c,
+        Effect::Scattering(c, _) => c,
+        Effect::Redirection(c, _) => c,
+        Effect::Relocation(c, _, _) => c,
+    };
+    FirstHit {
+        depth: hit.hit.distance * ray.direction.length(),
+        position: hit.hit.incident_ray.origin,
+        normal: hit.hit.normal.unit(),
+        albedo,
+        surface_coordinates: hit.geometry.surface_coordinates(&hit.hit.local_hit().incident_ray.origin),
+        object_id: hit.object_id,
+        material_id: hit.texture.material_id(),
+        motion: Vec3D::zero(),
+    }
+}
+
+/// Returns the effect of the material at the given hit, with its color as seen by the incident ray
+/// (see `seen_at`).
+fn effect_of(hit: &MaterialHit) -> Effect {
+    let material_holder = hit.texture.material(&hit.hit, hit.geometry, hit.other_side_texture);
+    let wavelength = hit.hit.incident_ray.wavelength;
+    material_holder.effect_of(&hit.hit).map_color(|c| seen_at(&c, wavelength))
+}
+
+/// Returns the light the given environment sends along the given ray, regardless of its origin, as
+/// seen by the ray (see `seen_at`).
+fn environment_light<W: World + ?Sized>(environment: &W, ray: &Ray) -> Color {
+    seen_at(&environment.trace(&ray.with_origin(Vec3D::zero()).with_wavelength(None)), ray.wavelength)
+}
+
+/// The most relocations of the light (e.g. steps of random walks within media) followed in a row,
+/// before giving up on it.
+const MAX_RELOCATIONS: u16 = 1024;
+
+/// Where the relocations of the light, starting at some hit, end, along with the product of their
+/// colors, or `None` if there were no relocations.
+#[allow(clippy::large_enum_variant)]
+enum Relocated<'a> {
+    Hit(MaterialHit<'a>, Effect, Option<Color>),
+    Escaped(Ray, Color),
+    Lost,
+}
+
+/// Follows the relocations of the light on the given subject, starting at the given hit, up to the
+/// first effect that is not a relocation, if any.
+fn follow_relocations<'a, T: Thing + ?Sized>(subject: &'a T, hit: MaterialHit<'a>) -> Relocated<'a> {
+    let mut effect = effect_of(&hit);
+    let mut hit = hit;
+    let mut color = None;
+    let mut relocations = 0;
+    while let Effect::Relocation(c, position, direction) = effect {
+        relocations += 1;
+        if relocations > MAX_RELOCATIONS {
+            return Relocated::Lost
+        }
+        let product = color.unwrap_or(Color::WHITE) * c;
+        color = Some(product);
+        let ray = hit.hit.incident_ray.with_origin_and_direction(position, direction);
+        let Some(next) = subject.shoot(&ray, 0.0001, f64::INFINITY) else {
+            return Relocated::Escaped(ray, product)
+        };
+        hit = next;
+        effect = effect_of(&hit);
+    }
+    Relocated::Hit(hit, effect, color)
+}
+
+/// Samples the light arriving directly, from one of the given lights, at the given hit, and
+/// scattering according to the given BRDF, unless the given subject blocks it. Unless the light is
+/// a delta light, it is weighted, if told so, against the light that scattered rays could hit
+/// (see `power_heuristic`).
+fn sample_lights<T: Thing + ?Sized>(subject: &T, lights: &Lights, hit: &MaterialHit, brdf: &dyn BRDF, weighted: bool) -> Color {
+    let position = &hit.hit.incident_ray.origin;
+    let Some((index, probability)) = lights.pick_index(position) else {
+        return Color::BLACK
+    };
+    if let Some(surface) = lights.surface(index) {
+        return sample_surface(subject, lights, surface, hit, brdf, weighted)
+    }
+    let light = &lights.lights()[index];
+    let Some(sample) = light.sample(position) else {
+        return Color::BLACK
+    };
+    let eval = brdf.eval(&sample.direction);
+    if eval <= 0.0 || sample.pdf <= 0.0 {
+        return Color::BLACK
+    }
+    let shadow_ray = hit.hit.incident_ray.with_direction(sample.direction);
+    if subject.shoot(&shadow_ray, 0.0001, sample.distance * (1.0 - 1e-6)).is_some() {
+        return Color::BLACK
+    }
+    let light_pdf = probability * sample.pdf;
+    let weight = if weighted && !light.is_delta() { power_heuristic(light_pdf, brdf.pdf(&sample.direction)) } else { 1.0 };
+    seen_at(&sample.radiance, hit.hit.incident_ray.wavelength) * (eval * weight / light_pdf)
+}
+
+/// Same as `sample_lights`, for the given emissive surface, picked out of the given lights.
+fn sample_surface<T: Thing + ?Sized>(subject: &T, lights: &Lights, surface: &EmissiveSurface, hit: &MaterialHit, brdf: &dyn BRDF, weighted: bool) -> Color {
+    let position = hit.hit.incident_ray.origin;
+    let Some((point, density)) = subject.surface(surface.index)
+        .and_then(|surface| surface.sample().map(|(point, density)| (point.hit.incident_ray.origin, density))) else {
+        return Color::BLACK
+    };
+    let offset = point - position;
+    let distance = offset.length();
+    if distance <= 0.0 || density <= 0.0 {
+        return Color::BLACK
+    }
+    let direction = offset / distance;
+    let eval = brdf.eval(&direction);
+    if eval <= 0.0 {
+        return Color::BLACK
+    }
+    // the sampled point must be the first one the ray towards it hits
+    let ray = hit.hit.incident_ray.with_direction(direction);
+    let Some(light_hit) = subject.shoot(&ray, 0.0001, distance * (1.0 + 1e-4)) else {
+        return Color::BLACK
+    };
+    if light_hit.surface_index != surface.index || light_hit.hit.distance < distance * (1.0 - 1e-4) {
+        return Color::BLACK
+    }
+    let Effect::Emission(radiance) = effect_of(&light_hit) else {
+        return Color::BLACK
+    };
+    let weighting_pdf = surface_pdf(lights, &position, &light_hit);
+    if weighting_pdf <= 0.0 {
+        return Color::BLACK
+    }
+    // the weighting density assumes the average density of sampling the surface
+    let pdf = weighting_pdf * density / surface.density;
+    let weight = if weighted { power_heuristic(weighting_pdf, brdf.pdf(&direction)) } else { 1.0 };
+    radiance * (eval * weight / pdf)
+}
+
+/// The solid angle density of sampling, from the given position, the given hit, out of the given
+/// lights, if it is on one of their emissive surfaces, or zero otherwise. It takes the density of
+/// sampling the surface to be its average one, which makes it suit weighting samples only (see
+/// `power_heuristic`).
+fn surface_pdf(lights: &Lights, position: &Vec3D, hit: &MaterialHit) -> f64 {
+    let Some((index, surface)) = lights.index_of_surface(hit.surface_index) else {
+        return 0.0
+    };
+    let offset = hit.hit.incident_ray.origin - *position;
+    let distance_squared = offset.length_squared();
+    let cos = hit.hit.normal.unit().dot(offset).abs() / distance_squared.sqrt();
+    if cos <= 0.0 {
+        return 0.0
+    }
+    lights.probability(index, position) * surface.density * distance_squared / cos
+}
+
+const SURFACE_PROBES: usize = 16;
+
+/// Estimates the power of the light emitted by the given surface, out of a few samples.
+fn estimated_power(surface: &dyn Surface) -> f64 {
+    let sum: f64 = (0 .. SURFACE_PROBES)
+        .filter_map(|_| surface.sample())
+        .map(|(ref hit, pdf)| match effect_of(hit) {
+            Effect::Emission(c) if pdf > 0.0 => c.luminance() / pdf,
+            _ => 0.0,
+        })
+        .sum();
+    PI * sum / SURFACE_PROBES as f64
+}
+
+/// Returns the emissive surfaces of the given subject, whose powers, bounds, and average sampling
+/// densities are estimated out of a few points sampled on them.
+fn emissive_surfaces<T: Thing + ?Sized>(subject: &T) -> Vec<EmissiveSurface> {
+    subject.surfaces().iter().enumerate()
+        .filter_map(|(index, surface)| {
+            let power = estimated_power(surface.as_ref());
+            if power <= 0.0 {
+                return None
+            }
+            let samples: Vec<_> = (0 .. SURFACE_PROBES)
+                .filter_map(|_| surface.sample())
+                .filter(|&(_, pdf)| pdf > 0.0)
+                .map(|(hit, pdf)| (hit.hit.incident_ray.origin, pdf))
+                .collect();
+            let (first, _) = *samples.first()?;
+            let (min, max) = samples.iter().fold((first, first), |(min, max), (point, _)| (
+                Vec3D::new(min.x().min(point.x()), min.y().min(point.y()), min.z().min(point.z())),
+                Vec3D::new(max.x().max(point.x()), max.y().max(point.y()), max.z().max(point.z())),
+            ));
+            let area: f64 = samples.iter().map(|(_, pdf)| 1.0 / pdf).sum::<f64>() / samples.len() as f64;
+            Some(EmissiveSurface { index, bounds: LightBounds::omnidirectional(min, max, power), density: 1.0 / area })
+        })
+        .collect()
+}
+
+/// The weight of a sample, out of one of two sampling strategies, given the densities of sampling
+/// it with either, following the power heuristic of multiple importance sampling.
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (pdf_squared, other_pdf_squared) = (pdf * pdf, other_pdf * other_pdf);
+    if pdf_squared > 0.0 { pdf_squared / (pdf_squared + other_pdf_squared) } else { 0.0 }
 }
 
 pub type WorldFunction = fn(&Ray) -> Color;
 
-impl<W: World> World for Arc<W> {
+impl<W: World + ?Sized> World for Arc<W> {
 
     fn trace(&self, ray: &Ray) -> Color {
         self.as_ref().trace(ray)
     }
 
+    fn trace_linked(&self, ray: &Ray, link: &dyn CameraLink) -> Color {
+        self.as_ref().trace_linked(ray, link)
+    }
+
+    fn trace_sample(&self, ray: &Ray, sample: usize, link: &dyn CameraLink) -> Color {
+        self.as_ref().trace_sample(ray, sample, link)
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        self.as_ref().first_hit(ray)
+    }
+
+    fn trace_with_first_hit(&self, ray: &Ray) -> (Color, Option<FirstHit>) {
+        self.as_ref().trace_with_first_hit(ray)
+    }
+
+    fn prepare(&self, exposure: u64) {
+        self.as_ref().prepare(exposure)
+    }
+
 }
 
 impl World for WorldFunction {
@@ -37,4 +334,28 @@ impl World for Color {
         *self
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::materials::Diffusive;
+    use crate::textures::Constant;
+
+    use super::*;
+
+    #[test]
+    fn reports_depths_as_distances_along_the_rays() {
+        // Unlike a depth along the z-axis, the distance does not depend on the direction of the
+        // ray, which is what projections other than the perspective one need.
+        let sphere = Building(Sphere).with_texture(Constant(Diffusive(Color::new(0.5, 0.25, 0.125)))).done();
+        let ray = Ray::new(Vec3D::new(3.0, 0.0, 0.0), Vec3D::along_x(-2.0), Color::WHITE, 0.0);
+
+        let hit = first_hit_on(&sphere, &ray).unwrap();
+
+        assert_eq!(hit.depth, 2.0);
+        assert_eq!(hit.albedo, Color::new(0.5, 0.25, 0.125));
+    }
+
+}
@@ -1,48 +1,123 @@
-use rand::random;
+use std::sync::OnceLock;
 
 use crate::basic::colors::Color;
 use crate::basic::rays::Ray;
+use crate::basic::spectra::seen_at;
 use crate::basic::vectors::Vec3D;
 use crate::brdfs::BRDF;
-use crate::materials::{Effect, Material};
-use crate::sampling::{Space, UniformUnitSphere};
+use crate::lights::Lights;
+use crate::materials::Effect;
+use crate::sampling::{random, reseed, Space, UniformUnitSphere};
 use crate::things::{MaterialHit, Thing};
-use crate::worlds::World;
+use crate::worlds::{emissive_surfaces, environment_light, first_hit_of, first_hit_on, follow_relocations, power_heuristic, sample_lights, surface_pdf, FirstHit, Relocated, World};
 
+/// A world made of the given subject, in the given environment, where rays are traced along
+/// paths bouncing off the subject, up to the given depth. Lights, besides the environment, are
+/// sampled directly at every scattering surface, combining, using multiple importance sampling, the
+/// light sampled directly with the light hit by scattered rays. Once the world is prepared, the
+/// emissive surfaces of the subject are sampled along with the lights.
 pub struct PathTraced<W: World, T: Thing, S: ImportantDirectionSampler> {
 
     pub environment: W,
     pub subject: T,
     pub depth: u8,
-    pub directions_sampler: S
+    pub directions_sampler: S,
+    pub lights: Lights,
+    pub(crate) prepared_lights: PreparedLights,
 
 }
 
+/// The lights of a world, along with the emissive surfaces of its subject, once prepared.
+#[derive(Default)]
+pub(crate) struct PreparedLights(OnceLock<Lights>);
+
 impl<W: World, T: Thing, S: ImportantDirectionSampler> World for PathTraced<W, T, S> {
 
     fn trace(&self, ray: &Ray) -> Color {
         self.do_trace(ray, self.depth)
     }
 
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        first_hit_on(&self.subject, ray)
+    }
+
+    fn trace_with_first_hit(&self, ray: &Ray) -> (Color, Option<FirstHit>) {
+        let hit = self.subject.shoot(ray, 0.0001, f64::INFINITY);
+        let first_hit = hit.as_ref().map(|hit| first_hit_of(ray, hit));
+        let color = if self.depth > 0 { self.trace_hit(ray, hit, self.depth, None) } else { Color::BLACK };
+        (color, first_hit)
+    }
+
+    fn prepare(&self, exposure: u64) {
+        let PreparedLights(ref lights) = self.prepared_lights;
+        lights.get_or_init(|| {
+            reseed(!exposure, 0);
+            self.lights.with_surfaces(emissive_surfaces(&self.subject))
+        });
+    }
+
 }
 
 impl<W: World, T: Thing, S: ImportantDirectionSampler> PathTraced<W, T, S> {
 
+    /// The lights sampled directly, which include the emissive surfaces of the subject once the
+    /// world is prepared.
+    fn lights(&self) -> &Lights {
+        let PreparedLights(ref lights) = self.prepared_lights;
+        lights.get().unwrap_or(&self.lights)
+    }
+
     fn do_trace(&self, ray: &Ray, depth: u8) -> Color {
-        if depth > 0 {
-            match self.subject.shoot(ray, 0.0001, f64::INFINITY) {
-                Some(ref hit) => self.color_of(hit, depth),
-                None => self.environment.trace(&ray.with_origin(Vec3D::zero())),
+        self.trace_scattered(ray, depth, None)
+    }
+
+    /// Traces the given ray, which, if scattered off a surface, has the given BRDF PDF, against
+    /// which the light it hits is weighted, as that light could also have been sampled directly.
+    fn trace_scattered(&self, ray: &Ray, depth: u8, brdf_pdf: Option<f64>) -> Color {
+        if depth == 0 {
+            return Color::BLACK
+        }
+        self.trace_hit(ray, self.subject.shoot(ray, 0.0001, f64::INFINITY), depth, brdf_pdf)
+    }
+
+    /// Same as `trace_scattered`, given the hit of the ray on the subject, if any.
+    fn trace_hit(&self, ray: &Ray, hit: Option<MaterialHit>, depth: u8, brdf_pdf: Option<f64>) -> Color {
+        let max = hit.as_ref().map_or(f64::INFINITY, |hit| hit.hit.distance);
+        let lights = self.lights();
+        if let Some((index, _, radiance)) = lights.hit(ray, max) {
+            let radiance = seen_at(&radiance, ray.wavelength);
+            return match brdf_pdf {
+                Some(brdf_pdf) => {
+                    let light_pdf = lights.probability(index, &ray.origin) * lights.lights()[index].pdf(&ray.origin, &ray.direction.unit());
+                    radiance * power_heuristic(brdf_pdf, light_pdf)
+                },
+                None => radiance,
             }
-        } else {
-            Color::BLACK
+        }
+        match hit {
+            Some(hit) => self.color_of(ray, hit, depth, brdf_pdf),
+            None => environment_light(&self.environment, ray),
+        }
+    }
+
+    /// Follows the light through consecutive relocations (e.g. a random walk within a medium),
+    /// which, unlike other effects, do not count against the depth. The light of the emissive
+    /// surfaces the given ray hits directly is weighted like the light of the lights it hits.
+    fn color_of(&self, ray: &Ray, hit: MaterialHit, depth: u8, brdf_pdf: Option<f64>) -> Color {
+        match follow_relocations(&self.subject, hit) {
+            Relocated::Hit(hit, Effect::Emission(c), None) => match brdf_pdf {
+                Some(brdf_pdf) => c * power_heuristic(brdf_pdf, surface_pdf(self.lights(), &ray.origin, &hit)),
+                None => c,
+            },
+            Relocated::Hit(hit, effect, relocation) => relocation.unwrap_or(Color::WHITE) * self.color_of_effect(&hit, effect, depth),
+            Relocated::Escaped(ray, relocation) => relocation * environment_light(&self.environment, &ray),
+            Relocated::Lost => Color::BLACK,
         }
     }
 
-    fn color_of(&self, hit: &MaterialHit, depth: u8) -> Color {
-        let material_holder = hit.texture.material(&hit.hit, hit.geometry, hit.other_side_texture);
-        match material_holder.effect_of(&hit.hit) {
-            Effect::Absorption => Color::BLACK,
+    fn color_of_effect(&self, hit: &MaterialHit, effect: Effect, depth: u8) -> Color {
+        match effect {
+            Effect::Absorption | Effect::Relocation(..) => Color::BLACK,
             Effect::Emission(c) => c,
             Effect::Scattering(c, ref brdf) => c * self.scatter(hit, brdf.as_ref(), depth),
             Effect::Redirection(c, direction) => c * self.redirect(hit, &direction, depth),
@@ -51,13 +126,15 @@ impl<W: World, T: Thing, S: ImportantDirectionSampler> PathTraced<W, T, S> {
 
     fn scatter(&self, hit: &MaterialHit, brdf: &dyn BRDF, depth: u8) -> Color {
         let position = &hit.hit.incident_ray.origin;
+        let direct = sample_lights(&self.subject, self.lights(), hit, brdf, true);
         let (direction, weight) = self.directions_sampler.sample_direction_from(position, brdf);
         if weight == 0.0 {
-            return Color::BLACK
+            return direct
         }
-        let color = self.do_trace(&hit.hit.incident_ray.with_direction(direction), depth - 1);
+        let brdf_pdf = if self.lights().is_empty() { None } else { Some(brdf.pdf(&direction)) };
+        let color = self.trace_scattered(&hit.hit.incident_ray.with_direction(direction), depth - 1, brdf_pdf);
         self.directions_sampler.feedback(position, &direction, &color);
-        weight * color
+        weight * color + direct
     }
 
     fn redirect(&self, hit: &MaterialHit, direction: &Vec3D, depth: u8) -> Color {
@@ -82,7 +159,7 @@ pub trait ImportantDirectionSampler: Send + Sync {
             (direction, dir_pdf, brdf_pdf)
         };
         let pdf = narrowness * brdf_pdf + (1.0 - narrowness) * dir_pdf;
-        (direction, brdf_pdf / pdf)
+        (direction, if pdf > 0.0 { brdf.eval(&direction) / pdf } else { 0.0 })
     }
 
     fn important_directions_at(&self, position: &Vec3D) -> Box<dyn Space<Vec3D>>;
@@ -96,7 +173,8 @@ pub struct Omnidirectional;
 impl ImportantDirectionSampler for Omnidirectional {
 
     fn sample_direction_from(&self, _: &Vec3D, brdf: &dyn BRDF) -> (Vec3D, f64) {
-        (brdf.arbitrary_sample(), 1.0)
+        let (direction, pdf) = brdf.arbitrary_sample_and_pdf();
+        (direction, if pdf > 0.0 { brdf.eval(&direction) / pdf } else { 0.0 })
     }
 
     fn important_directions_at(&self, _: &Vec3D) -> Box<dyn Space<Vec3D>> {
@@ -107,3 +185,93 @@ impl ImportantDirectionSampler for Omnidirectional {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::lights::SphereLight;
+    use crate::materials::{Diffusive, Emissive};
+    use crate::textures::Constant;
+    use crate::things::Things;
+    use crate::transforms::{Linear, Translation};
+
+    use super::*;
+
+    fn glowing_spheres_over_a_floor() -> PathTraced<Color, Things, Omnidirectional> {
+        let floor = Building(Sphere)
+            .transformed(Linear::omni_scaling(100.0))
+            .transformed(Translation::new(0.0, -100.0, 0.0))
+            .with_outer_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+            .boxed();
+        let spheres = (0 .. 64).map(|i| Building(Sphere)
+            .transformed(Linear::omni_scaling(0.1))
+            .transformed(Translation::new((i % 8) as f64 * 0.5 - 1.75, 1.0, (i / 8) as f64 * 0.5 - 1.75))
+            .with_outer_texture(Constant(Emissive(Color::new(10.0, 10.0, 10.0))))
+            .boxed());
+        Building(Things(std::iter::once(floor as Box<dyn Thing>).chain(spheres.map(|sphere| sphere as Box<dyn Thing>)).collect()))
+            .path_traced()
+            .with_depth(2)
+            .done()
+    }
+
+    /// Returns the mean and the variance of the luminance of the given ray, traced many times.
+    fn statistics<W: World>(world: &W, ray: &Ray) -> (f64, f64) {
+        let count = 20000;
+        let samples: Vec<_> = (0 .. count).map(|_| world.trace(ray).luminance()).collect();
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        let variance = samples.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / count as f64;
+        (mean, variance)
+    }
+
+    #[test]
+    fn samples_emissive_surfaces_directly() {
+        // Sampling the emissive spheres lighting the floor, once the world is prepared, gives the
+        // light that rays bouncing off the floor only hit by chance otherwise, with less noise.
+        let ray = Ray::new(Vec3D::new(0.1, 0.5, 0.2), -Vec3D::Y, Color::WHITE, 0.0);
+        let hit_only = glowing_spheres_over_a_floor();
+        let sampled = glowing_spheres_over_a_floor();
+        sampled.prepare(0);
+
+        let (mean, variance) = statistics(&hit_only, &ray);
+        let (sampled_mean, sampled_variance) = statistics(&sampled, &ray);
+
+        assert!((sampled_mean - mean).abs() < 0.05 * mean, "{} vs {}", sampled_mean, mean);
+        assert!(sampled_variance < variance / 2.0, "{} vs {}", sampled_variance, variance);
+    }
+
+    #[test]
+    fn weights_lights_sampled_and_hit_into_the_same_light() {
+        // A sphere light lighting the floor, sampled directly and hit by the rays bouncing off the
+        // floor, gives the same light as an emissive sphere in its place, which is only hit.
+        let floor = || Building(Sphere)
+            .transformed(Linear::omni_scaling(100.0))
+            .transformed(Translation::new(0.0, -100.0, 0.0))
+            .with_outer_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+            .boxed();
+        let ray = Ray::new(Vec3D::new(0.1, 0.5, 0.2), -Vec3D::Y, Color::WHITE, 0.0);
+        let lit = Building(Things(vec![floor()]))
+            .path_traced()
+            .with_depth(2)
+            .with_lights(Lights::new(vec![Arc::new(SphereLight::new(Vec3D::new(0.0, 1.0, 0.0), 0.5, Color::new(10.0, 10.0, 10.0)))]))
+            .done();
+        let glowing = Building(Sphere)
+            .transformed(Linear::omni_scaling(0.5))
+            .transformed(Translation::new(0.0, 1.0, 0.0))
+            .with_outer_texture(Constant(Emissive(Color::new(10.0, 10.0, 10.0))))
+            .boxed();
+        let hit_only = Building(Things(vec![floor(), glowing]))
+            .path_traced()
+            .with_depth(2)
+            .done();
+
+        let (mean, variance) = statistics(&hit_only, &ray);
+        let (lit_mean, lit_variance) = statistics(&lit, &ray);
+
+        assert!((lit_mean - mean).abs() < 0.05 * mean, "{} vs {}", lit_mean, mean);
+        assert!(lit_variance < variance, "{} vs {}", lit_variance, variance);
+    }
+
+}
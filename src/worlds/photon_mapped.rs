@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex, RwLock};
+
+use rayon::prelude::*;
+
+use crate::basic::colors::Color;
+use crate::basic::rays::Ray;
+use crate::basic::spectra::seen_at;
+use crate::basic::vectors::{Dot, Vec3D};
+use crate::brdfs::{Lambertian, BRDF};
+use crate::lights::Lights;
+use crate::materials::Effect;
+use crate::sampling::{random, reseed, Space};
+use crate::things::{MaterialHit, Surface, Thing};
+use crate::viewing::CameraLink;
+use crate::worlds::{effect_of, environment_light, estimated_power, first_hit_on, follow_relocations, sample_lights, FirstHit, Relocated, World};
+
+/// A world made of the given subject, in the given environment, where light is traced using
+/// stochastic progressive photon mapping (Hachisuka and Jensen, 2009). Every exposure (see
+/// `World::prepare`) emits the given number of photons, from the lights and from the emissive
+/// surfaces of the subject, and stores them where they scatter, along paths of up to the given
+/// depth. Camera rays follow mirrors, glass, and the like, up to the first scattering surface, where
+/// the light of the photons within a radius is gathered, besides the light sampled directly from
+/// the lights. Every sample of the exposures (see `World::trace_sample`) keeps count of the photons
+/// it gathered, and of their flux, over the exposures, within a radius starting at the given one,
+/// and shrinking as photons accumulate, so that averaging the exposures converges to the right
+/// image, caustics included. Rays traced otherwise (e.g. by spectral sensors) gather photons within
+/// a radius shrinking with every exposure instead, following the schedule of probabilistic
+/// progressive photon mapping (Knaus and Zwicker, 2011), which converges as well.
+///
+/// Only the lights that could emit light (see `Light::emit`), and the emissive surfaces of things
+/// whose geometries could sample them, emit photons. The other lights (e.g. directional lights),
+/// and the environment, light the scattering surfaces seen by the camera directly only.
+pub struct PhotonMapped<W: World, T: Thing> {
+
+    pub environment: W,
+    pub subject: T,
+    pub depth: u8,
+    pub lights: Lights,
+    pub photons: usize,
+    pub radius: f64,
+    pub(crate) map: PhotonMapCache,
+    pub(crate) visible_points: VisiblePoints,
+
+}
+
+impl<W: World, T: Thing> World for PhotonMapped<W, T> {
+
+    fn trace(&self, ray: &Ray) -> Color {
+        let map = self.map();
+        let radius = self.radius_of(map.exposure);
+        self.gather(ray, |hit, brdf, beta| {
+            let (_, flux) = map.gather(hit, brdf, radius);
+            beta * flux / (PI * radius * radius)
+        })
+    }
+
+    fn trace_sample(&self, ray: &Ray, sample: usize, _: &dyn CameraLink) -> Color {
+        let map = self.map();
+        let mut point = self.visible_points.get(sample).unwrap_or(VisiblePoint { photons: 0.0, flux: Color::BLACK, radius: self.radius, passes: 0 });
+        let mut gathered = (0, Color::BLACK);
+        let color = self.gather(ray, |hit, brdf, beta| {
+            let (count, flux) = map.gather(hit, brdf, point.radius);
+            gathered = (count, beta * flux);
+            Color::BLACK
+        });
+        point.update(gathered.0, gathered.1);
+        self.visible_points.set(sample, point);
+        color + point.radiance()
+    }
+
+    fn first_hit(&self, ray: &Ray) -> Option<FirstHit> {
+        first_hit_on(&self.subject, ray)
+    }
+
+    fn prepare(&self, exposure: u64) {
+        if exposure == 0 {
+            self.visible_points.clear();
+        }
+        let map = Arc::new(self.emit_photons(exposure));
+        *self.map.0.write().unwrap() = Some(map);
+    }
+
+}
+
+/// The photon map of the latest exposure, if any.
+#[derive(Default)]
+pub(crate) struct PhotonMapCache(RwLock<Option<Arc<PhotonMap>>>);
+
+/// The statistics of the samples of the exposures, by index, in shards locked separately.
+pub(crate) struct VisiblePoints(Vec<Mutex<HashMap<usize, VisiblePoint>>>);
+
+/// What a sample of the exposures gathered so far, at the first scattering surfaces its rays hit.
+#[derive(Clone, Copy)]
+struct VisiblePoint {
+    /// The number of photons gathered, discounting the ones left out by shrinking the radius.
+    photons: f64,
+    /// The flux of the photons within the radius, weighted by the throughput of the camera rays.
+    flux: Color,
+    radius: f64,
+    passes: u64,
+}
+
+/// Photons stored where they scattered, in a hash grid of cells as wide as twice the initial
+/// gathering radius, so that gathering around a point looks into 8 cells at most.
+struct PhotonMap {
+    exposure: u64,
+    radius: f64,
+    emitted: usize,
+    cells: HashMap<(i64, i64, i64), Vec<Photon>>,
+}
+
+struct Photon {
+    position: Vec3D,
+    /// The unit direction the photon came from.
+    direction: Vec3D,
+    /// The unit normal of the surface the photon scattered off, on the side it came from.
+    normal: Vec3D,
+    power: Color,
+}
+
+impl VisiblePoints {
+
+    const SHARDS: usize = 64;
+
+    fn get(&self, sample: usize) -> Option<VisiblePoint> {
+        self.0[sample % Self::SHARDS].lock().unwrap().get(&sample).copied()
+    }
+
+    fn set(&self, sample: usize, point: VisiblePoint) {
+        self.0[sample % Self::SHARDS].lock().unwrap().insert(sample, point);
+    }
+
+    fn clear(&self) {
+        for shard in self.0.iter() {
+            shard.lock().unwrap().clear();
+        }
+    }
+
+}
+
+impl Default for VisiblePoints {
+
+    fn default() -> Self {
+        Self((0 .. Self::SHARDS).map(|_| Mutex::default()).collect())
+    }
+
+}
+
+impl VisiblePoint {
+
+    /// Adds the given number of photons gathered in a pass, and their flux, keeping a fraction of
+    /// the new photons, and shrinking the radius so that it holds as many photons as kept.
+    fn update(&mut self, count: usize, flux: Color) {
+        let count = count as f64;
+        if count > 0.0 {
+            let photons = self.photons + ALPHA * count;
+            let ratio = photons / (self.photons + count);
+            self.flux = (self.flux + flux) * ratio;
+            self.radius *= ratio.sqrt();
+            self.photons = photons;
+        }
+        self.passes += 1;
+    }
+
+    /// The light scattered at the visible points, averaged over the passes.
+    fn radiance(&self) -> Color {
+        self.flux / (self.passes as f64 * PI * self.radius * self.radius)
+    }
+
+}
+
+/// The fraction of the photons that the radius keeps gathering from one pass to the next.
+const ALPHA: f64 = 2.0 / 3.0;
+
+impl PhotonMap {
+
+    fn new(exposure: u64, radius: f64, emitted: usize, photons: Vec<Photon>) -> Self {
+        let mut map = Self { exposure, radius, emitted, cells: HashMap::new() };
+        for photon in photons {
+            map.cells.entry(map.cell_of(&photon.position)).or_default().push(photon);
+        }
+        map
+    }
+
+    fn cell_of(&self, position: &Vec3D) -> (i64, i64, i64) {
+        let size = 2.0 * self.radius;
+        ((position.x() / size).floor() as i64, (position.y() / size).floor() as i64, (position.z() / size).floor() as i64)
+    }
+
+    /// Calls the given function with every photon within the given radius, at most the initial one,
+    /// of the given position.
+    fn for_each_near<F: FnMut(&Photon)>(&self, position: &Vec3D, radius: f64, mut f: F) {
+        let offset = Vec3D::new(radius, radius, radius);
+        let (x0, y0, z0) = self.cell_of(&(position - &offset));
+        let (x1, y1, z1) = self.cell_of(&(position + &offset));
+        let radius_squared = radius * radius;
+        for x in x0 ..= x1 {
+            for y in y0 ..= y1 {
+                for z in z0 ..= z1 {
+                    let Some(photons) = self.cells.get(&(x, y, z)) else {
+                        continue
+                    };
+                    for photon in photons.iter().filter(|photon| (photon.position - *position).length_squared() <= radius_squared) {
+                        f(photon)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the number of photons within the given radius of the given hit, along with their
+    /// flux, per photon emitted, as scattered according to the given BRDF.
+    fn gather(&self, hit: &MaterialHit, brdf: &dyn BRDF, radius: f64) -> (usize, Color) {
+        let position = hit.hit.incident_ray.origin;
+        let normal = hit.hit.normal.unit();
+        let (mut count, mut sum) = (0, Color::BLACK);
+        self.for_each_near(&position, radius, |photon| {
+            let cos = normal.dot(photon.direction).abs();
+            if photon.normal.dot(normal) > 0.5 && cos > 1e-6 {
+                count += 1;
+                sum += seen_at(&photon.power, hit.hit.incident_ray.wavelength) * (brdf.eval(&photon.direction) / cos);
+            }
+        });
+        (count, sum / self.emitted as f64)
+    }
+
+}
+
+/// Where photons get emitted from, picked in proportion to their (estimated) powers.
+enum Source<'a> {
+    Light(usize),
+    Surface(&'a dyn Surface),
+}
+
+impl<W: World, T: Thing> PhotonMapped<W, T> {
+
+    const PHOTONS_PER_TASK: usize = 1024;
+
+    /// Returns the photon map of the latest exposure, emitting the photons of a first exposure if
+    /// there was none.
+    fn map(&self) -> Arc<PhotonMap> {
+        if let Some(ref map) = *self.map.0.read().unwrap() {
+            return map.clone()
+        }
+        let mut map = self.map.0.write().unwrap();
+        map.get_or_insert_with(|| Arc::new(self.emit_photons(0))).clone()
+    }
+
+    /// The radius of the given exposure for the rays traced without the index of their sample,
+    /// whose square shrinks like `i^(α - 1)` for the `i`-th exposure.
+    fn radius_of(&self, exposure: u64) -> f64 {
+        self.radius * ((exposure + 1) as f64).powf((ALPHA - 1.0) / 2.0)
+    }
+
+    fn emit_photons(&self, exposure: u64) -> PhotonMap {
+        let surfaces = self.subject.surfaces();
+        let mut sources = Vec::new();
+        let mut cumulative_powers = Vec::new();
+        let mut total = 0.0;
+        let light_powers = self.lights.lights().iter().map(|light| light.power());
+        let surface_powers = surfaces.iter().map(|surface| estimated_power(surface.as_ref()));
+        let candidates = (0 ..).map(Source::Light).zip(light_powers)
+            .chain(surfaces.iter().map(|surface| Source::Surface(surface.as_ref())).zip(surface_powers));
+        for (source, power) in candidates.filter(|(_, power)| *power > 0.0) {
+            total += power;
+            sources.push((source, power));
+            cumulative_powers.push(total);
+        }
+        if sources.is_empty() {
+            return PhotonMap::new(exposure, self.radius, self.photons, Vec::new())
+        }
+        let tasks = self.photons.div_ceil(Self::PHOTONS_PER_TASK);
+        let photons = (0 .. tasks).into_par_iter()
+            .flat_map_iter(|task| {
+                reseed(!exposure, task as u64);
+                let count = Self::PHOTONS_PER_TASK.min(self.photons - task * Self::PHOTONS_PER_TASK);
+                let mut photons = Vec::new();
+                for _ in 0 .. count {
+                    let dice = random::<f64>() * total;
+                    let index = cumulative_powers.partition_point(|&power| power <= dice).min(sources.len() - 1);
+                    let (ref source, power) = sources[index];
+                    self.emit_photon(source, power / total, &mut photons);
+                }
+                photons
+            })
+            .collect();
+        PhotonMap::new(exposure, self.radius, self.photons, photons)
+    }
+
+    /// Emits a photon from the given source, picked with the given probability, storing it
+    /// wherever it scatters.
+    fn emit_photon(&self, source: &Source, probability: f64, photons: &mut Vec<Photon>) {
+        match *source {
+            Source::Light(index) => {
+                let Some(emission) = self.lights.lights()[index].emit() else {
+                    return
+                };
+                let pdf = probability * emission.pdf_position * emission.pdf_direction;
+                if pdf <= 0.0 {
+                    return
+                }
+                let cos = emission.normal.map_or(1.0, |normal| normal.dot(emission.direction).abs());
+                let ray = Ray::new(emission.origin, emission.direction, Color::WHITE, 0.0);
+                // the light arriving directly from the lights is sampled at the camera hits instead
+                self.trace_photon(ray, emission.radiance * (cos / pdf), false, photons);
+            },
+            Source::Surface(surface) => {
+                let Some((hit, pdf)) = surface.sample() else {
+                    return
+                };
+                let Effect::Emission(radiance) = effect_of(&hit) else {
+                    return
+                };
+                let normal = hit.hit.normal.unit();
+                let direction = Lambertian::new(&normal).arbitrary_sample();
+                let ray = hit.hit.incident_ray.with_direction(direction);
+                // cosine weighted directions leave a factor of pi
+                self.trace_photon(ray, radiance * (PI / (probability * pdf)), true, photons);
+            },
+        }
+    }
+
+    /// Traces a photon of the given power along the given ray, storing it wherever it scatters,
+    /// except at the first surface it reaches, unless told otherwise.
+    fn trace_photon(&self, ray: Ray, power: Color, store_first: bool, photons: &mut Vec<Photon>) {
+        let (mut ray, mut power) = (ray, power);
+        let mut store = store_first;
+        for _ in 0 .. self.depth {
+            let Some(hit) = self.subject.shoot(&ray, 0.0001, f64::INFINITY) else {
+                return
+            };
+            let Relocated::Hit(hit, effect, relocation) = follow_relocations(&self.subject, hit) else {
+                return
+            };
+            if let Some(c) = relocation {
+                power *= c;
+                store = true;
+            }
+            let (c, direction) = match effect {
+                Effect::Scattering(c, brdf) => {
+                    let incident = hit.hit.incident_ray.direction.unit();
+                    if store {
+                        photons.push(Photon {
+                            position: hit.hit.incident_ray.origin,
+                            direction: -incident,
+                            normal: hit.hit.normal.unit(),
+                            power,
+                        });
+                    }
+                    let (direction, pdf) = brdf.arbitrary_sample_and_pdf();
+                    if pdf <= 0.0 {
+                        return
+                    }
+                    (c * (brdf.eval(&direction) / pdf), direction)
+                },
+                Effect::Redirection(c, direction) => (c, direction),
+                _ => return,
+            };
+            // Russian roulette keeps the photons of a pass at roughly the same power
+            let survival = (power * c).luminance() / power.luminance();
+            if survival.is_nan() || survival <= 0.0 || (survival < 1.0 && random::<f64>() >= survival) {
+                return
+            }
+            power = power * c / survival.min(1.0);
+            ray = hit.hit.incident_ray.with_direction(direction);
+            store = true;
+        }
+    }
+
+    /// Traces the given camera ray up to the first scattering surface, where the light arriving
+    /// from the lights and the environment is sampled, and the light of the photons is gathered
+    /// by the given function, given the hit, its BRDF, and the throughput of the ray.
+    fn gather<F: FnOnce(&MaterialHit, &dyn BRDF, Color) -> Color>(&self, ray: &Ray, gather_photons: F) -> Color {
+        let mut ray = ray.clone();
+        let mut beta = Color::WHITE;
+        for _ in 0 .. self.depth {
+            let hit = self.subject.shoot(&ray, 0.0001, f64::INFINITY);
+            let max = hit.as_ref().map_or(f64::INFINITY, |hit| hit.hit.distance);
+            if let Some((_, _, radiance)) = self.lights.hit(&ray, max) {
+                return beta * seen_at(&radiance, ray.wavelength)
+            }
+            let Some(hit) = hit else {
+                return beta * environment_light(&self.environment, &ray)
+            };
+            let (hit, effect, relocation) = match follow_relocations(&self.subject, hit) {
+                Relocated::Hit(hit, effect, relocation) => (hit, effect, relocation),
+                Relocated::Escaped(ray, relocation) => return beta * relocation * environment_light(&self.environment, &ray),
+                Relocated::Lost => return Color::BLACK,
+            };
+            if let Some(c) = relocation {
+                beta *= c;
+            }
+            match effect {
+                Effect::Emission(c) => return beta * c,
+                Effect::Redirection(c, direction) => {
+                    beta *= c;
+                    ray = hit.hit.incident_ray.with_direction(direction);
+                },
+                Effect::Scattering(c, brdf) => {
+                    let direct = sample_lights(&self.subject, &self.lights, &hit, brdf.as_ref(), false) + self.sample_environment(&hit, brdf.as_ref());
+                    return beta * c * direct + gather_photons(&hit, brdf.as_ref(), beta * c)
+                },
+                _ => return Color::BLACK,
+            }
+        }
+        Color::BLACK
+    }
+
+    /// Samples the light arriving directly from the environment at the given hit, and scattering
+    /// according to the given BRDF.
+    fn sample_environment(&self, hit: &MaterialHit, brdf: &dyn BRDF) -> Color {
+        let (direction, pdf) = brdf.arbitrary_sample_and_pdf();
+        if pdf <= 0.0 {
+            return Color::BLACK
+        }
+        let ray = hit.hit.incident_ray.with_direction(direction);
+        if self.subject.shoot(&ray, 0.0001, f64::INFINITY).is_some() || self.lights.hit(&ray, f64::INFINITY).is_some() {
+            return Color::BLACK
+        }
+        environment_light(&self.environment, &ray) * (brdf.eval(&direction) / pdf)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builders::Building;
+    use crate::geometries::Sphere;
+    use crate::materials::{Diffusive, Emissive};
+    use crate::textures::Constant;
+    use crate::things::Things;
+    use crate::transforms::Linear;
+    use crate::viewing::LensLink;
+
+    use super::*;
+
+    #[test]
+    fn gathers_photons_emitted_by_emissive_things() {
+        // The inside of a sphere of radius 2 sees an emissive sphere, of radius 0.5, at its
+        // center, which makes it reflect a sixteenth of the radiance of the emissive sphere, times
+        // its albedo.
+        let things = Things(vec![
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(2.0))
+                .with_inner_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+                .boxed(),
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(0.5))
+                .with_outer_texture(Constant(Emissive(Color::new(16.0, 16.0, 16.0))))
+                .boxed(),
+        ]);
+        let world = Building(things)
+            .photon_mapped()
+            .with_depth(1)
+            .with_photons(20000)
+            .with_radius(0.5)
+            .done();
+        let ray = Ray::new(Vec3D::new(0.0, 1.0, 0.0), Vec3D::X, Color::WHITE, 0.0);
+        let count = 20;
+        let average = (0..count).fold(Color::BLACK, |sum, exposure| {
+            world.prepare(exposure);
+            sum + world.trace(&ray)
+        }) / count as f64;
+        assert!((average.luminance() - 0.5).abs() < 0.03, "{:?}", average);
+    }
+
+    #[test]
+    fn shrinks_the_radius_of_every_sample_as_it_gathers_photons() {
+        // Same as above, except that the sample keeps its statistics from one exposure to the
+        // next, and estimates the light out of all the photons it gathered.
+        let things = Things(vec![
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(2.0))
+                .with_inner_texture(Constant(Diffusive(Color::new(0.5, 0.5, 0.5))))
+                .boxed(),
+            Building(Sphere)
+                .transformed(Linear::omni_scaling(0.5))
+                .with_outer_texture(Constant(Emissive(Color::new(16.0, 16.0, 16.0))))
+                .boxed(),
+        ]);
+        let world = Building(things)
+            .photon_mapped()
+            .with_depth(1)
+            .with_photons(20000)
+            .with_radius(0.5)
+            .done();
+        let ray = Ray::new(Vec3D::new(0.0, 1.0, 0.0), Vec3D::X, Color::WHITE, 0.0);
+        let mut color = Color::BLACK;
+        for exposure in 0 .. 20 {
+            world.prepare(exposure);
+            color = world.trace_sample(&ray, 7, &Unlinked);
+        }
+        let point = world.visible_points.get(7).unwrap();
+        assert_eq!(point.passes, 20);
+        assert!(point.radius < 0.4, "{}", point.radius);
+        assert!((color.luminance() - 0.5).abs() < 0.03, "{:?}", color);
+        assert!(world.visible_points.get(8).is_none());
+    }
+
+    struct Unlinked;
+
+    impl CameraLink for Unlinked {
+
+        fn link(&self, _: &Vec3D) -> Option<LensLink> {
+            None
+        }
+
+        fn pdf(&self, _: &Vec3D, _: &Vec3D) -> f64 {
+            0.0
+        }
+
+        fn splat(&self, _: &LensLink, _: &Color) {
+        }
+
+    }
+
+}